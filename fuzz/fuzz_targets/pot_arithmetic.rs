@@ -0,0 +1,95 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use cosmwasm_std::Uint128;
+use libfuzzer_sys::fuzz_target;
+use nft_staking_lottery::{add_to_pot, split_fee, split_jackpot, split_prize};
+
+#[derive(Arbitrary, Debug)]
+struct PotInput {
+    pot: u128,
+    fundings: Vec<u128>,
+    fee_bps: u16,
+    claim: u128,
+    winner_share: u128,
+    consolation_bps: Vec<u16>,
+    jackpot_bps: u16,
+    rounds: Vec<(u128, bool)>,
+}
+
+fuzz_target!(|input: PotInput| {
+    // Funding must either add exactly or report overflow, never wrap
+    let mut pot = Uint128::new(input.pot);
+    for amount in input.fundings {
+        let amount = Uint128::new(amount);
+        match add_to_pot(pot, amount) {
+            Ok(new_pot) => {
+                assert!(new_pot >= pot);
+                assert_eq!(new_pot - pot, amount);
+                pot = new_pot;
+            }
+            Err(_) => assert!(pot.u128().checked_add(amount.u128()).is_none()),
+        }
+    }
+
+    // Funding on top of Uint128::MAX must be rejected unless it adds nothing
+    let maxed = add_to_pot(Uint128::MAX, pot);
+    assert_eq!(maxed.is_ok(), pot.is_zero());
+
+    // Fee and net amount always add back up to the total
+    match split_fee(pot, input.fee_bps) {
+        Ok((fee, net)) => {
+            assert!(input.fee_bps <= 10_000);
+            assert!(fee <= pot);
+            assert_eq!(fee + net, pot);
+        }
+        Err(_) => assert!(input.fee_bps > 10_000),
+    }
+
+    // Claiming from the pot can never take it below zero
+    let claim = Uint128::new(input.claim);
+    match pot.checked_sub(claim) {
+        Ok(remaining) => assert_eq!(remaining + claim, pot),
+        Err(_) => assert!(claim > pot),
+    }
+
+    // Consolation places never take more than the winner share reserved for the draw;
+    // first place gets whatever the other places leave, dust included
+    let mut places = vec![10_000u16];
+    for bps in input.consolation_bps {
+        let bps = bps % places[0];
+        if bps > 0 {
+            places[0] -= bps;
+            places.push(bps);
+        }
+    }
+    let reserved = Uint128::new(input.winner_share);
+    let shares = split_prize(reserved, &places);
+    assert_eq!(shares.len(), places.len());
+    let consolation = shares[1..].iter().copied().sum::<Uint128>();
+    assert!(consolation <= reserved);
+    assert_eq!(shares[0] + consolation, reserved);
+
+    // The jackpot carries over exactly what each round put in until a round hits
+    let share_bps = input.jackpot_bps % 10_001;
+    let mut jackpot = Uint128::zero();
+    let mut contributed = Uint128::zero();
+    let mut paid = Uint128::zero();
+    for (available, hit) in input.rounds {
+        let available = Uint128::new(available);
+        let (contribution, drawn) = split_jackpot(available, share_bps);
+        assert_eq!(contribution + drawn, available);
+        let Ok(carried) = jackpot.checked_add(contribution) else {
+            break;
+        };
+        let Ok(total) = contributed.checked_add(contribution) else {
+            break;
+        };
+        jackpot = carried;
+        contributed = total;
+        if hit {
+            paid += std::mem::take(&mut jackpot);
+        }
+        assert_eq!(paid + jackpot, contributed);
+    }
+});
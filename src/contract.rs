@@ -0,0 +1,553 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+    Uint128,
+};
+
+use crate::error::ContractError;
+use crate::execute::*;
+use crate::migrations::{
+    clear_staker_set, index_staker_positions, migrate_custody_collections,
+    migrate_min_staking_period, migrate_stakers_key_format, migrate_state_v1_to_v2,
+    seed_total_power, seed_unique_stakers,
+};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
+use crate::query::{
+    query_badge_contract, query_burn_leaderboard, query_collections, query_config,
+    query_contract_info, query_dao, query_dashboard, query_draw_exclusions, query_draw_snapshot,
+    query_draw_task, query_eligible_count, query_eligible_in, query_eligible_stakers,
+    query_full_draw_snapshot, query_hooks, query_info, query_interval_votes, query_is_stakeable,
+    query_jackpot, query_leaderboard, query_lifetime_winnings, query_list_stakers,
+    query_list_winners, query_merkle_claimed, query_merkle_distribution, query_next_draw,
+    query_odds, query_ownership, query_paused, query_pending_min_staking_period,
+    query_pending_protocol_fee, query_pending_rewards, query_prize_leaderboard,
+    query_prove_eligibility, query_round, query_simulate_draw, query_stake_cap, query_staked_tokens,
+    query_staker, query_staker_collections, query_staker_history, query_staker_settings,
+    query_stakers_by_weight, query_staking_rewards, query_state, query_stats, query_tickets,
+    query_tier_pots, query_total_power_at_height, query_unbonding_claims,
+    query_voting_power_at_height,
+};
+use crate::state::{
+    CommitRevealConfig, Config, CronCatConfig, PausableAction, PotSplit, State, CONFIG,
+    DEFAULT_ACCELERATED_COOLDOWN, DEFAULT_MAX_DRAW_INTERVAL, DEFAULT_MIN_DRAW_INTERVAL,
+    DRAW_EXCLUSIONS, MIN_STAKING_PERIOD, SECONDS_IN_DAY, STATE, TOTAL_POWER,
+};
+
+const CONTRACT_NAME: &str = "crates.io:burn-at-the-stake";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if msg.weight_decay_rate_bps_per_week > 10_000 {
+        return Err(ContractError::InvalidBasisPoints {
+            field: "weight_decay_rate_bps_per_week".to_string(),
+        });
+    }
+    if msg.max_win_probability_bps > 10_000 {
+        return Err(ContractError::InvalidBasisPoints {
+            field: "max_win_probability_bps".to_string(),
+        });
+    }
+    if msg.keeper_reward_bps > 10_000 {
+        return Err(ContractError::InvalidBasisPoints {
+            field: "keeper_reward_bps".to_string(),
+        });
+    }
+    if msg.sacrifice_bps > 10_000 {
+        return Err(ContractError::InvalidBasisPoints {
+            field: "sacrifice_bps".to_string(),
+        });
+    }
+
+    let min_draw_interval_seconds =
+        msg.min_draw_interval_seconds.unwrap_or(DEFAULT_MIN_DRAW_INTERVAL);
+    let max_draw_interval_seconds =
+        msg.max_draw_interval_seconds.unwrap_or(DEFAULT_MAX_DRAW_INTERVAL);
+    if min_draw_interval_seconds > max_draw_interval_seconds {
+        return Err(ContractError::InvalidDrawIntervalBounds {});
+    }
+    let randomness_sources = [
+        msg.nois_proxy.is_some(),
+        msg.drand.is_some(),
+        msg.commit_reveal.is_some(),
+    ];
+    if randomness_sources.iter().filter(|set| **set).count() > 1 {
+        return Err(ContractError::ConflictingRandomnessSources {});
+    }
+    if let Some(drand) = &msg.drand {
+        if drand.pubkey.len() != 48 || drand.period_seconds == 0 {
+            return Err(ContractError::InvalidDrandConfig {});
+        }
+    }
+    if let Some(commit_reveal) = &msg.commit_reveal {
+        let min_reveals = commit_reveal.min_reveals as usize;
+        if min_reveals == 0 || min_reveals > commit_reveal.contributors.len() {
+            return Err(ContractError::InvalidCommitRevealConfig {});
+        }
+    }
+    if !msg.prize_split_bps.is_empty()
+        && (msg.prize_split_bps.contains(&0)
+            || msg.prize_split_bps.iter().map(|bps| *bps as u32).sum::<u32>() != 10_000)
+    {
+        return Err(ContractError::InvalidPrizeSplit {});
+    }
+    if matches!(&msg.duration_weighting, Some(weighting) if weighting.period_seconds == 0) {
+        return Err(ContractError::InvalidDurationWeighting {});
+    }
+    validate_lock_tiers(&msg.lock_tiers)?;
+    validate_pot_split(&msg.pot_split)?;
+    if matches!(&msg.jackpot, Some(jackpot) if jackpot.share_bps > 10_000 || jackpot.odds == 0) {
+        return Err(ContractError::InvalidJackpotConfig {});
+    }
+    if matches!(&msg.stake_fee, Some(fee) if fee.amount.is_zero()) {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let treasury = msg
+        .treasury
+        .as_deref()
+        .map(|address| deps.api.addr_validate(address))
+        .transpose()?;
+    validate_protocol_fee(msg.protocol_fee_bps, &treasury)?;
+
+    let config = Config {
+        admin: deps.api.addr_validate(&msg.admin)?,
+        nft_contract: deps.api.addr_validate(&msg.nft_contract)?,
+        reward_token: deps.api.addr_validate(&msg.reward_token)?,
+        activity_timeout_seconds: msg.activity_timeout_seconds,
+        weight_decay_rate_bps_per_week: msg.weight_decay_rate_bps_per_week,
+        min_draw_interval_seconds,
+        max_draw_interval_seconds,
+        badge_nft_contract: msg
+            .badge_nft_contract
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?,
+        target_pot_size: msg.target_pot_size.unwrap_or_default(),
+        accelerated_cooldown_seconds: msg
+            .accelerated_cooldown_seconds
+            .unwrap_or(DEFAULT_ACCELERATED_COOLDOWN),
+        abandonment_timeout_seconds: msg.abandonment_timeout_seconds.unwrap_or_default(),
+        max_win_probability_bps: msg.max_win_probability_bps,
+        stake_receipt_contract: msg
+            .stake_receipt_contract
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?,
+        min_staking_period: validate_min_staking_period(
+            msg.min_staking_period.unwrap_or(MIN_STAKING_PERIOD),
+        )?,
+        duration_weighting: msg.duration_weighting,
+        nois_proxy: msg
+            .nois_proxy
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?,
+        drand: msg.drand,
+        commit_reveal: msg
+            .commit_reveal
+            .map(|commit_reveal| -> StdResult<_> {
+                Ok(CommitRevealConfig {
+                    contributors: commit_reveal
+                        .contributors
+                        .iter()
+                        .map(|address| deps.api.addr_validate(address))
+                        .collect::<StdResult<_>>()?,
+                    commit_window_seconds: commit_reveal.commit_window_seconds,
+                    reveal_window_seconds: commit_reveal.reveal_window_seconds,
+                    min_reveals: commit_reveal.min_reveals,
+                })
+            })
+            .transpose()?,
+        claim_window_seconds: msg.claim_window_seconds.unwrap_or_default(),
+        prize_split_bps: msg.prize_split_bps,
+        prize_tiers: vec![],
+        dao: Some(info.sender),
+        unbonding_seconds: msg.unbonding_seconds.unwrap_or_default(),
+        early_unstake_penalty: msg.early_unstake_penalty.unwrap_or_default(),
+        keeper_reward_bps: msg.keeper_reward_bps,
+        croncat: msg
+            .croncat
+            .map(|croncat| -> StdResult<_> {
+                Ok(CronCatConfig {
+                    tasks: deps.api.addr_validate(&croncat.tasks)?,
+                    manager: deps.api.addr_validate(&croncat.manager)?,
+                })
+            })
+            .transpose()?,
+        burn_multiplier: msg.burn_multiplier,
+        sacrifice_bps: msg.sacrifice_bps,
+        allow_wallet_stakes: msg.allow_wallet_stakes,
+        lock_tiers: msg.lock_tiers,
+        winner_cooldown_rounds: msg.winner_cooldown_rounds,
+        max_nfts_per_staker: msg.max_nfts_per_staker,
+        max_entries_per_round: msg.max_entries_per_round,
+        stake_fee: msg.stake_fee,
+        protocol_fee_bps: msg.protocol_fee_bps,
+        treasury,
+        pot_split: msg.pot_split,
+        jackpot: msg.jackpot,
+        emission_per_second: msg.emission_per_second,
+        compound_rate: msg.compound_rate,
+        push_payouts: msg.push_payouts,
+    };
+    
+    CONFIG.save(deps.storage, &config)?;
+    
+    let state = State {
+        total_staked: 0,
+        current_pot: Uint128::zero(),
+        last_winner: None,
+        staker_count: 0,
+        staker_event_count: 0,
+        next_draw_interval_seconds: SECONDS_IN_DAY
+            .clamp(min_draw_interval_seconds, max_draw_interval_seconds),
+        draw_count: 0,
+        last_draw_time: None,
+        last_admin_action_time: env.block.time,
+        native_pot: vec![],
+        pending_draw_job: None,
+        pending_drand_round: None,
+        pending_commit_reveal: None,
+        total_bonus_weight: 0,
+        total_burned_weight: 0,
+        total_burns: 0,
+        total_prizes_paid: Uint128::zero(),
+        unique_stakers: 0,
+        jackpot: Uint128::zero(),
+        last_jackpot: None,
+        total_compounded_weight: 0,
+    };
+    STATE.save(deps.storage, &state)?;
+    TOTAL_POWER.save(deps.storage, &0, env.block.height)?;
+    ensure_collection(deps.storage, &config.nft_contract)?;
+    DRAW_EXCLUSIONS.save(deps.storage, &config.admin, &true)?;
+    DRAW_EXCLUSIONS.save(deps.storage, &env.contract.address, &true)?;
+    
+    Ok(Response::new())
+}
+
+// The catch-all arm keeps the dispatcher answering cleanly if a variant is added
+// before its handler is wired in
+#[cfg_attr(not(feature = "library"), entry_point)]
+#[allow(unreachable_patterns)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    if let Some(action) = pausable_action(&msg) {
+        ensure_not_paused(deps.storage, action)?;
+    }
+    
+    match msg {
+        ExecuteMsg::Stake { token_ids, lock_duration } => {
+            execute_stake(deps, env, info, token_ids, lock_duration)
+        }
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
+        ExecuteMsg::Unstake {} => execute_unstake(deps, env, info),
+        ExecuteMsg::UnstakeToken { collection, token_id } => {
+            execute_unstake_token(deps, env, info, collection, token_id)
+        }
+        ExecuteMsg::StakeBatch { collection, token_ids, lock_duration } => {
+            execute_stake_batch(deps, env, info, collection, token_ids, lock_duration)
+        }
+        ExecuteMsg::ExtendLock { collection, token_ids, new_duration } => {
+            execute_extend_lock(deps, env, info, collection, token_ids, new_duration)
+        }
+        ExecuteMsg::UnstakeBatch { collection, token_ids } => {
+            execute_unstake_batch(deps, env, info, collection, token_ids)
+        }
+        ExecuteMsg::DrawWinner {} => execute_draw_winner(deps, env, info),
+        ExecuteMsg::NoisReceive { callback } => execute_nois_receive(deps, env, info, callback),
+        ExecuteMsg::SubmitDrandBeacon { round, previous_signature, signature } => {
+            execute_submit_drand_beacon(deps, env, round, previous_signature, signature)
+        }
+        ExecuteMsg::CommitEntropy { commitment } => {
+            execute_commit_entropy(deps, env, info, commitment)
+        }
+        ExecuteMsg::RevealEntropy { secret } => execute_reveal_entropy(deps, env, info, secret),
+        ExecuteMsg::FinalizeDraw {} => execute_finalize_draw(deps, env),
+        ExecuteMsg::ClaimReward {} => execute_claim_reward(deps, env, info),
+        ExecuteMsg::ClaimStakingRewards {} => execute_claim_staking_rewards(deps, env, info),
+        ExecuteMsg::ClaimAll {} => execute_claim_all(deps, env, info),
+        ExecuteMsg::SetAutoCompound { enabled } => execute_set_auto_compound(deps, info, enabled),
+        ExecuteMsg::PostMerkleRoot { merkle_root } => {
+            execute_post_merkle_root(deps, env, info, merkle_root)
+        }
+        ExecuteMsg::ClaimMerkle { proof, amount } => {
+            execute_claim_merkle(deps, info, proof, amount)
+        }
+        ExecuteMsg::SetEmissionRate { emission_per_second } => {
+            execute_set_emission_rate(deps, env, info, emission_per_second)
+        }
+        ExecuteMsg::SweepExpiredPrizes { start_after, limit } => {
+            execute_sweep_expired_prizes(deps, env, start_after, limit)
+        }
+        ExecuteMsg::ClaimUnstaked {} => execute_claim_unstaked(deps, env, info),
+        ExecuteMsg::BurnForTickets { collection, token_id } => {
+            execute_burn_for_tickets(deps, env, info, collection, token_id)
+        }
+        ExecuteMsg::Pause { actions } => execute_pause(deps, env, info, actions),
+        ExecuteMsg::Unpause { actions } => execute_unpause(deps, env, info, actions),
+        ExecuteMsg::ScheduleDraws { cron, funding } => {
+            execute_schedule_draws(deps, env, info, cron, funding)
+        }
+        ExecuteMsg::AddCollection { address, weight } => {
+            execute_add_collection(deps, env, info, address, weight)
+        }
+        ExecuteMsg::RemoveCollection { address } => {
+            execute_remove_collection(deps, env, info, address)
+        }
+        ExecuteMsg::SetTraitWeights { collection, trait_weights } => {
+            execute_set_trait_weights(deps, env, info, collection, trait_weights)
+        }
+        ExecuteMsg::AllowTokens { collection, token_ids } => {
+            execute_allow_tokens(deps, env, info, collection, token_ids)
+        }
+        ExecuteMsg::ExcludeFromDraws { addresses } => {
+            execute_exclude_from_draws(deps, env, info, addresses)
+        }
+        ExecuteMsg::IncludeInDraws { addresses } => {
+            execute_include_in_draws(deps, env, info, addresses)
+        }
+        ExecuteMsg::DisallowTokens { collection, token_ids } => {
+            execute_disallow_tokens(deps, env, info, collection, token_ids)
+        }
+        ExecuteMsg::AddHook { addr } => execute_add_hook(deps, env, info, addr),
+        ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, env, info, addr),
+        ExecuteMsg::SetPrizeTiers { tiers } => execute_set_prize_tiers(deps, env, info, tiers),
+        ExecuteMsg::SetLockTiers { tiers } => execute_set_lock_tiers(deps, env, info, tiers),
+        ExecuteMsg::SetPotSplit { rollover_bps, burn_bps } => {
+            execute_set_pot_split(deps, env, info, PotSplit { rollover_bps, burn_bps })
+        }
+        ExecuteMsg::ClaimTierPrize { tier } => execute_claim_tier_prize(deps, env, info, tier),
+        ExecuteMsg::SetConfig { admin, nft_contract, reward_token } => {
+            execute_set_config(deps, env, info, admin, nft_contract, reward_token)
+        }
+        ExecuteMsg::TransferAdmin { new_admin } => {
+            execute_transfer_admin(deps, env, info, new_admin)
+        }
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, env, info),
+        ExecuteMsg::FundPot {} => execute_fund_pot(deps, env, info),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::FundPotByAllowance { amount } => {
+            execute_fund_pot_by_allowance(deps, env, info, amount)
+        }
+        ExecuteMsg::ValidateStateInvariants {} => {
+            execute_validate_state_invariants(deps, env, info)
+        }
+        ExecuteMsg::CheckIn {} => execute_check_in(deps, env, info),
+        ExecuteMsg::VoteDrawInterval { preferred_seconds } => {
+            execute_vote_draw_interval(deps, env, info, preferred_seconds)
+        }
+        ExecuteMsg::RecalculateTotalStaked { start_after, batch_size } => {
+            execute_recalculate_total_staked(deps, env, info, start_after, batch_size)
+        }
+        ExecuteMsg::ProposeMinStakingPeriod { period } => {
+            execute_propose_min_staking_period(deps, env, info, period)
+        }
+        ExecuteMsg::ApplyMinStakingPeriod {} => execute_apply_min_staking_period(deps, env),
+        ExecuteMsg::ProposeProtocolFee { fee_bps, treasury } => {
+            execute_propose_protocol_fee(deps, env, info, fee_bps, treasury)
+        }
+        ExecuteMsg::ApplyProtocolFee {} => execute_apply_protocol_fee(deps, env),
+        ExecuteMsg::UpdateConfig {
+            nft_contract,
+            reward_token,
+            min_draw_interval_seconds,
+            max_draw_interval_seconds,
+            draw_interval_seconds,
+        } => execute_update_config(
+            deps,
+            env,
+            info,
+            nft_contract,
+            reward_token,
+            min_draw_interval_seconds,
+            max_draw_interval_seconds,
+            draw_interval_seconds,
+        ),
+        ExecuteMsg::AbandonmentExit {} => execute_abandonment_exit(deps, env, info),
+        ExecuteMsg::EmergencyReturnNfts { limit } => {
+            execute_emergency_return_nfts(deps, env, info, limit)
+        }
+        _ => Err(ContractError::UnknownExecuteMessage {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetEligibleStakers { start_after, limit } => {
+            to_json_binary(&query_eligible_stakers(deps, env, start_after, limit)?)
+        }
+        QueryMsg::GetState {} => to_json_binary(&query_state(deps)?),
+        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::GetPendingMinStakingPeriod {} => {
+            to_json_binary(&query_pending_min_staking_period(deps)?)
+        }
+        QueryMsg::GetPendingProtocolFee {} => to_json_binary(&query_pending_protocol_fee(deps)?),
+        QueryMsg::GetJackpot {} => to_json_binary(&query_jackpot(deps)?),
+        QueryMsg::GetStakingRewards { address } => {
+            to_json_binary(&query_staking_rewards(deps, env, address)?)
+        }
+        QueryMsg::GetStakerSettings { address } => {
+            to_json_binary(&query_staker_settings(deps, address)?)
+        }
+        QueryMsg::GetMerkleDistribution {} => {
+            to_json_binary(&query_merkle_distribution(deps)?)
+        }
+        QueryMsg::MerkleClaimed { address } => {
+            to_json_binary(&query_merkle_claimed(deps, address)?)
+        }
+        QueryMsg::GetStaker { address } => to_json_binary(&query_staker(deps, address)?),
+        QueryMsg::GetRound { round_id } => to_json_binary(&query_round(deps, round_id)?),
+        QueryMsg::ListWinners { start_after, limit } => {
+            to_json_binary(&query_list_winners(deps, start_after, limit)?)
+        }
+        QueryMsg::GetTierPots {} => to_json_binary(&query_tier_pots(deps)?),
+        QueryMsg::GetEligibleCount {} => to_json_binary(&query_eligible_count(deps, env)?),
+        QueryMsg::EligibleIn { address } => {
+            to_json_binary(&query_eligible_in(deps, env, address)?)
+        }
+        QueryMsg::GetOdds { address } => to_json_binary(&query_odds(deps, env, address)?),
+        QueryMsg::Stats {} => to_json_binary(&query_stats(deps)?),
+        QueryMsg::NextDraw {} => to_json_binary(&query_next_draw(deps, env)?),
+        QueryMsg::Dashboard { address } => to_json_binary(&query_dashboard(deps, env, address)?),
+        QueryMsg::GetPendingRewards { address } => {
+            to_json_binary(&query_pending_rewards(deps, address)?)
+        }
+        QueryMsg::GetTickets { address } => to_json_binary(&query_tickets(deps, env, address)?),
+        QueryMsg::SimulateDraw { entropy_override } => {
+            to_json_binary(&query_simulate_draw(deps, env, entropy_override)?)
+        }
+        QueryMsg::GetPrizeLeaderboard { limit } => {
+            to_json_binary(&query_prize_leaderboard(deps, limit)?)
+        }
+        QueryMsg::GetBurnLeaderboard { limit } => {
+            to_json_binary(&query_burn_leaderboard(deps, limit)?)
+        }
+        QueryMsg::Leaderboard { limit } => to_json_binary(&query_leaderboard(deps, limit)?),
+        QueryMsg::GetLifetimeWinnings { address } => {
+            to_json_binary(&query_lifetime_winnings(deps, address)?)
+        }
+        QueryMsg::ContractInfo {} => to_json_binary(&query_contract_info(deps)?),
+        QueryMsg::GetOwnership {} => to_json_binary(&query_ownership(deps)?),
+        QueryMsg::GetHooks {} => to_json_binary(&query_hooks(deps)?),
+        QueryMsg::GetPaused {} => to_json_binary(&query_paused(deps)?),
+        QueryMsg::GetStakeCap { address } => to_json_binary(&query_stake_cap(deps, address)?),
+        QueryMsg::GetDrawExclusions { start_after, limit } => {
+            to_json_binary(&query_draw_exclusions(deps, start_after, limit)?)
+        }
+        QueryMsg::ListCollections { start_after, limit } => {
+            to_json_binary(&query_collections(deps, start_after, limit)?)
+        }
+        QueryMsg::GetStakerCollections { address } => {
+            to_json_binary(&query_staker_collections(deps, address)?)
+        }
+        QueryMsg::IsStakeable { collection, token_id } => {
+            to_json_binary(&query_is_stakeable(deps, collection, token_id)?)
+        }
+        QueryMsg::GetDrawTask {} => to_json_binary(&query_draw_task(deps)?),
+        QueryMsg::GetBadgeContract {} => to_json_binary(&query_badge_contract(deps)?),
+        QueryMsg::ProveEligibility { address } => {
+            to_json_binary(&query_prove_eligibility(deps, env, address)?)
+        }
+        QueryMsg::GetStakerHistory { address, start_after, limit } => {
+            to_json_binary(&query_staker_history(deps, address, start_after, limit)?)
+        }
+        QueryMsg::GetIntervalVotes {} => to_json_binary(&query_interval_votes(deps)?),
+        QueryMsg::GetDrawSnapshot { round_id, address } => {
+            to_json_binary(&query_draw_snapshot(deps, round_id, address)?)
+        }
+        QueryMsg::GetStakedTokens { address, start_after, limit } => {
+            to_json_binary(&query_staked_tokens(deps, env, address, start_after, limit)?)
+        }
+        QueryMsg::GetUnbondingClaims { address } => {
+            to_json_binary(&query_unbonding_claims(deps, address)?)
+        }
+        QueryMsg::ListStakers { start_after, limit } => {
+            to_json_binary(&query_list_stakers(deps, start_after, limit)?)
+        }
+        QueryMsg::ListStakersByWeight { descending, limit } => {
+            to_json_binary(&query_stakers_by_weight(deps, descending, limit)?)
+        }
+        QueryMsg::VotingPowerAtHeight { address, height } => {
+            to_json_binary(&query_voting_power_at_height(deps, env, address, height)?)
+        }
+        QueryMsg::TotalPowerAtHeight { height } => {
+            to_json_binary(&query_total_power_at_height(deps, env, height)?)
+        }
+        QueryMsg::Info {} => to_json_binary(&query_info(deps)?),
+        QueryMsg::Dao {} => to_json_binary(&query_dao(deps)?),
+        QueryMsg::GetFullDrawSnapshot { round_id, start_after, limit } => {
+            to_json_binary(&query_full_draw_snapshot(deps, round_id, start_after, limit)?)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        CLAIM_REPLY_ID => reply_claim(deps, env, msg),
+        FUND_REPLY_ID => reply_fund(deps, env, msg),
+        BADGE_REPLY_ID => reply_badge(deps, env, msg),
+        CRONCAT_REPLY_ID => reply_croncat(deps, env, msg),
+        PULL_REPLY_ID => reply_pull(deps, env, msg),
+        PUSH_REPLY_ID => reply_push_payout(deps, env, msg),
+        POT_PAYOUT_REPLY_ID => reply_pot_payout(deps, env, msg),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::TriggerDraw {} => {
+            ensure_not_paused(deps.storage, PausableAction::Draw)?;
+            sudo_trigger_draw(deps, env)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let previous_version = cw2::get_contract_version(deps.storage)
+        .map(|info| info.version)
+        .unwrap_or_default();
+
+    // Refuses other contracts and newer stored versions, and records the new version on success
+    if msg.skip_version_check {
+        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    } else {
+        cw2::ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    }
+
+    let migrated = migrate_state_v1_to_v2(deps.branch())?;
+    // Before re-keying, which writes index entries of its own
+    let indexed = index_staker_positions(deps.branch())?;
+    let rekeyed = migrate_stakers_key_format(deps.branch())?;
+    let rekeyed_tokens = migrate_custody_collections(deps.branch())?;
+    let period_converted = migrate_min_staking_period(deps.branch())?;
+    let set_cleared = clear_staker_set(deps.branch())?;
+    let unique_stakers = seed_unique_stakers(deps.branch())?;
+    seed_total_power(deps, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("previous_version", previous_version)
+        .add_attribute("version", CONTRACT_VERSION)
+        .add_attribute("stakers_migrated", migrated.to_string())
+        .add_attribute("stakers_indexed", indexed.to_string())
+        .add_attribute("staker_keys_migrated", rekeyed.to_string())
+        .add_attribute("custody_tokens_migrated", rekeyed_tokens.to_string())
+        .add_attribute("min_staking_period_converted", period_converted.to_string())
+        .add_attribute("staker_set_cleared", set_cleared.to_string())
+        .add_attribute("unique_stakers_seeded", unique_stakers.to_string()))
+}
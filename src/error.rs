@@ -0,0 +1,254 @@
+use cosmwasm_std::{Coin, StdError, Timestamp, Uint128};
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+use crate::state::PausableAction;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("NFTs from {collection} cannot be staked")]
+    UnknownCollection { collection: String },
+
+    #[error("Unknown execute message")]
+    UnknownExecuteMessage {},
+
+    #[error("Draws need a randomness source: nois_proxy, drand or commit_reveal")]
+    NoEntropyAvailable {},
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Not staked")]
+    NothingStaked {},
+
+    #[error("No unstaked token has finished unbonding")]
+    NothingToClaim {},
+
+    #[error("No staking rewards to claim")]
+    NoStakingRewards {},
+
+    #[error("Nothing is owed to the sender")]
+    NothingOwed {},
+
+    #[error("A merkle root has to be 32 bytes")]
+    InvalidMerkleRoot {},
+
+    #[error("No merkle distribution has been posted")]
+    NoMerkleDistribution {},
+
+    #[error("Merkle proof does not match the posted root")]
+    InvalidMerkleProof {},
+
+    #[error("Already claimed from this merkle distribution")]
+    MerkleAlreadyClaimed {},
+
+    #[error("The merkle distribution is not funded for this claim")]
+    MerkleUnderfunded {},
+
+    #[error("Early unstaking is disabled")]
+    EarlyUnstakeDisabled {},
+
+    #[error("Early unstaking costs {required} reward tokens")]
+    InsufficientPenalty { required: Uint128 },
+
+    #[error("Burning for tickets is disabled")]
+    BurnDisabled {},
+
+    #[error("Token {token_id} is not staked by sender")]
+    TokenNotStaked { token_id: String },
+
+    #[error("Minimum staking requirement not met, {remaining_secs} seconds remaining")]
+    MinStakePeriodNotMet { remaining_secs: u64 },
+
+    #[error("No staker has passed the minimum staking period and is still active")]
+    NoEligibleStakers {},
+
+    #[error("Next draw is allowed in {remaining_secs} seconds")]
+    DrawCooldownActive { remaining_secs: u64 },
+
+    #[error("A draw is already waiting for randomness")]
+    DrawInProgress {},
+
+    #[error("No draw is waiting for Nois job {job_id}")]
+    UnknownNoisJob { job_id: String },
+
+    #[error("No draw is waiting for a drand beacon")]
+    NoDrandRoundPending {},
+
+    #[error("The pending draw uses drand round {expected}")]
+    WrongDrandRound { expected: u64 },
+
+    #[error("Signature does not verify for drand round {round}")]
+    InvalidDrandSignature { round: u64 },
+
+    #[error("drand public key must be a 48 byte G1 point and the period at least one second")]
+    InvalidDrandConfig {},
+
+    #[error("Only one of nois_proxy, drand and commit_reveal can be set")]
+    ConflictingRandomnessSources {},
+
+    #[error("Commit-reveal needs contributors and min_reveals between 1 and their number")]
+    InvalidCommitRevealConfig {},
+
+    #[error("No commit-reveal round is open")]
+    NoCommitRevealRound {},
+
+    #[error("Sender is not a registered entropy contributor")]
+    NotAContributor {},
+
+    #[error("The commit window has closed")]
+    CommitWindowClosed {},
+
+    #[error("Reveals are accepted once the commit window closes and until the reveal window ends")]
+    NotInRevealWindow {},
+
+    #[error("Secret does not match the commitment")]
+    RevealMismatch {},
+
+    #[error("The reveal window is still open")]
+    RevealWindowOpen {},
+
+    #[error("No winner to claim")]
+    NoWinner {},
+
+    #[error("Not the winner")]
+    NotTheWinner {},
+
+    #[error("The claim window closed at {deadline}")]
+    ClaimWindowExpired { deadline: Timestamp },
+
+    #[error("Amount must be greater than zero")]
+    ZeroAmount {},
+
+    #[error("{field} cannot exceed 10000 bps")]
+    InvalidBasisPoints { field: String },
+
+    #[error("Duration weighting period must be at least one second")]
+    InvalidDurationWeighting {},
+
+    #[error("Prize split must be non-zero shares summing to 10000 bps")]
+    InvalidPrizeSplit {},
+
+    #[error("Pot rollover and burn shares cannot exceed 10000 bps together")]
+    InvalidPotSplit {},
+
+    #[error("Jackpot share must be at most 10000 bps and its odds above zero")]
+    InvalidJackpotConfig {},
+
+    #[error("Prize tiers need unique non-empty names and at least one winner each")]
+    InvalidPrizeTiers {},
+
+    #[error("No prize tier named {tier}")]
+    UnknownTier { tier: String },
+
+    #[error("The {tier} tier still holds reward tokens")]
+    TierNotEmpty { tier: String },
+
+    #[error("Minimum draw interval exceeds the maximum")]
+    InvalidDrawIntervalBounds {},
+
+    #[error("Draw interval must be between {min} and {max} seconds")]
+    DrawIntervalOutOfRange { min: u64, max: u64 },
+
+    #[error("Batch size must be at least 1")]
+    InvalidBatchSize {},
+
+    #[error("At most {max} tokens fit in one batch")]
+    BatchTooLarge { max: u32 },
+
+    #[error("Staking needs token_ids so their ownership can be checked")]
+    WalletStakesDisabled {},
+
+    #[error("Sender does not own token {token_id}")]
+    NotTokenOwner { token_id: String },
+
+    #[error("This contract is not approved to transfer token {token_id}")]
+    NotApproved { token_id: String },
+
+    #[error("start_after does not continue the current reconciliation")]
+    ReconcileOutOfSequence {},
+
+    #[error("No admin transfer is pending")]
+    NoPendingAdmin {},
+
+    #[error("No minimum staking period is queued")]
+    NoPendingMinStakingPeriod {},
+
+    #[error("No protocol fee is queued")]
+    NoPendingProtocolFee {},
+
+    #[error("A protocol fee needs a treasury to send it to")]
+    TreasuryRequired {},
+
+    #[error("The new setting applies in {remaining_secs} seconds")]
+    TimelockActive { remaining_secs: u64 },
+
+    #[error("Minimum staking period must be a time duration")]
+    InvalidMinStakingPeriod {},
+
+    #[error("NFTs are still held from the collection")]
+    NftsInCustody {},
+
+    #[error("The pot or unclaimed prizes still hold reward tokens")]
+    PotNotEmpty {},
+
+    #[error("Abandonment exits are disabled")]
+    AbandonmentExitDisabled {},
+
+    #[error("Contract has not been abandoned")]
+    NotAbandoned {},
+
+    #[error("{action:?} is paused")]
+    Paused { action: PausableAction },
+
+    #[error("{addr} is already a registered hook")]
+    HookAlreadyRegistered { addr: String },
+
+    #[error("{addr} is not a registered hook")]
+    HookNotRegistered { addr: String },
+
+    #[error("Native pot holds less than {required}")]
+    InsufficientNativePot { required: Coin },
+
+    #[error("No CronCat deployment is configured")]
+    CronCatNotConfigured {},
+
+    #[error("Collection weight must be at least 1")]
+    InvalidCollectionWeight {},
+
+    #[error("Token {token_id} is not on the collection's allowlist")]
+    TokenNotAllowed { token_id: String },
+
+    #[error("Lock tiers need increasing non-zero durations and multipliers of at least 10000 bps")]
+    InvalidLockTiers {},
+
+    #[error("No lock tier of {duration} seconds")]
+    UnknownLockTier { duration: u64 },
+
+    #[error("Only custody tokens can be locked")]
+    LockRequiresCustody {},
+
+    #[error("Token {token_id} is locked for another {remaining_secs} seconds")]
+    TokenLocked { token_id: String, remaining_secs: u64 },
+
+    #[error("A new lock on token {token_id} has to run longer without dropping a tier")]
+    LockNotExtended { token_id: String },
+
+    #[error("One address can stake at most {max} NFTs")]
+    StakeCapExceeded { max: u64 },
+
+    #[error("Staking these NFTs costs {expected}")]
+    WrongStakeFee { expected: Coin },
+
+    #[error("SendNft cannot pay the stake fee; stake with Stake or StakeBatch")]
+    StakeFeeUnpaid {},
+}
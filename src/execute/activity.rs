@@ -0,0 +1,25 @@
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+
+use crate::error::ContractError;
+use crate::state::{stakers, LAST_ACTIVITY};
+
+// Proof of activity for stakers who have nothing to stake or unstake
+pub fn execute_check_in(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if !stakers().has(deps.storage, &info.sender) {
+        return Err(ContractError::NothingStaked {});
+    }
+
+    record_activity(deps.storage, &info.sender, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "check_in")
+        .add_attribute("sender", info.sender))
+}
+
+pub(crate) fn record_activity(storage: &mut dyn Storage, address: &Addr, env: &Env) -> StdResult<()> {
+    LAST_ACTIVITY.save(storage, address.to_string(), &env.block.time)
+}
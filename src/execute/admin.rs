@@ -0,0 +1,516 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage,
+    Uint128,
+};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use cw_storage_plus::Bound;
+use cw_utils::Duration;
+
+use super::collections::ensure_collection;
+use crate::error::ContractError;
+use crate::msg::InvariantReport;
+use crate::state::{
+    stakers, Config, PendingMinStakingPeriod, PendingProtocolFee, PotSplit, ReconcileState,
+    CONFIG, MERKLE_DISTRIBUTION, MIN_STAKING_PERIOD_TIMELOCK, PENDING_ADMIN,
+    PENDING_MIN_STAKING_PERIOD, PENDING_PRIZES, PENDING_PROTOCOL_FEE, PROTOCOL_FEE_TIMELOCK,
+    RECONCILE_STATE, REWARD_STREAM, STAKED_NFTS, STATE, TIER_POTS, TIER_PRIZES, TOTAL_POWER,
+    UNBONDING,
+};
+
+pub fn execute_set_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    admin: String,
+    nft_contract: String,
+    reward_token: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Same guards as UpdateConfig for either address that actually changes
+    let admin = deps.api.addr_validate(&admin)?;
+    let nft_contract = deps.api.addr_validate(&nft_contract)?;
+    if nft_contract != config.nft_contract {
+        ensure_no_custody(deps.storage)?;
+    }
+    let reward_token = deps.api.addr_validate(&reward_token)?;
+    if reward_token != config.reward_token {
+        ensure_pot_empty(deps.storage)?;
+    }
+    let config = Config {
+        nft_contract,
+        reward_token,
+        ..config
+    };
+    CONFIG.save(deps.storage, &config)?;
+    ensure_collection(deps.storage, &config.nft_contract)?;
+    record_admin_action(deps.storage, &env)?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "set_config")
+        .add_attribute("admin", &config.admin)
+        .add_attribute("nft_contract", config.nft_contract)
+        .add_attribute("reward_token", config.reward_token);
+    if admin != config.admin {
+        PENDING_ADMIN.save(deps.storage, &admin)?;
+        response = response.add_attribute("pending_admin", admin);
+    }
+    Ok(response)
+}
+
+// First half of an admin handover. A later proposal replaces an earlier one.
+pub fn execute_transfer_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_admin = deps.api.addr_validate(&new_admin)?;
+    PENDING_ADMIN.save(deps.storage, &new_admin)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer_admin")
+        .add_attribute("admin", config.admin)
+        .add_attribute("pending_admin", new_admin))
+}
+
+// Second half: only the proposed address can complete the handover
+pub fn execute_accept_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_ADMIN
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingAdmin {})?;
+    if info.sender != pending {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous_admin = std::mem::replace(&mut config.admin, pending);
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_ADMIN.remove(deps.storage);
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_admin")
+        .add_attribute("previous_admin", previous_admin)
+        .add_attribute("admin", config.admin))
+}
+
+// nft_contract can't move while any of its tokens are staked or unbonding
+fn ensure_no_custody(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !STAKED_NFTS.is_empty(storage) || !UNBONDING.is_empty(storage) {
+        return Err(ContractError::NftsInCustody {});
+    }
+    Ok(())
+}
+
+// reward_token can't move while any of it is held for the pot, jackpot, reward stream,
+// merkle drop, tiers or unclaimed prizes
+fn ensure_pot_empty(storage: &dyn Storage) -> Result<(), ContractError> {
+    let state = STATE.load(storage)?;
+    let stream = REWARD_STREAM.may_load(storage)?.unwrap_or_default();
+    let merkle_held = MERKLE_DISTRIBUTION
+        .may_load(storage)?
+        .is_some_and(|distribution| distribution.funded > distribution.claimed);
+    let pot_held = !state.current_pot.is_zero()
+        || !state.jackpot.is_zero()
+        || !(stream.reserve + stream.outstanding).is_zero()
+        || merkle_held;
+    let tiers_held = !TIER_POTS.is_empty(storage) || !TIER_PRIZES.is_empty(storage);
+    if pot_held || !PENDING_PRIZES.is_empty(storage) || tiers_held {
+        return Err(ContractError::PotNotEmpty {});
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_contract: Option<String>,
+    reward_token: Option<String>,
+    min_draw_interval_seconds: Option<u64>,
+    max_draw_interval_seconds: Option<u64>,
+    draw_interval_seconds: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut response = Response::new().add_attribute("action", "update_config");
+
+    // UnstakeToken defaults to nft_contract and the pot is paid in reward_token, so
+    // neither can move while something is held in it
+    if let Some(address) = nft_contract {
+        ensure_no_custody(deps.storage)?;
+        config.nft_contract = deps.api.addr_validate(&address)?;
+        ensure_collection(deps.storage, &config.nft_contract)?;
+        response = response.add_attribute("nft_contract", &config.nft_contract);
+    }
+    if let Some(address) = reward_token {
+        ensure_pot_empty(deps.storage)?;
+        config.reward_token = deps.api.addr_validate(&address)?;
+        response = response.add_attribute("reward_token", &config.reward_token);
+    }
+
+    if let Some(seconds) = min_draw_interval_seconds {
+        config.min_draw_interval_seconds = seconds;
+        response = response.add_attribute("min_draw_interval_seconds", seconds.to_string());
+    }
+    if let Some(seconds) = max_draw_interval_seconds {
+        config.max_draw_interval_seconds = seconds;
+        response = response.add_attribute("max_draw_interval_seconds", seconds.to_string());
+    }
+    if config.min_draw_interval_seconds > config.max_draw_interval_seconds {
+        return Err(ContractError::InvalidDrawIntervalBounds {});
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    if let Some(seconds) = draw_interval_seconds {
+        if seconds < config.min_draw_interval_seconds || seconds > config.max_draw_interval_seconds {
+            return Err(ContractError::DrawIntervalOutOfRange {
+                min: config.min_draw_interval_seconds,
+                max: config.max_draw_interval_seconds,
+            });
+        }
+        let mut state = STATE.load(deps.storage)?;
+        state.next_draw_interval_seconds = seconds;
+        STATE.save(deps.storage, &state)?;
+        response = response.add_attribute("draw_interval_seconds", seconds.to_string());
+    }
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(response)
+}
+
+// The new period only applies after MIN_STAKING_PERIOD_TIMELOCK, so stakers see a longer
+// lock-in coming before it holds their NFTs
+pub fn execute_propose_min_staking_period(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    period: Duration,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let period = validate_min_staking_period(period)?;
+
+    let pending = PendingMinStakingPeriod {
+        period,
+        effective_at: env.block.time.plus_seconds(MIN_STAKING_PERIOD_TIMELOCK),
+    };
+    PENDING_MIN_STAKING_PERIOD.save(deps.storage, &pending)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_min_staking_period")
+        .add_attribute("period", period.to_string())
+        .add_attribute("effective_at", pending.effective_at.to_string()))
+}
+
+pub fn execute_apply_min_staking_period(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_MIN_STAKING_PERIOD
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingMinStakingPeriod {})?;
+    if env.block.time < pending.effective_at {
+        return Err(ContractError::TimelockActive {
+            remaining_secs: pending.effective_at.seconds() - env.block.time.seconds(),
+        });
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.min_staking_period = pending.period;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_MIN_STAKING_PERIOD.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "apply_min_staking_period")
+        .add_attribute("period", pending.period.to_string()))
+}
+
+// Like the minimum staking period, a new fee only applies after a timelock, so stakers
+// can leave before a larger cut of the pot is taken
+pub fn execute_propose_protocol_fee(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    fee_bps: u16,
+    treasury: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let treasury = treasury
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    validate_protocol_fee(fee_bps, &treasury)?;
+
+    let pending = PendingProtocolFee {
+        fee_bps,
+        treasury,
+        effective_at: env.block.time.plus_seconds(PROTOCOL_FEE_TIMELOCK),
+    };
+    PENDING_PROTOCOL_FEE.save(deps.storage, &pending)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_protocol_fee")
+        .add_attribute("fee_bps", fee_bps.to_string())
+        .add_attribute("effective_at", pending.effective_at.to_string()))
+}
+
+pub fn execute_apply_protocol_fee(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let pending = PENDING_PROTOCOL_FEE
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingProtocolFee {})?;
+    if env.block.time < pending.effective_at {
+        return Err(ContractError::TimelockActive {
+            remaining_secs: pending.effective_at.seconds() - env.block.time.seconds(),
+        });
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.protocol_fee_bps = pending.fee_bps;
+    config.treasury = pending.treasury;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_PROTOCOL_FEE.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "apply_protocol_fee")
+        .add_attribute("fee_bps", pending.fee_bps.to_string()))
+}
+
+pub(crate) fn validate_protocol_fee(
+    fee_bps: u16,
+    treasury: &Option<Addr>,
+) -> Result<(), ContractError> {
+    if fee_bps > 10_000 {
+        return Err(ContractError::InvalidBasisPoints {
+            field: "protocol_fee_bps".to_string(),
+        });
+    }
+    if fee_bps > 0 && treasury.is_none() {
+        return Err(ContractError::TreasuryRequired {});
+    }
+    Ok(())
+}
+
+pub fn execute_set_pot_split(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pot_split: PotSplit,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    validate_pot_split(&pot_split)?;
+
+    config.pot_split = pot_split;
+    CONFIG.save(deps.storage, &config)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_pot_split")
+        .add_attribute("rollover_bps", config.pot_split.rollover_bps.to_string())
+        .add_attribute("burn_bps", config.pot_split.burn_bps.to_string()))
+}
+
+pub(crate) fn validate_pot_split(pot_split: &PotSplit) -> Result<(), ContractError> {
+    if pot_split.rollover_bps as u32 + pot_split.burn_bps as u32 > 10_000 {
+        return Err(ContractError::InvalidPotSplit {});
+    }
+    Ok(())
+}
+
+// Stakes only carry a timestamp, so the period has to be a time duration
+pub(crate) fn validate_min_staking_period(period: Duration) -> Result<Duration, ContractError> {
+    match period {
+        Duration::Time(_) => Ok(period),
+        Duration::Height(_) => Err(ContractError::InvalidMinStakingPeriod {}),
+    }
+}
+
+pub fn execute_recalculate_total_staked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<String>,
+    batch_size: u32,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if batch_size == 0 {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+
+    // A run without start_after starts over, otherwise it must pick up where the last call ended
+    let mut reconcile = match &start_after {
+        None => ReconcileState::default(),
+        Some(_) => {
+            let reconcile = RECONCILE_STATE.may_load(deps.storage)?.unwrap_or_default();
+            if reconcile.completed || reconcile.last_key != start_after {
+                return Err(ContractError::ReconcileOutOfSequence {});
+            }
+            reconcile
+        }
+    };
+
+    let start_addr = start_after.map(|address| deps.api.addr_validate(&address)).transpose()?;
+    let batch = stakers()
+        .range(
+            deps.storage,
+            start_addr.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(batch_size as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (address, staker) in &batch {
+        reconcile.accumulated += staker.nft_count;
+        reconcile.last_key = Some(address.to_string());
+    }
+    reconcile.completed = batch.len() < batch_size as usize;
+
+    if reconcile.completed {
+        let mut state = STATE.load(deps.storage)?;
+        state.total_staked = reconcile.accumulated;
+        STATE.save(deps.storage, &state)?;
+        TOTAL_POWER.save(deps.storage, &state.total_weight(), env.block.height)?;
+    }
+    RECONCILE_STATE.save(deps.storage, &reconcile)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "recalculate_total_staked")
+        .add_attribute("progress", reconcile.accumulated.to_string())
+        .add_attribute("last_key", reconcile.last_key.unwrap_or_default())
+        .add_attribute("is_complete", reconcile.completed.to_string()))
+}
+
+// Full consistency check of the staking counters and pot, meant to be run after a migration
+pub fn execute_validate_state_invariants(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let report = check_state_invariants(deps.as_ref(), &env, &config)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&report)?)
+        .add_attribute("action", "validate_state_invariants")
+        .add_attribute("passed", report.passed.to_string()))
+}
+
+// Stamp the current block as the admin's latest action, resetting the abandonment clock
+pub(crate) fn record_admin_action(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let mut state = STATE.load(storage)?;
+    state.last_admin_action_time = env.block.time;
+    STATE.save(storage, &state)
+}
+
+fn check_state_invariants(deps: Deps, env: &Env, config: &Config) -> StdResult<InvariantReport> {
+    let state = STATE.load(deps.storage)?;
+    let mut details = Vec::new();
+
+    let nft_sum = stakers()
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(0u64, |sum, item| item.map(|(_, staker)| sum + staker.nft_count))?;
+    if nft_sum != state.total_staked {
+        details.push(format!(
+            "total_staked is {} but stakers hold {} NFTs",
+            state.total_staked, nft_sum
+        ));
+    }
+
+    let positions = stakers()
+        .keys(deps.storage, None, None, Order::Ascending)
+        .try_fold(0u64, |count, item| item.map(|_| count + 1))?;
+    if positions != state.staker_count {
+        details.push(format!(
+            "staker_count is {} but {} positions are stored",
+            state.staker_count, positions
+        ));
+    }
+
+    let balance: BalanceResponse = deps.querier.query_wasm_smart(
+        &config.reward_token,
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    let unclaimed = PENDING_PRIZES
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |sum, item| item.map(|(_, pending)| sum + pending.amount))?;
+    let tier_pots = TIER_POTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |sum, item| item.map(|(_, pot)| sum + pot))?;
+    let tier_prizes = TIER_PRIZES
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |sum, item| item.map(|(_, prize)| sum + prize))?;
+    let stream = REWARD_STREAM.may_load(deps.storage)?.unwrap_or_default();
+    let streamed = stream.reserve + stream.outstanding;
+    let merkle = MERKLE_DISTRIBUTION
+        .may_load(deps.storage)?
+        .map_or(Uint128::zero(), |distribution| distribution.funded - distribution.claimed);
+    let owed = state.current_pot
+        + state.jackpot
+        + unclaimed
+        + tier_pots
+        + tier_prizes
+        + streamed
+        + merkle;
+    if balance.balance < owed {
+        details.push(format!(
+            "reward token balance {} does not cover the pot of {}, the jackpot of {}, {} in unclaimed prizes, {} held for prize tiers, {} for streaming rewards and {} for the merkle distribution",
+            balance.balance,
+            state.current_pot,
+            state.jackpot,
+            unclaimed,
+            tier_pots + tier_prizes,
+            streamed,
+            merkle
+        ));
+    }
+    for coin in &state.native_pot {
+        let held = deps.querier.query_balance(&env.contract.address, &coin.denom)?;
+        if held.amount < coin.amount {
+            details.push(format!("native balance {} does not cover the pot of {}", held, coin));
+        }
+    }
+
+    Ok(InvariantReport {
+        passed: details.is_empty(),
+        details,
+    })
+}
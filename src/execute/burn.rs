@@ -0,0 +1,201 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128,
+    WasmMsg,
+};
+use cw721::Cw721ExecuteMsg;
+use sha2::{Digest, Sha256};
+
+use super::activity::record_activity;
+use super::fund::pot_transfer;
+use super::history::record_event;
+use super::hooks::stake_changed_hooks;
+use super::receipt::{receipt_burn_msg, staked_receipt_id};
+use super::voting::checkpoint_power;
+use crate::error::ContractError;
+use crate::msg::StakeChangedHookMsg;
+use crate::state::{
+    stakers, Config, StakeEventType, StakedNft, State, BURN_COUNTS, BURN_LEADERBOARD, COLLECTIONS,
+    CONFIG, STAKED_NFTS, STATE,
+};
+
+// Burn a custody token at the stake. Its tickets are multiplied by burn_multiplier and
+// kept on the position for good, so they carry across rounds.
+pub fn execute_burn_for_tickets(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: Option<String>,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.burn_multiplier == 0 {
+        return Err(ContractError::BurnDisabled {});
+    }
+    let collection = match collection {
+        Some(address) => deps.api.addr_validate(&address)?,
+        None => config.nft_contract.clone(),
+    };
+    let staked = STAKED_NFTS
+        .may_load(deps.storage, (&info.sender, &collection, &token_id))?
+        .ok_or_else(|| ContractError::TokenNotStaked {
+            token_id: token_id.clone(),
+        })?;
+    let mut state = STATE.load(deps.storage)?;
+
+    // A lock's multiplier is for holding the token, which burning ends
+    let burned_weight = staked.base_weight().saturating_mul(config.burn_multiplier);
+    let token = (collection.clone(), token_id.clone(), staked);
+    let response = burn_staked_token(
+        deps.storage,
+        &env,
+        &config,
+        &mut state,
+        &info.sender,
+        token,
+        burned_weight,
+        StakeEventType::Burned,
+    )?;
+    state.total_burns += 1;
+    STATE.save(deps.storage, &state)?;
+    record_activity(deps.storage, &info.sender, &env)?;
+
+    let burns = BURN_COUNTS.may_load(deps.storage, &info.sender)?.unwrap_or_default() + 1;
+    BURN_COUNTS.save(deps.storage, &info.sender, &burns)?;
+    let mut leaderboard = BURN_LEADERBOARD.may_load(deps.storage)?.unwrap_or_default();
+    leaderboard.record(info.sender.to_string(), Uint128::from(burns));
+    BURN_LEADERBOARD.save(deps.storage, &leaderboard)?;
+
+    Ok(response
+        .add_attribute("action", "burn_for_tickets")
+        .add_attribute("sender", info.sender)
+        .add_attribute("collection", collection)
+        .add_attribute("token_id", token_id)
+        .add_attribute("burned_weight", burned_weight.to_string())
+        .add_attribute("total_burns", state.total_burns.to_string()))
+}
+
+// Owner and (collection, token_id, staked token) of a custody token
+type CustodyToken = (Addr, (Addr, String, StakedNft));
+
+// The custody token a draw burns at the stake, picked uniformly over every custody token
+// with the draw's entropy. None if nothing is held in custody.
+pub(super) fn pick_sacrifice(
+    storage: &dyn Storage,
+    entropy: &[u8],
+) -> StdResult<Option<CustodyToken>> {
+    let custody = COLLECTIONS
+        .range(storage, None, None, Order::Ascending)
+        .try_fold(0u64, |sum, item| item.map(|(_, collection)| sum + collection.staked))?;
+    if custody == 0 {
+        return Ok(None);
+    }
+    let digest = Sha256::digest([entropy, b"sacrifice"].concat());
+    let index = u64::from_be_bytes(digest[0..8].try_into().unwrap()) % custody;
+    let token = STAKED_NFTS
+        .range(storage, None, None, Order::Ascending)
+        .nth(index as usize)
+        .transpose()?;
+    Ok(token.map(|((owner, collection, token_id), staked)| (owner, (collection, token_id, staked))))
+}
+
+// Burn the token picked by pick_sacrifice and pay its owner `compensation` from the pot.
+// The caller has already taken `compensation` out of the pot and saves `state`; a failed
+// payment goes back to the pot.
+pub(super) fn sacrifice_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    state: &mut State,
+    owner: &Addr,
+    token: (Addr, String, StakedNft),
+    compensation: Uint128,
+) -> Result<Response, ContractError> {
+    let response = burn_staked_token(
+        storage,
+        env,
+        config,
+        state,
+        owner,
+        token,
+        0,
+        StakeEventType::Sacrificed,
+    )?;
+    if compensation.is_zero() {
+        return Ok(response);
+    }
+    Ok(response.add_submessage(pot_transfer(storage, config, owner, compensation)?))
+}
+
+// Burn one of `owner`'s custody tokens and take it off the position, leaving
+// `burned_weight` permanent tickets in its place. A position left with neither NFTs nor
+// permanent tickets is closed. The caller saves `state`.
+#[allow(clippy::too_many_arguments)]
+fn burn_staked_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    state: &mut State,
+    owner: &Addr,
+    token: (Addr, String, StakedNft),
+    burned_weight: u64,
+    event_type: StakeEventType,
+) -> Result<Response, ContractError> {
+    let (collection, token_id, staked) = token;
+    let mut staker = stakers().load(storage, owner)?;
+    let weight_before = staker.weight();
+
+    let receipt_id = staked_receipt_id(owner, staker.nft_count, &staked);
+    let receipt_msg = receipt_burn_msg(config, receipt_id)?;
+    STAKED_NFTS.remove(storage, (owner, &collection, &token_id));
+    let mut registered = COLLECTIONS.load(storage, &collection)?;
+    registered.staked -= 1;
+    COLLECTIONS.save(storage, &collection, &registered)?;
+
+    staker.nft_count -= 1;
+    staker.bonus_weight -= staked.weight - 1;
+    staker.burned_weight += burned_weight;
+    if staker.nft_count == 0 && staker.permanent_weight() == 0 {
+        stakers().remove(storage, owner)?;
+        state.staker_count -= 1;
+    } else {
+        stakers().save(storage, owner, &staker)?;
+    }
+
+    state.total_staked -= 1;
+    state.total_bonus_weight -= staked.weight - 1;
+    state.total_burned_weight += burned_weight;
+    let weight = staker.weight();
+    checkpoint_power(storage, env, owner, weight_before, weight, state.total_weight())?;
+    record_event(
+        storage,
+        state,
+        env,
+        owner.as_str(),
+        event_type,
+        staker.nft_count,
+        Some(&token_id),
+    )?;
+
+    let hook_msg = match staker.weight().cmp(&weight_before) {
+        std::cmp::Ordering::Greater => Some(StakeChangedHookMsg::Stake {
+            addr: owner.clone(),
+            amount: Uint128::from(staker.weight() - weight_before),
+        }),
+        std::cmp::Ordering::Less => Some(StakeChangedHookMsg::Unstake {
+            addr: owner.clone(),
+            amount: Uint128::from(weight_before - staker.weight()),
+        }),
+        std::cmp::Ordering::Equal => None,
+    };
+    let mut response = Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: collection.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::Burn { token_id })?,
+            funds: vec![],
+        })
+        .add_messages(receipt_msg);
+    if let Some(hook_msg) = hook_msg {
+        response = response.add_submessages(stake_changed_hooks(storage, hook_msg)?);
+    }
+    Ok(response)
+}
@@ -0,0 +1,283 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult,
+    Storage, SubMsg, SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_storage_plus::Bound;
+
+use super::history::record_event;
+use super::merkle::restore_merkle_claim;
+use super::rewards::{restore_staking_rewards, take_staking_rewards};
+use crate::error::ContractError;
+use crate::query::{DEFAULT_LIMIT, MAX_LIMIT};
+use crate::state::{
+    stakers, ClaimInFlight, Config, PendingPrize, StakeEventType, CLAIM_IN_FLIGHT, CONFIG,
+    PENDING_PRIZES, PRIZE_LEADERBOARD, PUSH_PAYOUTS_IN_FLIGHT, STATE, TIER_PRIZES,
+    TOTAL_WINNINGS,
+};
+
+pub const CLAIM_REPLY_ID: u64 = 1;
+pub const PUSH_REPLY_ID: u64 = 6;
+
+// Pays out everything the sender has won and not yet claimed
+pub fn execute_claim_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    
+    let Some(pending) = PENDING_PRIZES.may_load(deps.storage, &info.sender)? else {
+        return Err(match state.last_winner {
+            Some(_) => ContractError::NotTheWinner {},
+            None => ContractError::NoWinner {},
+        });
+    };
+    if let Some(deadline) = pending.claim_deadline {
+        if env.block.time > deadline {
+            return Err(ContractError::ClaimWindowExpired { deadline });
+        }
+    }
+    let prize = pending.amount;
+    // Cleared before the transfer; the reply puts it back if the transfer fails
+    PENDING_PRIZES.remove(deps.storage, &info.sender);
+    
+    let claim = ClaimInFlight {
+        prize: Some(pending),
+        ..ClaimInFlight::new(info.sender.clone())
+    };
+    
+    Ok(Response::new()
+        .add_submessage(send_claim(deps.storage, &config, claim)?)
+        .add_attribute("action", "claim_reward")
+        .add_attribute("winner", info.sender)
+        .add_attribute("amount", prize))
+}
+
+// Pays out the sender's main pot prize, tier prizes and streaming rewards in one
+// transfer. A main pot prize past its claim window is left for SweepExpiredPrizes.
+pub fn execute_claim_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut claim = ClaimInFlight::new(info.sender.clone());
+    if let Some(pending) = PENDING_PRIZES.may_load(deps.storage, &info.sender)? {
+        if pending.claim_deadline.is_none_or(|deadline| env.block.time <= deadline) {
+            PENDING_PRIZES.remove(deps.storage, &info.sender);
+            claim.prize = Some(pending);
+        }
+    }
+    for tier in &config.prize_tiers {
+        let key = (tier.name.as_str(), &info.sender);
+        if let Some(prize) = TIER_PRIZES.may_load(deps.storage, key)? {
+            TIER_PRIZES.remove(deps.storage, key);
+            claim.tier_prizes.push((tier.name.clone(), prize));
+        }
+    }
+    claim.staking_rewards = take_staking_rewards(deps.storage, &env, &info.sender)?;
+    let (winnings, streamed, amount) = (claim.winnings(), claim.staking_rewards, claim.amount());
+    if amount.is_zero() {
+        return Err(ContractError::NothingOwed {});
+    }
+
+    Ok(Response::new()
+        .add_submessage(send_claim(deps.storage, &config, claim)?)
+        .add_attribute("action", "claim_all")
+        .add_attribute("claimer", info.sender)
+        .add_attribute("prizes", winnings)
+        .add_attribute("staking_rewards", streamed)
+        .add_attribute("amount", amount))
+}
+
+// Returns the prizes whose claim window has passed to the pot. Callable by anyone;
+// scans up to `limit` winners with an unclaimed prize, after `start_after`.
+pub fn execute_sweep_expired_prizes(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(|address| deps.api.addr_validate(&address)).transpose()?;
+    let page = PENDING_PRIZES
+        .range(
+            deps.storage,
+            start_addr.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_key = page.last().map(|(winner, _)| winner.to_string()).unwrap_or_default();
+    let expired: Vec<_> = page
+        .into_iter()
+        .filter(|(_, pending)| {
+            pending.claim_deadline.is_some_and(|deadline| env.block.time > deadline)
+        })
+        .collect();
+
+    let mut state = STATE.load(deps.storage)?;
+    let mut swept = Uint128::zero();
+    for (winner, pending) in &expired {
+        PENDING_PRIZES.remove(deps.storage, winner);
+        swept += pending.amount;
+    }
+    state.current_pot += swept;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sweep_expired_prizes")
+        .add_attribute("prizes", expired.len().to_string())
+        .add_attribute("amount", swept)
+        .add_attribute("current_pot", state.current_pot)
+        .add_attribute("last_key", last_key))
+}
+
+// Transfer everything `claim` took off the ledgers to its claimer. The reply credits
+// the prizes as winnings, or puts it all back if the transfer fails.
+pub(super) fn send_claim(
+    storage: &mut dyn Storage,
+    config: &Config,
+    claim: ClaimInFlight,
+) -> StdResult<SubMsg> {
+    let msg = WasmMsg::Execute {
+        contract_addr: config.reward_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: claim.claimer.to_string(),
+            amount: claim.amount(),
+        })?,
+        funds: vec![],
+    };
+    CLAIM_IN_FLIGHT.save(storage, &claim)?;
+    Ok(SubMsg::reply_always(msg, CLAIM_REPLY_ID))
+}
+
+pub(crate) fn reply_claim(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let claim = CLAIM_IN_FLIGHT.load(deps.storage)?;
+    CLAIM_IN_FLIGHT.remove(deps.storage);
+
+    if let SubMsgResult::Err(err) = msg.result {
+        restore_claim(deps.storage, &claim)?;
+        return Ok(Response::new()
+            .add_attribute("action", "claim_failed")
+            .add_attribute("amount", claim.amount())
+            .add_attribute("claimer", claim.claimer)
+            .add_attribute("error", err));
+    }
+
+    let winnings = claim.winnings();
+    if winnings.is_zero() {
+        return Ok(Response::new()
+            .add_attribute("action", "claim_confirmed")
+            .add_attribute("claimer", claim.claimer));
+    }
+    let total = credit_winnings(deps.storage, &env, &claim.claimer, winnings)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_confirmed")
+        .add_attribute("winner", claim.claimer)
+        .add_attribute("lifetime_winnings", total))
+}
+
+// Undo what a claim took off the ledgers. Nothing else can have touched them since, as
+// the reply runs in the same transaction as the claim.
+fn restore_claim(storage: &mut dyn Storage, claim: &ClaimInFlight) -> StdResult<()> {
+    if let Some(prize) = &claim.prize {
+        PENDING_PRIZES.save(storage, &claim.claimer, prize)?;
+    }
+    for (tier, prize) in &claim.tier_prizes {
+        TIER_PRIZES.save(storage, (tier.as_str(), &claim.claimer), prize)?;
+    }
+    if !claim.staking_rewards.is_zero() {
+        restore_staking_rewards(storage, &claim.claimer, claim.staking_rewards)?;
+    }
+    if let Some((id, amount)) = claim.merkle {
+        restore_merkle_claim(storage, &claim.claimer, id, amount)?;
+    }
+    Ok(())
+}
+
+// Replies come back in the order the draw sent the transfers, so each one settles the
+// oldest payout in flight. A failed transfer doesn't revert the draw; the prize is held
+// for the winner to claim instead.
+pub(crate) fn reply_push_payout(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let mut in_flight = PUSH_PAYOUTS_IN_FLIGHT.load(deps.storage)?;
+    if in_flight.is_empty() {
+        return Err(ContractError::UnknownReplyId { id: msg.id });
+    }
+    let (winner, amount) = in_flight.remove(0);
+    if in_flight.is_empty() {
+        PUSH_PAYOUTS_IN_FLIGHT.remove(deps.storage);
+    } else {
+        PUSH_PAYOUTS_IN_FLIGHT.save(deps.storage, &in_flight)?;
+    }
+
+    if let SubMsgResult::Err(err) = msg.result {
+        let config = CONFIG.load(deps.storage)?;
+        let claim_deadline = (config.claim_window_seconds > 0)
+            .then(|| env.block.time.plus_seconds(config.claim_window_seconds));
+        PENDING_PRIZES.update(deps.storage, &winner, |pending| -> StdResult<_> {
+            let unclaimed = pending.map_or(Uint128::zero(), |pending| pending.amount);
+            Ok(PendingPrize {
+                amount: unclaimed.checked_add(amount)?,
+                claim_deadline,
+            })
+        })?;
+        return Ok(Response::new()
+            .add_attribute("action", "push_payout_failed")
+            .add_attribute("winner", winner)
+            .add_attribute("amount", amount)
+            .add_attribute("error", err));
+    }
+
+    let total = credit_winnings(deps.storage, &env, &winner, amount)?;
+    Ok(Response::new()
+        .add_attribute("action", "push_payout_confirmed")
+        .add_attribute("winner", winner)
+        .add_attribute("lifetime_winnings", total))
+}
+
+// Count a prize that has left the contract towards the winner's lifetime winnings and
+// the leaderboard. Returns the new lifetime total.
+fn credit_winnings(
+    storage: &mut dyn Storage,
+    env: &Env,
+    winner: &Addr,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let total = TOTAL_WINNINGS
+        .may_load(storage, winner.to_string())?
+        .unwrap_or_default()
+        .checked_add(amount)
+        .map_err(StdError::from)?;
+    TOTAL_WINNINGS.save(storage, winner.to_string(), &total)?;
+
+    let mut leaderboard = PRIZE_LEADERBOARD.may_load(storage)?.unwrap_or_default();
+    leaderboard.record(winner.to_string(), total);
+    PRIZE_LEADERBOARD.save(storage, &leaderboard)?;
+
+    let mut state = STATE.load(storage)?;
+    state.total_prizes_paid += amount;
+    let nft_count = stakers()
+        .may_load(storage, winner)?
+        .map_or(0, |staker| staker.nft_count);
+    record_event(
+        storage,
+        &mut state,
+        env,
+        winner.as_str(),
+        StakeEventType::ClaimedReward,
+        nft_count,
+        None,
+    )?;
+    STATE.save(storage, &state)?;
+    Ok(total)
+}
@@ -0,0 +1,232 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use cw721::{Cw721QueryMsg, NftInfoResponse};
+
+use super::admin::record_admin_action;
+use crate::error::ContractError;
+use crate::msg::NftMetadata;
+use crate::state::{Collection, TraitWeight, COLLECTIONS, CONFIG, TOKEN_ALLOWLIST};
+
+pub fn execute_add_collection(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    weight: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if weight == 0 {
+        return Err(ContractError::InvalidCollectionWeight {});
+    }
+
+    // Tokens already staked keep the weight they were staked with
+    let address = deps.api.addr_validate(&address)?;
+    let collection = match COLLECTIONS.may_load(deps.storage, &address)? {
+        Some(collection) => Collection { weight, ..collection },
+        None => Collection {
+            weight,
+            staked: 0,
+            trait_weights: vec![],
+            allowlisted: 0,
+        },
+    };
+    COLLECTIONS.save(deps.storage, &address, &collection)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_collection")
+        .add_attribute("collection", address)
+        .add_attribute("weight", weight.to_string()))
+}
+
+pub fn execute_remove_collection(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    let collection = COLLECTIONS
+        .may_load(deps.storage, &address)?
+        .ok_or_else(|| ContractError::UnknownCollection {
+            collection: address.to_string(),
+        })?;
+    // Unstaking looks up the collection, so it has to outlive its tokens
+    if collection.staked > 0 {
+        return Err(ContractError::NftsInCustody {});
+    }
+    COLLECTIONS.remove(deps.storage, &address);
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_collection")
+        .add_attribute("collection", address))
+}
+
+pub fn execute_set_trait_weights(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    trait_weights: Vec<TraitWeight>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if trait_weights.iter().any(|trait_weight| trait_weight.weight == 0) {
+        return Err(ContractError::InvalidCollectionWeight {});
+    }
+
+    let address = deps.api.addr_validate(&collection)?;
+    let mut registered = COLLECTIONS
+        .may_load(deps.storage, &address)?
+        .ok_or(ContractError::UnknownCollection { collection })?;
+    registered.trait_weights = trait_weights;
+    COLLECTIONS.save(deps.storage, &address, &registered)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_trait_weights")
+        .add_attribute("collection", address)
+        .add_attribute("traits", registered.trait_weights.len().to_string()))
+}
+
+// Add token ids to the collection's allowlist. The first entries restrict staking to
+// the allowlist.
+pub fn execute_allow_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    update_allowlist(deps, env, info, collection, token_ids, true)
+}
+
+// Remove token ids from the collection's allowlist. Tokens already staked stay staked,
+// and removing the last entry opens the collection to every token again.
+pub fn execute_disallow_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    update_allowlist(deps, env, info, collection, token_ids, false)
+}
+
+fn update_allowlist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    token_ids: Vec<String>,
+    allow: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if token_ids.is_empty() {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+
+    let address = deps.api.addr_validate(&collection)?;
+    let mut registered = COLLECTIONS
+        .may_load(deps.storage, &address)?
+        .ok_or(ContractError::UnknownCollection { collection })?;
+    let mut changed = 0u64;
+    for token_id in &token_ids {
+        let key = (&address, token_id.as_str());
+        if TOKEN_ALLOWLIST.has(deps.storage, key) == allow {
+            continue;
+        }
+        if allow {
+            TOKEN_ALLOWLIST.save(deps.storage, key, &true)?;
+        } else {
+            TOKEN_ALLOWLIST.remove(deps.storage, key);
+        }
+        changed += 1;
+    }
+    if allow {
+        registered.allowlisted += changed;
+    } else {
+        registered.allowlisted -= changed;
+    }
+    COLLECTIONS.save(deps.storage, &address, &registered)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", if allow { "allow_tokens" } else { "disallow_tokens" })
+        .add_attribute("collection", address)
+        .add_attribute("changed", changed.to_string())
+        .add_attribute("allowlisted", registered.allowlisted.to_string()))
+}
+
+// Whether `token_id` passes the collection's allowlist, if it has one
+pub(crate) fn token_allowed(
+    storage: &dyn Storage,
+    address: &Addr,
+    collection: &Collection,
+    token_id: &str,
+) -> StdResult<bool> {
+    Ok(collection.allowlisted == 0 || TOKEN_ALLOWLIST.has(storage, (address, token_id)))
+}
+
+// Tickets `token_id` is staked for. Collections without trait weights skip the metadata
+// query.
+pub(crate) fn resolve_token_weight(
+    deps: Deps,
+    address: &Addr,
+    collection: &Collection,
+    token_id: &str,
+) -> StdResult<u64> {
+    if collection.trait_weights.is_empty() {
+        return Ok(collection.weight);
+    }
+    let info: NftInfoResponse<Option<NftMetadata>> = deps.querier.query_wasm_smart(
+        address,
+        &Cw721QueryMsg::NftInfo {
+            token_id: token_id.to_string(),
+        },
+    )?;
+    let attributes = info
+        .extension
+        .and_then(|metadata| metadata.attributes)
+        .unwrap_or_default();
+    Ok(collection
+        .trait_weights
+        .iter()
+        .filter(|trait_weight| {
+            attributes.iter().any(|attribute| {
+                attribute.trait_type == trait_weight.trait_type
+                    && attribute.value == trait_weight.value
+            })
+        })
+        .map(|trait_weight| trait_weight.weight)
+        .max()
+        .unwrap_or(collection.weight))
+}
+
+// Register `address` with weight 1 unless it already is, for collections set through
+// nft_contract
+pub(crate) fn ensure_collection(storage: &mut dyn Storage, address: &Addr) -> StdResult<()> {
+    if !COLLECTIONS.has(storage, address) {
+        let collection = Collection {
+            weight: 1,
+            staked: 0,
+            trait_weights: vec![],
+            allowlisted: 0,
+        };
+        COLLECTIONS.save(storage, address, &collection)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use cosmwasm_std::{DepsMut, Env, HexBinary, MessageInfo, Order, Response, StdResult, Storage};
+use sha2::{Digest, Sha256};
+
+use super::draw::complete_deferred_draw;
+use crate::error::ContractError;
+use crate::state::{
+    CommitRevealConfig, CommitRevealRound, State, CONFIG, ENTROPY_COMMITS, ENTROPY_REVEALS, STATE,
+};
+
+pub(super) fn open_commit_reveal_round(
+    deps: DepsMut,
+    env: &Env,
+    mut state: State,
+    commit_reveal: &CommitRevealConfig,
+) -> Result<Response, ContractError> {
+    let commit_deadline = env
+        .block
+        .time
+        .plus_seconds(commit_reveal.commit_window_seconds);
+    let reveal_deadline = commit_deadline.plus_seconds(commit_reveal.reveal_window_seconds);
+    state.pending_commit_reveal = Some(CommitRevealRound {
+        commit_deadline,
+        reveal_deadline,
+    });
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "open_commit_reveal")
+        .add_attribute("commit_deadline", commit_deadline.seconds().to_string())
+        .add_attribute("reveal_deadline", reveal_deadline.seconds().to_string()))
+}
+
+// A later commit replaces the earlier one while the commit window is open
+pub fn execute_commit_entropy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commitment: HexBinary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let round = open_round(deps.storage)?;
+    ensure_contributor(&config.commit_reveal, &info)?;
+    if env.block.time >= round.commit_deadline {
+        return Err(ContractError::CommitWindowClosed {});
+    }
+
+    ENTROPY_COMMITS.save(deps.storage, &info.sender, &commitment)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "commit_entropy")
+        .add_attribute("contributor", info.sender))
+}
+
+pub fn execute_reveal_entropy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    secret: HexBinary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let round = open_round(deps.storage)?;
+    ensure_contributor(&config.commit_reveal, &info)?;
+    if env.block.time < round.commit_deadline || env.block.time >= round.reveal_deadline {
+        return Err(ContractError::NotInRevealWindow {});
+    }
+
+    let commitment = ENTROPY_COMMITS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::RevealMismatch {})?;
+    if Sha256::digest(secret.as_slice())[..] != commitment[..] {
+        return Err(ContractError::RevealMismatch {});
+    }
+    ENTROPY_REVEALS.save(deps.storage, &info.sender, &secret)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reveal_entropy")
+        .add_attribute("contributor", info.sender))
+}
+
+// Runs once the reveal window has ended, or earlier when every commitment has been
+// revealed. The seed hashes the secrets in contributor address order, so no single
+// contributor can predict it. A contributor can still withhold their reveal after
+// seeing the others; with fewer than min_reveals the draw is dropped and the admin
+// draws again.
+pub fn execute_finalize_draw(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let round = state
+        .pending_commit_reveal
+        .clone()
+        .ok_or(ContractError::NoCommitRevealRound {})?;
+
+    let reveals = ENTROPY_REVEALS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let commits = ENTROPY_COMMITS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let all_revealed = !commits.is_empty()
+        && reveals.len() == commits.len()
+        && env.block.time >= round.commit_deadline;
+    if env.block.time < round.reveal_deadline && !all_revealed {
+        return Err(ContractError::RevealWindowOpen {});
+    }
+
+    for address in &commits {
+        ENTROPY_COMMITS.remove(deps.storage, address);
+        ENTROPY_REVEALS.remove(deps.storage, address);
+    }
+    state.pending_commit_reveal = None;
+
+    let min_reveals = config
+        .commit_reveal
+        .as_ref()
+        .map_or(1, |commit_reveal| commit_reveal.min_reveals as usize);
+    if reveals.len() < min_reveals {
+        STATE.save(deps.storage, &state)?;
+        return Ok(Response::new()
+            .add_attribute("action", "draw_cancelled")
+            .add_attribute("reveals", reveals.len().to_string()));
+    }
+
+    let mut hasher = Sha256::new();
+    for (_, secret) in &reveals {
+        hasher.update(secret.as_slice());
+    }
+    let seed = hasher.finalize();
+    let response = complete_deferred_draw(deps, &env, &config, state, &seed)?;
+    Ok(response.add_attribute("reveals", reveals.len().to_string()))
+}
+
+fn open_round(storage: &dyn Storage) -> Result<CommitRevealRound, ContractError> {
+    STATE
+        .load(storage)?
+        .pending_commit_reveal
+        .ok_or(ContractError::NoCommitRevealRound {})
+}
+
+fn ensure_contributor(
+    commit_reveal: &Option<CommitRevealConfig>,
+    info: &MessageInfo,
+) -> Result<(), ContractError> {
+    let is_contributor = commit_reveal
+        .as_ref()
+        .is_some_and(|commit_reveal| commit_reveal.contributors.contains(&info.sender));
+    if !is_contributor {
+        return Err(ContractError::NotAContributor {});
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response, StdResult, Storage, SubMsg, Uint128};
+
+use super::hooks::stake_changed_hooks;
+use super::voting::checkpoint_power;
+use crate::error::ContractError;
+use crate::msg::StakeChangedHookMsg;
+use crate::state::{stakers, Config, State, STAKER_SETTINGS};
+
+pub fn execute_set_auto_compound(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let mut settings = STAKER_SETTINGS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    settings.auto_compound = enabled;
+    STAKER_SETTINGS.save(deps.storage, &info.sender, &settings)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_auto_compound")
+        .add_attribute("staker", info.sender)
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+// With auto_compound on, as much of `prize` as buys whole tickets at compound_rate goes
+// back into the pot and the tickets are added to the winner's position for good.
+// Returns the tickets bought, what is left of the prize to claim and the stake hooks for
+// the new tickets. The caller saves `state`.
+pub(super) fn compound_prize(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    state: &mut State,
+    winner: &Addr,
+    prize: Uint128,
+) -> StdResult<(u64, Uint128, Vec<SubMsg>)> {
+    let settings = STAKER_SETTINGS.may_load(storage, winner)?.unwrap_or_default();
+    if !settings.auto_compound || config.compound_rate.is_zero() {
+        return Ok((0, prize, vec![]));
+    }
+    let tickets = u64::try_from((prize / config.compound_rate).u128()).unwrap_or(u64::MAX);
+    if tickets == 0 {
+        return Ok((0, prize, vec![]));
+    }
+    let cost = config.compound_rate * Uint128::from(tickets);
+
+    let mut staker = stakers().load(storage, winner)?;
+    let previous = staker.weight();
+    staker.compounded_weight += tickets;
+    stakers().save(storage, winner, &staker)?;
+    state.total_compounded_weight += tickets;
+    state.current_pot += cost;
+    checkpoint_power(storage, env, winner, previous, staker.weight(), state.total_weight())?;
+    let hook_msg = StakeChangedHookMsg::Stake {
+        addr: winner.clone(),
+        amount: Uint128::from(tickets),
+    };
+
+    Ok((tickets, prize - cost, stake_changed_hooks(storage, hook_msg)?))
+}
@@ -0,0 +1,107 @@
+use cosmwasm_std::{
+    to_json_binary, Coin, DepsMut, Env, MessageInfo, Reply, Response, StdError, SubMsg, WasmMsg,
+};
+
+use super::admin::record_admin_action;
+use super::fund::coins_to_string;
+use crate::error::ContractError;
+use crate::msg::{
+    CronCatAction, CronCatInterval, CronCatTaskRequest, CronCatTasksExecuteMsg, ExecuteMsg,
+};
+use crate::state::{CONFIG, DRAW_TASK, STATE};
+
+pub const CRONCAT_REPLY_ID: u64 = 4;
+
+// Register the CronCat task that calls DrawWinner on the cron schedule. Calling it again
+// removes the old task first, e.g. to change the schedule or top up a task that ran dry.
+// The task fails harmlessly on runs where the cooldown hasn't passed yet.
+pub fn execute_schedule_draws(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cron: String,
+    funding: Coin,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    record_admin_action(deps.storage, &env)?;
+    let croncat = config.croncat.ok_or(ContractError::CronCatNotConfigured {})?;
+    if funding.amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    // The task's gas is paid from the native side of the pot
+    let mut state = STATE.load(deps.storage)?;
+    let held = state
+        .native_pot
+        .iter_mut()
+        .find(|coin| coin.denom == funding.denom)
+        .filter(|coin| coin.amount >= funding.amount)
+        .ok_or_else(|| ContractError::InsufficientNativePot {
+            required: funding.clone(),
+        })?;
+    held.amount -= funding.amount;
+    state.native_pot.retain(|coin| !coin.amount.is_zero());
+    STATE.save(deps.storage, &state)?;
+
+    let mut response = Response::new();
+    if let Some(task_hash) = DRAW_TASK.may_load(deps.storage)? {
+        // CronCat refunds whatever the old task had left to this contract
+        response = response
+            .add_message(WasmMsg::Execute {
+                contract_addr: croncat.tasks.to_string(),
+                msg: to_json_binary(&CronCatTasksExecuteMsg::RemoveTask {
+                    task_hash: task_hash.clone(),
+                })?,
+                funds: vec![],
+            })
+            .add_attribute("removed_task", task_hash);
+        DRAW_TASK.remove(deps.storage);
+    }
+
+    let task = CronCatTaskRequest {
+        interval: CronCatInterval::Cron(cron.clone()),
+        stop_on_fail: false,
+        actions: vec![CronCatAction {
+            msg: WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_json_binary(&ExecuteMsg::DrawWinner {})?,
+                funds: vec![],
+            }
+            .into(),
+            gas_limit: None,
+        }],
+    };
+    let create_msg = WasmMsg::Execute {
+        contract_addr: croncat.tasks.to_string(),
+        msg: to_json_binary(&CronCatTasksExecuteMsg::CreateTask {
+            task: Box::new(task),
+        })?,
+        funds: vec![funding.clone()],
+    };
+
+    Ok(response
+        .add_submessage(SubMsg::reply_on_success(create_msg, CRONCAT_REPLY_ID))
+        .add_attribute("action", "schedule_draws")
+        .add_attribute("cron", cron)
+        .add_attribute("funding", funding.to_string())
+        .add_attribute("native_pot", coins_to_string(&state.native_pot)))
+}
+
+// CreateTask answers with the task hash as its data
+pub(crate) fn reply_croncat(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let data = msg
+        .result
+        .into_result()
+        .map_err(StdError::generic_err)?
+        .data
+        .ok_or_else(|| StdError::generic_err("CronCat returned no task hash"))?;
+    let task_hash = String::from_utf8(data.to_vec()).map_err(StdError::from)?;
+    DRAW_TASK.save(deps.storage, &task_hash)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "draw_task_created")
+        .add_attribute("task_hash", task_hash))
+}
@@ -0,0 +1,76 @@
+use cosmwasm_std::{DepsMut, Env, HexBinary, Response, Timestamp};
+use drand_verify::{derive_randomness, G1Pubkey, Pubkey};
+
+use super::draw::complete_deferred_draw;
+use crate::error::ContractError;
+use crate::state::{DrandConfig, State, CONFIG, STATE};
+
+// Commit the pending draw to the first round published after this block, so nobody can
+// know its beacon when the draw is requested
+pub(super) fn request_drand_round(
+    deps: DepsMut,
+    env: &Env,
+    mut state: State,
+    drand: &DrandConfig,
+) -> Result<Response, ContractError> {
+    let round = next_drand_round(drand, env.block.time);
+    state.pending_drand_round = Some(round);
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "request_drand_round")
+        .add_attribute("drand_round", round.to_string()))
+}
+
+pub fn execute_submit_drand_beacon(
+    deps: DepsMut,
+    env: Env,
+    round: u64,
+    previous_signature: HexBinary,
+    signature: HexBinary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    let expected = state
+        .pending_drand_round
+        .ok_or(ContractError::NoDrandRoundPending {})?;
+    if round != expected {
+        return Err(ContractError::WrongDrandRound { expected });
+    }
+    let drand = config
+        .drand
+        .as_ref()
+        .ok_or(ContractError::NoDrandRoundPending {})?;
+    verify_beacon(drand, round, &previous_signature, &signature)?;
+    state.pending_drand_round = None;
+
+    let randomness = derive_randomness(signature.as_slice());
+    let response = complete_deferred_draw(deps, &env, &config, state, &randomness)?;
+    Ok(response.add_attribute("drand_round", round.to_string()))
+}
+
+// Round r is published at genesis_time + (r - 1) * period_seconds, so the current round
+// is elapsed / period + 1 and the one after it is still in the future
+fn next_drand_round(drand: &DrandConfig, now: Timestamp) -> u64 {
+    let elapsed = now.seconds().saturating_sub(drand.genesis_time);
+    elapsed / drand.period_seconds + 2
+}
+
+fn verify_beacon(
+    drand: &DrandConfig,
+    round: u64,
+    previous_signature: &HexBinary,
+    signature: &HexBinary,
+) -> Result<(), ContractError> {
+    let pubkey: [u8; 48] = drand
+        .pubkey
+        .as_slice()
+        .try_into()
+        .map_err(|_| ContractError::InvalidDrandConfig {})?;
+    let pubkey = G1Pubkey::from_fixed(pubkey).map_err(|_| ContractError::InvalidDrandConfig {})?;
+    match pubkey.verify(round, previous_signature.as_slice(), signature.as_slice()) {
+        Ok(true) => Ok(()),
+        _ => Err(ContractError::InvalidDrandSignature { round }),
+    }
+}
@@ -0,0 +1,542 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Coin, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+    SubMsg, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use sha2::{Digest, Sha256};
+
+use super::burn::{pick_sacrifice, sacrifice_token};
+use super::claim::PUSH_REPLY_ID;
+use super::commit_reveal::open_commit_reveal_round;
+use super::compound::compound_prize;
+use super::drand::request_drand_round;
+use super::fund::{coins_to_string, pot_send, pot_transfer};
+use super::history::record_event;
+use super::interval::update_next_draw_interval;
+use super::tiers::draw_tiers;
+use crate::error::ContractError;
+use crate::lottery::{candidate_page, pick_winners, pool_totals, PoolTotals};
+use crate::msg::{BadgeExecuteMsg, MintMsg, NoisCallback, NoisProxyExecuteMsg, WinnerBadgeExt};
+use crate::state::{
+    stakers, Config, JackpotHit, PendingPrize, Round, SacrificedNft, StakeEventType, State,
+    CONFIG, DRAW_SNAPSHOTS, LAST_WIN_ROUND, PENDING_PRIZES, PUSH_PAYOUTS_IN_FLIGHT, ROUNDS, STATE,
+    WIN_COUNTS,
+};
+
+pub const BADGE_REPLY_ID: u64 = 3;
+
+// Candidates read per page while a draw snapshots the pool
+const SNAPSHOT_PAGE_SIZE: usize = 50;
+
+// Who started a draw. Only the admin's draws count as admin activity and only keepers
+// are paid. Chain covers every scheduled draw, from sudo or the CronCat manager.
+enum DrawTrigger {
+    Admin,
+    Keeper(Addr),
+    Chain,
+}
+
+pub fn execute_draw_winner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let scheduled = matches!(&config.croncat, Some(croncat) if info.sender == croncat.manager);
+    let trigger = if info.sender == config.admin {
+        DrawTrigger::Admin
+    } else if scheduled {
+        DrawTrigger::Chain
+    } else if config.keeper_reward_bps > 0 {
+        DrawTrigger::Keeper(info.sender)
+    } else {
+        return Err(ContractError::Unauthorized {});
+    };
+    start_draw(deps, env, config, trigger, info.funds)
+}
+
+// Scheduled draw from the chain itself, e.g. an end-blocker hook or a governance proposal
+pub fn sudo_trigger_draw(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    start_draw(deps, env, config, DrawTrigger::Chain, vec![])
+}
+
+fn start_draw(
+    mut deps: DepsMut,
+    env: Env,
+    config: Config,
+    trigger: DrawTrigger,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if state.pending_draw_job.is_some()
+        || state.pending_drand_round.is_some()
+        || state.pending_commit_reveal.is_some()
+    {
+        return Err(ContractError::DrawInProgress {});
+    }
+    
+    if let Some(last_draw_time) = state.last_draw_time {
+        let next_draw_time = last_draw_time.plus_seconds(draw_cooldown_seconds(&config, &state));
+        if env.block.time < next_draw_time {
+            return Err(ContractError::DrawCooldownActive {
+                remaining_secs: next_draw_time.seconds() - env.block.time.seconds(),
+            });
+        }
+    }
+    
+    // Keeper and chain draws don't show the admin is still around
+    if let DrawTrigger::Admin = trigger {
+        state.last_admin_action_time = env.block.time;
+    }
+    
+    // Same selection SimulateDraw runs. With an external randomness source this fails
+    // now rather than once the randomness arrives.
+    let totals = pool_totals(deps.as_ref(), &env, &config)?;
+    if totals.entries == 0 {
+        return Err(ContractError::NoEligibleStakers {});
+    }
+    
+    // The keeper is paid up front, so the winner draws from what is left
+    let keeper_reward = match &trigger {
+        DrawTrigger::Keeper(_) => {
+            state.current_pot.multiply_ratio(config.keeper_reward_bps, 10_000u128)
+        }
+        _ => Uint128::zero(),
+    };
+    state.current_pot -= keeper_reward;
+    
+    let response = if let Some(nois_proxy) = &config.nois_proxy {
+        request_nois_randomness(deps.branch(), state, nois_proxy, funds)?
+    } else if let Some(drand) = &config.drand {
+        request_drand_round(deps.branch(), &env, state, drand)?
+    } else if let Some(commit_reveal) = &config.commit_reveal {
+        open_commit_reveal_round(deps.branch(), &env, state, commit_reveal)?
+    } else {
+        // A seed from the block header is known to its proposer, who could steer the draw
+        return Err(ContractError::NoEntropyAvailable {});
+    };
+    let DrawTrigger::Keeper(keeper) = trigger else {
+        return Ok(response);
+    };
+    if keeper_reward.is_zero() {
+        return Ok(response);
+    }
+    // Queued after the draw's own payouts, in the order the messages go out
+    Ok(response
+        .add_submessage(pot_transfer(deps.storage, &config, &keeper, keeper_reward)?)
+        .add_attribute("keeper", keeper)
+        .add_attribute("keeper_reward", keeper_reward))
+}
+
+fn request_nois_randomness(
+    deps: DepsMut,
+    mut state: State,
+    nois_proxy: &Addr,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let job_id = format!("draw_{}", state.draw_count + 1);
+    state.pending_draw_job = Some(job_id.clone());
+    STATE.save(deps.storage, &state)?;
+    
+    let msg = WasmMsg::Execute {
+        contract_addr: nois_proxy.to_string(),
+        msg: to_json_binary(&NoisProxyExecuteMsg::GetNextRandomness {
+            job_id: job_id.clone(),
+        })?,
+        funds,
+    };
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "request_randomness")
+        .add_attribute("job_id", job_id))
+}
+
+// Completes the draw requested under callback.job_id
+pub fn execute_nois_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback: NoisCallback,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.nois_proxy.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    
+    let mut state = STATE.load(deps.storage)?;
+    if state.pending_draw_job.as_deref() != Some(callback.job_id.as_str()) {
+        return Err(ContractError::UnknownNoisJob {
+            job_id: callback.job_id,
+        });
+    }
+    state.pending_draw_job = None;
+    
+    let response =
+        complete_deferred_draw(deps, &env, &config, state, callback.randomness.as_slice())?;
+    Ok(response.add_attribute("job_id", callback.job_id))
+}
+
+// Finish a draw whose randomness arrived after DrawWinner. The pool is read again here,
+// so positions changed while the randomness was pending count as they are now. If
+// nobody is eligible any more the draw is dropped instead of failing, so the admin can
+// draw again.
+pub(super) fn complete_deferred_draw(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    state: State,
+    entropy: &[u8],
+) -> Result<Response, ContractError> {
+    let totals = pool_totals(deps.as_ref(), env, config)?;
+    if totals.entries == 0 {
+        STATE.save(deps.storage, &state)?;
+        return Ok(Response::new().add_attribute("action", "draw_cancelled"));
+    }
+    complete_draw(deps, env, config, state, totals, entropy)
+}
+
+// Pick the winners from the pool `totals` was counted over with `entropy` and settle
+// the round
+fn complete_draw(
+    mut deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    mut state: State,
+    totals: PoolTotals,
+    entropy: &[u8],
+) -> Result<Response, ContractError> {
+    let places = config.prize_places();
+    let winners = pick_winners(deps.as_ref(), env, config, &totals, entropy, places.len())?;
+    let first = winners.first().cloned().ok_or(ContractError::NoEligibleStakers {})?;
+    
+    // Keep every candidate's weight for this round for later disputes
+    state.draw_count += 1;
+    let round_id = state.draw_count;
+    let total_tickets = snapshot_pool(deps.branch(), env, config, &totals, round_id)?;
+    
+    let started_at = state.last_draw_time;
+    state.last_winner = Some(first.clone());
+    state.last_draw_time = Some(env.block.time);
+    // The protocol fee comes off the top, then the owner of the token burned at the stake
+    // is compensated before the split
+    let pot = state.current_pot;
+    let protocol_fee = match &config.treasury {
+        Some(_) => pot.multiply_ratio(config.protocol_fee_bps, 10_000u128),
+        None => Uint128::zero(),
+    };
+    let sacrifice = match config.sacrifice_bps {
+        0 => None,
+        _ => pick_sacrifice(deps.storage, entropy)?,
+    };
+    let compensation = match &sacrifice {
+        Some(_) => (pot - protocol_fee).multiply_ratio(config.sacrifice_bps, 10_000u128),
+        None => Uint128::zero(),
+    };
+    // A slice feeds the jackpot, then what is left is split three ways: the rollover stays
+    // in the pot, the burn leaves it for good and the winners share the rest
+    let available = pot - protocol_fee - compensation;
+    let (jackpot_contribution, drawn) = match &config.jackpot {
+        Some(jackpot) => split_jackpot(available, jackpot.share_bps),
+        None => (Uint128::zero(), available),
+    };
+    state.jackpot += jackpot_contribution;
+    let rollover = drawn.multiply_ratio(config.pot_split.rollover_bps, 10_000u128);
+    let burned = drawn.multiply_ratio(config.pot_split.burn_bps, 10_000u128);
+    let winner_share = drawn - rollover - burned;
+    // The winner share is split between the places; shares of places nobody was left to
+    // fill stay in the pot for the next round
+    let mut prizes = split_prize(winner_share, &places);
+    state.current_pot = pot
+        - protocol_fee
+        - compensation
+        - jackpot_contribution
+        - burned
+        - prizes[..winners.len()].iter().copied().sum::<Uint128>();
+    // The whole jackpot, this round's slice included, goes to first place when it hits
+    let jackpot_paid = match &config.jackpot {
+        Some(jackpot) if jackpot_triggers(entropy, jackpot.odds) => {
+            std::mem::take(&mut state.jackpot)
+        }
+        _ => Uint128::zero(),
+    };
+    if !jackpot_paid.is_zero() {
+        prizes[0] += jackpot_paid;
+        state.last_jackpot = Some(JackpotHit {
+            round_id,
+            winner: first.clone(),
+            amount: jackpot_paid,
+            triggered_at: env.block.time,
+        });
+    }
+    // Native coins are paid out right away; the reward token side is claimed
+    let native_pot = std::mem::take(&mut state.native_pot);
+    let native_splits: Vec<(String, Vec<Uint128>)> = native_pot
+        .iter()
+        .map(|coin| (coin.denom.clone(), split_prize(coin.amount, &places)))
+        .collect();
+    for (denom, shares) in &native_splits {
+        let left = shares[winners.len()..].iter().copied().sum::<Uint128>();
+        if !left.is_zero() {
+            state.native_pot.push(Coin::new(left.u128(), denom));
+        }
+    }
+    
+    let claim_deadline = (config.claim_window_seconds > 0)
+        .then(|| env.block.time.plus_seconds(config.claim_window_seconds));
+    let mut response = Response::new();
+    if let (Some(treasury), false) = (&config.treasury, protocol_fee.is_zero()) {
+        response = response
+            .add_submessage(pot_transfer(deps.storage, config, treasury, protocol_fee)?)
+            .add_attribute("protocol_fee", protocol_fee);
+    }
+    if !burned.is_zero() {
+        response = response
+            .add_message(WasmMsg::Execute {
+                contract_addr: config.reward_token.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount: burned })?,
+                funds: vec![],
+            })
+            .add_attribute("burned", burned);
+    }
+    if !rollover.is_zero() {
+        response = response.add_attribute("rollover", rollover);
+    }
+    if !jackpot_paid.is_zero() {
+        response = response.add_attribute("jackpot", jackpot_paid);
+    }
+    let mut pushed = PUSH_PAYOUTS_IN_FLIGHT.may_load(deps.storage)?.unwrap_or_default();
+    for (place, winner) in winners.iter().enumerate() {
+        let winner_addr = Addr::unchecked(winner);
+        // Compounded prizes turn into tickets; only the rest is left to claim
+        let (compounded, prize, hook_msgs) =
+            compound_prize(deps.storage, env, config, &mut state, &winner_addr, prizes[place])?;
+        if compounded > 0 {
+            response = response
+                .add_submessages(hook_msgs)
+                .add_attribute("compounded", format!("{}:{}", winner, compounded));
+        }
+        if config.push_payouts && !prize.is_zero() {
+            // Paid in this transaction; the reply falls back to a pending claim if the
+            // transfer fails
+            let msg = WasmMsg::Execute {
+                contract_addr: config.reward_token.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: winner.clone(),
+                    amount: prize,
+                })?,
+                funds: vec![],
+            };
+            response = response.add_submessage(SubMsg::reply_always(msg, PUSH_REPLY_ID));
+            pushed.push((winner_addr.clone(), prize));
+        } else if !prize.is_zero() {
+            PENDING_PRIZES.update(deps.storage, &winner_addr, |pending| -> StdResult<_> {
+                let unclaimed = pending.map_or(Uint128::zero(), |pending| pending.amount);
+                Ok(PendingPrize {
+                    amount: unclaimed.checked_add(prize)?,
+                    claim_deadline,
+                })
+            })?;
+        }
+        
+        WIN_COUNTS.update(deps.storage, &winner_addr, |wins| -> StdResult<_> {
+            Ok(wins.unwrap_or_default() + 1)
+        })?;
+        LAST_WIN_ROUND.save(deps.storage, &winner_addr, &round_id)?;
+        let nft_count = stakers().load(deps.storage, &winner_addr)?.nft_count;
+        record_event(
+            deps.storage,
+            &mut state,
+            env,
+            winner,
+            StakeEventType::WonDraw,
+            nft_count,
+            None,
+        )?;
+        
+        let native_prize: Vec<Coin> = native_splits
+            .iter()
+            .filter(|(_, shares)| !shares[place].is_zero())
+            .map(|(denom, shares)| Coin::new(shares[place].u128(), denom))
+            .collect();
+        if !native_prize.is_empty() {
+            response =
+                response.add_submessage(pot_send(deps.storage, &winner_addr, native_prize)?);
+        }
+        if let Some(badge_contract) = &config.badge_nft_contract {
+            let token_id = match place {
+                0 => format!("win_round_{}", round_id),
+                _ => format!("win_round_{}_place_{}", round_id, place + 1),
+            };
+            let mint_msg = BadgeExecuteMsg::Mint(MintMsg {
+                token_id,
+                owner: winner.clone(),
+                token_uri: None,
+                extension: WinnerBadgeExt {
+                    round: round_id,
+                    prize: prizes[place],
+                    eligible_stakers: totals.entries,
+                    timestamp: env.block.time,
+                },
+            });
+            let msg = WasmMsg::Execute {
+                contract_addr: badge_contract.to_string(),
+                msg: to_json_binary(&mint_msg)?,
+                funds: vec![],
+            };
+            // A failed mint must not revert the draw
+            response = response.add_submessage(SubMsg::reply_on_error(msg, BADGE_REPLY_ID));
+        }
+    }
+    if !pushed.is_empty() {
+        PUSH_PAYOUTS_IN_FLIGHT.save(deps.storage, &pushed)?;
+    }
+    // Burned after the winners are recorded, so a sacrificed winner's event still shows
+    // the position it won with
+    let sacrificed = match sacrifice {
+        Some((owner, token)) => {
+            let (collection, token_id) = (token.0.clone(), token.1.clone());
+            let burned = sacrifice_token(
+                deps.storage,
+                env,
+                config,
+                &mut state,
+                &owner,
+                token,
+                compensation,
+            )?;
+            response = response
+                .add_submessages(burned.messages)
+                .add_attribute("sacrificed", format!("{}/{}", collection, token_id))
+                .add_attribute("sacrificed_owner", &owner)
+                .add_attribute("compensation", compensation);
+            Some(SacrificedNft {
+                owner: owner.to_string(),
+                collection,
+                token_id,
+                compensation,
+            })
+        }
+        None => None,
+    };
+    ROUNDS.save(
+        deps.storage,
+        round_id,
+        &Round {
+            round_id,
+            pot,
+            native_pot,
+            winners: winners.iter().cloned().zip(prizes.iter().copied()).collect(),
+            entropy: Binary::from(entropy),
+            entry_count: totals.entries,
+            total_tickets,
+            started_at,
+            drawn_at: env.block.time,
+            block_height: env.block.height,
+            sacrifice: sacrificed,
+            protocol_fee,
+            winner_share,
+            rollover,
+            burned,
+            jackpot_contribution,
+            jackpot_paid,
+        },
+    )?;
+    let tier_results = draw_tiers(deps.branch(), env, config, &totals, entropy)?;
+    for (tier, tier_winners) in tier_results {
+        response = response.add_attribute(format!("tier_{}_winners", tier), tier_winners.join(","));
+    }
+    update_next_draw_interval(deps.storage, &mut state)?;
+    STATE.save(deps.storage, &state)?;
+    
+    let first_native_prize: Vec<Coin> = native_splits
+        .iter()
+        .filter(|(_, shares)| !shares[0].is_zero())
+        .map(|(denom, shares)| Coin::new(shares[0].u128(), denom))
+        .collect();
+    Ok(response
+        .add_attribute("action", "draw_winner")
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("winner", &first)
+        .add_attribute("prize", prizes[0])
+        .add_attribute("native_prize", coins_to_string(&first_native_prize))
+        .add_attribute("winners", winners.join(","))
+        .add_attribute("next_draw_interval_seconds", state.next_draw_interval_seconds.to_string()))
+}
+
+// Save every candidate's capped weight under `round_id`, a page of the pool at a time,
+// returning the tickets in the draw. Stops after the totals.entries candidates that made
+// it under max_entries_per_round.
+fn snapshot_pool(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    totals: &PoolTotals,
+    round_id: u64,
+) -> StdResult<u64> {
+    let mut total_tickets = 0u64;
+    let mut remaining = totals.entries as usize;
+    let mut start_after: Option<(u64, Addr)> = None;
+    loop {
+        let limit = remaining.min(SNAPSHOT_PAGE_SIZE);
+        if limit == 0 {
+            return Ok(total_tickets);
+        }
+        let cursor = start_after.as_ref().map(|(staked_at, address)| (*staked_at, address));
+        let mut page = candidate_page(deps.as_ref(), env, config, totals, cursor, limit)?;
+        for (address, _, weight) in &page {
+            DRAW_SNAPSHOTS.save(deps.storage, (round_id, address.to_string()), weight)?;
+            total_tickets += weight;
+        }
+        remaining -= page.len();
+        if page.len() < limit {
+            return Ok(total_tickets);
+        }
+        start_after = page
+            .pop()
+            .map(|(address, staker, _)| (staker.staked_at.seconds(), address));
+    }
+}
+
+// Whether this draw pays out the jackpot: a 1 in `odds` chance, hashed from the draw's
+// entropy with its own tag so it doesn't follow the winner pick
+fn jackpot_triggers(entropy: &[u8], odds: u32) -> bool {
+    let digest = Sha256::digest([entropy, b"jackpot"].concat());
+    u64::from_be_bytes(digest[0..8].try_into().unwrap()) % odds as u64 == 0
+}
+
+// Split what is left of the pot after the protocol fee and compensation into
+// (jackpot contribution, drawn) for a jackpot share in basis points
+pub fn split_jackpot(available: Uint128, share_bps: u16) -> (Uint128, Uint128) {
+    let contribution = available.multiply_ratio(share_bps, 10_000u128);
+    (contribution, available - contribution)
+}
+
+// Shares of `amount` per place in basis points. Rounding dust goes to first place.
+pub fn split_prize(amount: Uint128, places: &[u16]) -> Vec<Uint128> {
+    let mut shares: Vec<Uint128> = places
+        .iter()
+        .map(|bps| amount.multiply_ratio(*bps as u128, 10_000u128))
+        .collect();
+    let dust = amount - shares.iter().copied().sum::<Uint128>();
+    shares[0] += dust;
+    shares
+}
+
+// Time the next draw has to wait after the last one. A pot at or above the target
+// shortens the voted interval to the accelerated cooldown.
+pub(crate) fn draw_cooldown_seconds(config: &Config, state: &State) -> u64 {
+    if !config.target_pot_size.is_zero() && state.current_pot >= config.target_pot_size {
+        config.accelerated_cooldown_seconds.min(state.next_draw_interval_seconds)
+    } else {
+        state.next_draw_interval_seconds
+    }
+}
+
+// Only called when the badge mint failed; the draw itself stands
+pub(crate) fn reply_badge(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let error = msg.result.into_result().err().unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("action", "badge_mint_failed")
+        .add_attribute("error", error))
+}
@@ -0,0 +1,59 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+
+use super::admin::record_admin_action;
+use crate::error::ContractError;
+use crate::state::{CONFIG, DRAW_EXCLUSIONS};
+
+// Keep `addresses` out of draws. Their stakes still count for voting power.
+pub fn execute_exclude_from_draws(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    update_exclusions(deps, env, info, addresses, true)
+}
+
+pub fn execute_include_in_draws(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    update_exclusions(deps, env, info, addresses, false)
+}
+
+fn update_exclusions(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addresses: Vec<String>,
+    exclude: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if addresses.is_empty() {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+
+    let mut changed = 0u64;
+    for address in &addresses {
+        let address = deps.api.addr_validate(address)?;
+        if DRAW_EXCLUSIONS.has(deps.storage, &address) == exclude {
+            continue;
+        }
+        if exclude {
+            DRAW_EXCLUSIONS.save(deps.storage, &address, &true)?;
+        } else {
+            DRAW_EXCLUSIONS.remove(deps.storage, &address);
+        }
+        changed += 1;
+    }
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", if exclude { "exclude_from_draws" } else { "include_in_draws" })
+        .add_attribute("changed", changed.to_string()))
+}
@@ -0,0 +1,164 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128};
+
+use super::admin::record_admin_action;
+use super::fund::pot_transfer;
+use super::history::record_event;
+use super::hooks::stake_changed_hooks;
+use super::receipt::{receipt_burn_msg, receipt_token_id};
+use super::unstake::{remove_stake, return_nft_msg};
+use super::voting::checkpoint_power;
+use crate::error::ContractError;
+use crate::msg::StakeChangedHookMsg;
+use crate::state::{stakers, StakeEventType, COLLECTIONS, CONFIG, STAKED_NFTS, STATE, UNBONDING};
+
+// Escape hatch for a contract whose admin has stopped drawing. The sender's position is
+// closed so the same stake can't be paid out twice, and each exit leaves the remaining
+// stakers' shares of what is left unchanged.
+pub fn execute_abandonment_exit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    if config.abandonment_timeout_seconds == 0 {
+        return Err(ContractError::AbandonmentExitDisabled {});
+    }
+    let abandoned_at = state
+        .last_admin_action_time
+        .plus_seconds(config.abandonment_timeout_seconds);
+    if env.block.time <= abandoned_at {
+        return Err(ContractError::NotAbandoned {});
+    }
+
+    let staker = stakers()
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NothingStaked {})?;
+
+    // A position of burned tickets alone may outlast every staked NFT
+    let share = match state.total_staked {
+        0 => Uint128::zero(),
+        total_staked => state.current_pot.multiply_ratio(staker.nft_count, total_staked),
+    };
+    state.current_pot -= share;
+    state.total_staked -= staker.nft_count;
+    state.total_bonus_weight -= staker.bonus_weight;
+    state.total_burned_weight -= staker.burned_weight;
+    state.total_compounded_weight -= staker.compounded_weight;
+    state.staker_count -= 1;
+    stakers().remove(deps.storage, &info.sender)?;
+    let previous = staker.weight();
+    checkpoint_power(deps.storage, &env, &info.sender, previous, 0, state.total_weight())?;
+    let tokens = STAKED_NFTS
+        .sub_prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((collection, token_id), _) in &tokens {
+        STAKED_NFTS.remove(deps.storage, (&info.sender, collection, token_id));
+        let mut registered = COLLECTIONS.load(deps.storage, collection)?;
+        registered.staked -= 1;
+        COLLECTIONS.save(deps.storage, collection, &registered)?;
+    }
+
+    record_event(
+        deps.storage,
+        &mut state,
+        &env,
+        info.sender.as_str(),
+        StakeEventType::Unstaked,
+        0,
+        None,
+    )?;
+    STATE.save(deps.storage, &state)?;
+
+    let hook_msg = StakeChangedHookMsg::Unstake {
+        addr: info.sender.clone(),
+        amount: Uint128::from(staker.weight()),
+    };
+    let mut response = Response::new().add_submessages(stake_changed_hooks(deps.storage, hook_msg)?);
+    for ((collection, token_id), _) in &tokens {
+        response = response.add_message(return_nft_msg(collection, &info.sender, token_id)?);
+    }
+    // Tokens staked before receipts were named after their token hold numbered ones,
+    // like wallet NFTs
+    let named: Vec<_> = tokens
+        .iter()
+        .filter_map(|(_, staked)| staked.receipt_id.clone())
+        .collect();
+    let numbered = staker.nft_count - named.len() as u64;
+    let receipt_ids = named
+        .into_iter()
+        .chain((1..=numbered).map(|index| receipt_token_id(&info.sender, index)));
+    for receipt_id in receipt_ids {
+        response = response.add_messages(receipt_burn_msg(&config, receipt_id)?);
+    }
+    if !share.is_zero() {
+        let payout = pot_transfer(deps.storage, &config, &info.sender, share)?;
+        response = response.add_submessage(payout);
+    }
+
+    Ok(response
+        .add_attribute("action", "abandonment_exit")
+        .add_attribute("sender", info.sender)
+        .add_attribute("nft_count", staker.nft_count.to_string())
+        .add_attribute("amount", share))
+}
+
+// Wind-down for a contract that has to close. Each call hands back a page of custody
+// tokens, staked ones first, shrinking their positions as it goes so the pool and voting
+// power stay consistent in between. Pause staking first so no new tokens arrive.
+pub fn execute_emergency_return_nfts(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: u32,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if limit == 0 {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+
+    let mut response = Response::new();
+    let staked = STAKED_NFTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+    let returned_staked = staked.len();
+    for ((owner, collection, token_id), nft) in staked {
+        let staker = stakers().load(deps.storage, &owner)?;
+        let token = Some((collection, token_id, nft));
+        let removed = remove_stake(deps.branch(), &env, &owner, staker, token, false)?;
+        response = response.add_submessages(removed.messages);
+    }
+
+    // Then tokens already unstaked and waiting out the unbonding period
+    let mut budget = limit as usize - returned_staked;
+    let owners = UNBONDING
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(budget)
+        .collect::<StdResult<Vec<_>>>()?;
+    for owner in &owners {
+        let mut unbonding = UNBONDING.load(deps.storage, owner)?;
+        let count = budget.min(unbonding.len());
+        for nft in unbonding.drain(..count) {
+            response = response.add_message(return_nft_msg(&nft.collection, owner, &nft.token_id)?);
+        }
+        if unbonding.is_empty() {
+            UNBONDING.remove(deps.storage, owner);
+        } else {
+            UNBONDING.save(deps.storage, owner, &unbonding)?;
+        }
+        budget -= count;
+    }
+    record_admin_action(deps.storage, &env)?;
+
+    let is_complete = STAKED_NFTS.is_empty(deps.storage) && UNBONDING.is_empty(deps.storage);
+    Ok(response
+        .add_attribute("action", "emergency_return_nfts")
+        .add_attribute("returned", (limit as usize - budget).to_string())
+        .add_attribute("is_complete", is_complete.to_string()))
+}
@@ -0,0 +1,292 @@
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Reply,
+    Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+use super::merkle::fund_merkle_distribution;
+use super::pause::ensure_not_paused;
+use super::rewards::fund_rewards;
+use super::tiers::fund_tier;
+use super::unstake::emergency_unstake;
+use crate::error::ContractError;
+use crate::msg::ReceiveMsg;
+use crate::state::{
+    Config, PausableAction, PotPayout, CONFIG, FUND_IN_FLIGHT, POT_PAYOUTS_IN_FLIGHT, STATE,
+};
+
+pub const FUND_REPLY_ID: u64 = 2;
+pub const POT_PAYOUT_REPLY_ID: u64 = 7;
+
+// Add the native coins attached to the message to the pot. Reward tokens go through a
+// cw20 Send with a FundPot hook message, or FundPotByAllowance.
+pub fn execute_fund_pot(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.funds.iter().all(|coin| coin.amount.is_zero()) {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let mut state = STATE.load(deps.storage)?;
+    add_coins(&mut state.native_pot, &info.funds)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "fund_pot")
+        .add_attribute("funder", info.sender)
+        .add_attribute("amount", coins_to_string(&info.funds))
+        .add_attribute("native_pot", coins_to_string(&state.native_pot)))
+}
+
+// cw20 Send hook. Only the reward token is accepted; the tokens have already arrived.
+pub fn execute_receive(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.reward_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let receive_msg: ReceiveMsg = from_json(&msg.msg)?;
+    let action = match receive_msg {
+        ReceiveMsg::EmergencyUnstake {} => PausableAction::Unstake,
+        _ => PausableAction::Fund,
+    };
+    ensure_not_paused(deps.storage, action)?;
+
+    match receive_msg {
+        ReceiveMsg::FundPot {} => {
+            let response = credit_pot(deps.storage, msg.amount)?;
+            Ok(response
+                .add_attribute("action", "fund_pot")
+                .add_attribute("funder", msg.sender)
+                .add_attribute("amount", msg.amount))
+        }
+        ReceiveMsg::FundTier { tier } => {
+            let response = fund_tier(deps.storage, &tier, msg.amount)?;
+            Ok(response
+                .add_attribute("action", "fund_tier")
+                .add_attribute("funder", msg.sender)
+                .add_attribute("amount", msg.amount))
+        }
+        ReceiveMsg::FundRewards {} => {
+            let response = fund_rewards(deps.storage, &env, msg.amount)?;
+            Ok(response
+                .add_attribute("action", "fund_rewards")
+                .add_attribute("funder", msg.sender)
+                .add_attribute("amount", msg.amount))
+        }
+        ReceiveMsg::FundMerkleDistribution {} => {
+            let response = fund_merkle_distribution(deps.storage, msg.amount)?;
+            Ok(response
+                .add_attribute("action", "fund_merkle_distribution")
+                .add_attribute("funder", msg.sender)
+                .add_attribute("amount", msg.amount))
+        }
+        ReceiveMsg::EmergencyUnstake {} => {
+            let owner = deps.api.addr_validate(&msg.sender)?;
+            let response = emergency_unstake(deps.branch(), &env, &owner, msg.amount)?;
+            let credited = credit_pot(deps.storage, msg.amount)?;
+            Ok(response
+                .add_attributes(credited.attributes)
+                .add_attribute("action", "emergency_unstake")
+                .add_attribute("sender", owner)
+                .add_attribute("penalty", msg.amount))
+        }
+    }
+}
+
+// Pull reward tokens the sender has approved for this contract into the pot. The pot
+// is only credited once the TransferFrom succeeds.
+pub fn execute_fund_pot_by_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+
+    let transfer_msg = Cw20ExecuteMsg::TransferFrom {
+        owner: info.sender.to_string(),
+        recipient: env.contract.address.to_string(),
+        amount,
+    };
+    let msg = WasmMsg::Execute {
+        contract_addr: config.reward_token.to_string(),
+        msg: to_json_binary(&transfer_msg)?,
+        funds: vec![],
+    };
+
+    FUND_IN_FLIGHT.save(deps.storage, &(info.sender.clone(), amount))?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(msg, FUND_REPLY_ID))
+        .add_attribute("action", "fund_pot_by_allowance")
+        .add_attribute("funder", info.sender)
+        .add_attribute("amount", amount))
+}
+
+pub(crate) fn reply_fund(deps: DepsMut, _env: Env, _msg: Reply) -> Result<Response, ContractError> {
+    let (funder, amount) = FUND_IN_FLIGHT.load(deps.storage)?;
+    FUND_IN_FLIGHT.remove(deps.storage);
+
+    let response = credit_pot(deps.storage, amount)?;
+    Ok(response
+        .add_attribute("action", "fund_pot_confirmed")
+        .add_attribute("funder", funder)
+        .add_attribute("amount", amount))
+}
+
+// Pay `recipient` reward tokens the caller has already taken out of the pot. A failed
+// transfer puts them back.
+pub(super) fn pot_transfer(
+    storage: &mut dyn Storage,
+    config: &Config,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<SubMsg> {
+    let msg = WasmMsg::Execute {
+        contract_addr: config.reward_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+    let payout = PotPayout {
+        recipient: recipient.clone(),
+        amount,
+        native: vec![],
+    };
+    queue_pot_payout(storage, msg.into(), payout)
+}
+
+// Same for native coins taken out of the native pot
+pub(super) fn pot_send(
+    storage: &mut dyn Storage,
+    recipient: &Addr,
+    native: Vec<Coin>,
+) -> StdResult<SubMsg> {
+    let msg = BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: native.clone(),
+    };
+    let payout = PotPayout {
+        recipient: recipient.clone(),
+        amount: Uint128::zero(),
+        native,
+    };
+    queue_pot_payout(storage, msg.into(), payout)
+}
+
+// Every payout replies, as the replies are matched to the payouts by order
+fn queue_pot_payout(
+    storage: &mut dyn Storage,
+    msg: CosmosMsg,
+    payout: PotPayout,
+) -> StdResult<SubMsg> {
+    let mut in_flight = POT_PAYOUTS_IN_FLIGHT.may_load(storage)?.unwrap_or_default();
+    in_flight.push(payout);
+    POT_PAYOUTS_IN_FLIGHT.save(storage, &in_flight)?;
+    Ok(SubMsg::reply_always(msg, POT_PAYOUT_REPLY_ID))
+}
+
+pub(crate) fn reply_pot_payout(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let mut in_flight = POT_PAYOUTS_IN_FLIGHT.load(deps.storage)?;
+    if in_flight.is_empty() {
+        return Err(ContractError::UnknownReplyId { id: msg.id });
+    }
+    let payout = in_flight.remove(0);
+    if in_flight.is_empty() {
+        POT_PAYOUTS_IN_FLIGHT.remove(deps.storage);
+    } else {
+        POT_PAYOUTS_IN_FLIGHT.save(deps.storage, &in_flight)?;
+    }
+
+    let SubMsgResult::Err(err) = msg.result else {
+        return Ok(Response::new());
+    };
+    let mut state = STATE.load(deps.storage)?;
+    state.current_pot = add_to_pot(state.current_pot, payout.amount)?;
+    add_coins(&mut state.native_pot, &payout.native)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "pot_payout_failed")
+        .add_attribute("recipient", payout.recipient)
+        .add_attribute("amount", payout.amount)
+        .add_attribute("native", coins_to_string(&payout.native))
+        .add_attribute("error", err)
+        .add_attribute("current_pot", state.current_pot))
+}
+
+// Add tokens that have arrived to the pot. The returned response reports the new pot
+// and, when a target is set, the progress towards it.
+fn credit_pot(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Response> {
+    let config = CONFIG.load(storage)?;
+    let mut state = STATE.load(storage)?;
+    state.current_pot = add_to_pot(state.current_pot, amount)?;
+    STATE.save(storage, &state)?;
+
+    let mut response = Response::new().add_attribute("current_pot", state.current_pot);
+    if let Some(progress) = pot_progress_pct(state.current_pot, config.target_pot_size) {
+        response = response.add_attribute("pot_progress_pct", progress);
+    }
+    Ok(response)
+}
+
+// Pot as a whole percentage of the target, None when no target is set. Goes past 100
+// once the target is exceeded.
+pub(crate) fn pot_progress_pct(pot: Uint128, target: Uint128) -> Option<Uint128> {
+    if target.is_zero() {
+        return None;
+    }
+    Some(pot.multiply_ratio(100u128, target))
+}
+
+// Add a funding amount to the pot, erroring instead of wrapping on overflow
+pub fn add_to_pot(pot: Uint128, amount: Uint128) -> StdResult<Uint128> {
+    Ok(pot.checked_add(amount)?)
+}
+
+// Merge `funds` into `pot`, keeping one entry per denom sorted by denom
+pub(crate) fn add_coins(pot: &mut Vec<Coin>, funds: &[Coin]) -> StdResult<()> {
+    for coin in funds.iter().filter(|coin| !coin.amount.is_zero()) {
+        match pot.binary_search_by(|held| held.denom.cmp(&coin.denom)) {
+            Ok(index) => pot[index].amount = pot[index].amount.checked_add(coin.amount)?,
+            Err(index) => pot.insert(index, coin.clone()),
+        }
+    }
+    Ok(())
+}
+
+// Attribute value for a list of coins. The chain rejects empty attribute values, so no
+// coins reads as "none".
+pub(crate) fn coins_to_string(coins: &[Coin]) -> String {
+    if coins.is_empty() {
+        return "none".to_string();
+    }
+    coins.iter().map(Coin::to_string).collect::<Vec<_>>().join(",")
+}
+
+// Split an amount into (fee, net) for a fee given in basis points
+pub fn split_fee(amount: Uint128, fee_bps: u16) -> StdResult<(Uint128, Uint128)> {
+    if fee_bps > 10_000 {
+        return Err(StdError::generic_err("Fee cannot exceed 10000 bps"));
+    }
+    let fee = amount.multiply_ratio(fee_bps as u128, 10_000u128);
+    let net = amount.checked_sub(fee)?;
+    Ok((fee, net))
+}
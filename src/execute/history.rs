@@ -0,0 +1,30 @@
+use cosmwasm_std::{Env, StdResult, Storage};
+
+use crate::state::{StakeEvent, StakeEventType, State, STAKER_HISTORY};
+
+// Append an event to the address's history. The caller saves `state` afterwards.
+pub(crate) fn record_event(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    env: &Env,
+    address: &str,
+    event_type: StakeEventType,
+    nft_count_after: u64,
+    token_id: Option<&str>,
+) -> StdResult<()> {
+    let round_id = match event_type {
+        StakeEventType::WonDraw | StakeEventType::Sacrificed => state.draw_count,
+        _ => state.draw_count + 1,
+    };
+    let event = StakeEvent {
+        event_type,
+        token_id: token_id.map(str::to_string),
+        block_height: env.block.height,
+        timestamp: env.block.time,
+        nft_count_after,
+        round_id,
+    };
+    STAKER_HISTORY.save(storage, (address.to_string(), state.staker_event_count), &event)?;
+    state.staker_event_count += 1;
+    Ok(())
+}
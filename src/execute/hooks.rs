@@ -0,0 +1,80 @@
+use cosmwasm_std::{
+    to_json_binary, DepsMut, Env, MessageInfo, Response, StdResult, Storage, SubMsg, WasmMsg,
+};
+
+use super::admin::record_admin_action;
+use crate::error::ContractError;
+use crate::msg::{StakeChangedExecuteMsg, StakeChangedHookMsg};
+use crate::state::{CONFIG, HOOKS};
+
+pub fn execute_add_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    
+    let addr = deps.api.addr_validate(&addr)?;
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if hooks.contains(&addr) {
+        return Err(ContractError::HookAlreadyRegistered {
+            addr: addr.to_string(),
+        });
+    }
+    hooks.push(addr.clone());
+    HOOKS.save(deps.storage, &hooks)?;
+    record_admin_action(deps.storage, &env)?;
+    
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", addr))
+}
+
+pub fn execute_remove_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    let Some(index) = hooks.iter().position(|hook| hook.as_str() == addr) else {
+        return Err(ContractError::HookNotRegistered { addr });
+    };
+    hooks.remove(index);
+    HOOKS.save(deps.storage, &hooks)?;
+    record_admin_action(deps.storage, &env)?;
+    
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", addr))
+}
+
+// One submessage per registered hook. A failing hook reverts the stake change, so only
+// the admin can register them.
+pub(crate) fn stake_changed_hooks(
+    storage: &dyn Storage,
+    msg: StakeChangedHookMsg,
+) -> StdResult<Vec<SubMsg>> {
+    let msg = to_json_binary(&StakeChangedExecuteMsg::StakeChangeHook(msg))?;
+    Ok(HOOKS
+        .may_load(storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hook| {
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: hook.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,68 @@
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage};
+
+use crate::error::ContractError;
+use crate::state::{stakers, State, CONFIG, INTERVAL_VOTES};
+
+pub fn execute_vote_draw_interval(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    preferred_seconds: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if preferred_seconds < config.min_draw_interval_seconds
+        || preferred_seconds > config.max_draw_interval_seconds
+    {
+        return Err(ContractError::DrawIntervalOutOfRange {
+            min: config.min_draw_interval_seconds,
+            max: config.max_draw_interval_seconds,
+        });
+    }
+    if !stakers().has(deps.storage, &info.sender) {
+        return Err(ContractError::NothingStaked {});
+    }
+
+    INTERVAL_VOTES.save(deps.storage, info.sender.to_string(), &preferred_seconds)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "vote_draw_interval")
+        .add_attribute("sender", info.sender)
+        .add_attribute("preferred_seconds", preferred_seconds.to_string()))
+}
+
+// Votes grouped by interval as (preferred_seconds, weight), shortest first. Each vote
+// weighs as many NFTs as the voter currently has staked, so exited voters drop out.
+pub(crate) fn interval_vote_histogram(storage: &dyn Storage) -> StdResult<Vec<(u64, u64)>> {
+    let mut histogram: Vec<(u64, u64)> = Vec::new();
+    for vote in INTERVAL_VOTES.range(storage, None, None, Order::Ascending) {
+        let (address, seconds) = vote?;
+        let weight = stakers()
+            .may_load(storage, &Addr::unchecked(address))?
+            .map_or(0, |staker| staker.nft_count);
+        if weight == 0 {
+            continue;
+        }
+        match histogram.binary_search_by_key(&seconds, |(bucket, _)| *bucket) {
+            Ok(index) => histogram[index].1 += weight,
+            Err(index) => histogram.insert(index, (seconds, weight)),
+        }
+    }
+    Ok(histogram)
+}
+
+// Set state.next_draw_interval_seconds to the weighted median vote. With no votes the
+// current interval is kept. The caller saves `state`.
+pub(crate) fn update_next_draw_interval(storage: &dyn Storage, state: &mut State) -> StdResult<()> {
+    let histogram = interval_vote_histogram(storage)?;
+    let total_weight: u64 = histogram.iter().map(|(_, weight)| weight).sum();
+
+    let mut cumulative = 0u64;
+    for (seconds, weight) in histogram {
+        cumulative += weight;
+        if cumulative * 2 >= total_weight {
+            state.next_draw_interval_seconds = seconds;
+            break;
+        }
+    }
+    Ok(())
+}
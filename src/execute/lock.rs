@@ -0,0 +1,178 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128};
+
+use super::activity::record_activity;
+use super::admin::record_admin_action;
+use super::hooks::stake_changed_hooks;
+use super::stake::MAX_BATCH_SIZE;
+use super::voting::checkpoint_power;
+use crate::error::ContractError;
+use crate::msg::StakeChangedHookMsg;
+use crate::state::{stakers, Config, LockTier, StakedNft, TokenLock, CONFIG, STAKED_NFTS, STATE};
+
+// Replace the lock tiers. Locks already taken keep the duration and weight they were
+// staked with.
+pub fn execute_set_lock_tiers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tiers: Vec<LockTier>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    validate_lock_tiers(&tiers)?;
+
+    config.lock_tiers = tiers;
+    CONFIG.save(deps.storage, &config)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_lock_tiers")
+        .add_attribute("tiers", config.lock_tiers.len().to_string()))
+}
+
+// Relock custody tokens of one collection, from nft_contract unless `collection` says
+// otherwise, for `new_duration` from now. The tier can go up or stay the same but not
+// down, and the lock can only end later. staked_at is kept, so the minimum period and
+// duration bonus still run from the original stake.
+pub fn execute_extend_lock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: Option<String>,
+    token_ids: Vec<String>,
+    new_duration: u64,
+) -> Result<Response, ContractError> {
+    if token_ids.is_empty() {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+    if token_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE as u32,
+        });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let collection = match collection {
+        Some(address) => deps.api.addr_validate(&address)?,
+        None => config.nft_contract.clone(),
+    };
+
+    let mut added_weight = 0u64;
+    for token_id in &token_ids {
+        let mut staked = STAKED_NFTS
+            .may_load(deps.storage, (&info.sender, &collection, token_id))?
+            .ok_or_else(|| ContractError::TokenNotStaked {
+                token_id: token_id.clone(),
+            })?;
+        let (weight, lock) = tier_lock(&config, &env, staked.base_weight(), new_duration)?;
+        // Admins can reprice tiers, so a relock is also held to never cost tickets
+        if let Some(current) = &staked.lock {
+            if new_duration < current.duration_seconds
+                || lock.unlock_at <= current.unlock_at
+                || weight < staked.weight
+            {
+                return Err(ContractError::LockNotExtended {
+                    token_id: token_id.clone(),
+                });
+            }
+        }
+        added_weight += weight - staked.weight;
+        staked.weight = weight;
+        staked.lock = Some(lock);
+        STAKED_NFTS.save(deps.storage, (&info.sender, &collection, token_id), &staked)?;
+    }
+
+    let mut response = Response::new();
+    if added_weight > 0 {
+        let mut staker = stakers().load(deps.storage, &info.sender)?;
+        let mut state = STATE.load(deps.storage)?;
+        let previous = staker.weight();
+        staker.bonus_weight += added_weight;
+        state.total_bonus_weight += added_weight;
+        stakers().save(deps.storage, &info.sender, &staker)?;
+        STATE.save(deps.storage, &state)?;
+        checkpoint_power(
+            deps.storage,
+            &env,
+            &info.sender,
+            previous,
+            staker.weight(),
+            state.total_weight(),
+        )?;
+        let hook_msg = StakeChangedHookMsg::Stake {
+            addr: info.sender.clone(),
+            amount: Uint128::from(added_weight),
+        };
+        response = response.add_submessages(stake_changed_hooks(deps.storage, hook_msg)?);
+    }
+    record_activity(deps.storage, &info.sender, &env)?;
+
+    Ok(response
+        .add_attribute("action", "extend_lock")
+        .add_attribute("sender", info.sender)
+        .add_attribute("collection", collection)
+        .add_attribute("token_ids", token_ids.join(","))
+        .add_attribute("new_duration", new_duration.to_string())
+        .add_attribute("added_weight", added_weight.to_string()))
+}
+
+pub(crate) fn validate_lock_tiers(tiers: &[LockTier]) -> Result<(), ContractError> {
+    let mut shortest = 0;
+    for tier in tiers {
+        if tier.duration_seconds <= shortest || tier.multiplier_bps < 10_000 {
+            return Err(ContractError::InvalidLockTiers {});
+        }
+        shortest = tier.duration_seconds;
+    }
+    Ok(())
+}
+
+// Weight of a token worth `base_weight` tickets staked now with the lock of
+// `lock_duration`, and the lock to record with it
+pub(super) fn lock_token(
+    config: &Config,
+    env: &Env,
+    base_weight: u64,
+    lock_duration: Option<u64>,
+) -> Result<(u64, Option<TokenLock>), ContractError> {
+    match lock_duration {
+        Some(duration) => {
+            let (weight, lock) = tier_lock(config, env, base_weight, duration)?;
+            Ok((weight, Some(lock)))
+        }
+        None => Ok((base_weight, None)),
+    }
+}
+
+fn tier_lock(
+    config: &Config,
+    env: &Env,
+    base_weight: u64,
+    duration: u64,
+) -> Result<(u64, TokenLock), ContractError> {
+    let tier = config
+        .lock_tier(duration)
+        .ok_or(ContractError::UnknownLockTier { duration })?;
+    let weight = (base_weight as u128 * tier.multiplier_bps as u128 / 10_000) as u64;
+    let lock = TokenLock {
+        duration_seconds: duration,
+        unlock_at: env.block.time.plus_seconds(duration),
+        base_weight,
+    };
+    Ok((weight, lock))
+}
+
+pub(super) fn ensure_unlocked(
+    env: &Env,
+    token_id: &str,
+    staked: &StakedNft,
+) -> Result<(), ContractError> {
+    match &staked.lock {
+        Some(lock) if env.block.time < lock.unlock_at => Err(ContractError::TokenLocked {
+            token_id: token_id.to_string(),
+            remaining_secs: lock.unlock_at.seconds() - env.block.time.seconds(),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,123 @@
+use cosmwasm_std::{
+    Addr, DepsMut, Env, HexBinary, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+};
+use sha2::{Digest, Sha256};
+
+use super::admin::record_admin_action;
+use super::claim::send_claim;
+use crate::error::ContractError;
+use crate::state::{ClaimInFlight, MerkleDistribution, CONFIG, MERKLE_CLAIMED, MERKLE_DISTRIBUTION};
+
+// Admin only. Replaces the current distribution; whatever it had funded and not paid
+// out carries over to the new root, and everyone can claim again under it.
+pub fn execute_post_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    merkle_root: HexBinary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if merkle_root.len() != 32 {
+        return Err(ContractError::InvalidMerkleRoot {});
+    }
+
+    let previous = MERKLE_DISTRIBUTION.may_load(deps.storage)?;
+    let distribution = MerkleDistribution {
+        id: previous.as_ref().map_or(1, |previous| previous.id + 1),
+        merkle_root,
+        funded: previous.map_or(Uint128::zero(), |previous| previous.funded - previous.claimed),
+        claimed: Uint128::zero(),
+    };
+    MERKLE_DISTRIBUTION.save(deps.storage, &distribution)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "post_merkle_root")
+        .add_attribute("distribution_id", distribution.id.to_string())
+        .add_attribute("merkle_root", distribution.merkle_root.to_hex()))
+}
+
+pub(super) fn fund_merkle_distribution(
+    storage: &mut dyn Storage,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut distribution = MERKLE_DISTRIBUTION
+        .may_load(storage)?
+        .ok_or(ContractError::NoMerkleDistribution {})?;
+    distribution.funded = distribution.funded.checked_add(amount).map_err(StdError::from)?;
+    MERKLE_DISTRIBUTION.save(storage, &distribution)?;
+
+    Ok(Response::new()
+        .add_attribute("distribution_id", distribution.id.to_string())
+        .add_attribute("merkle_funded", distribution.funded))
+}
+
+// The leaf is sha256 of the claimer's address followed by the amount in decimal. Each
+// step hashes the pair in ascending byte order, so proofs don't have to say which side
+// a sibling is on.
+pub fn execute_claim_merkle(
+    deps: DepsMut,
+    info: MessageInfo,
+    proof: Vec<HexBinary>,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut distribution = MERKLE_DISTRIBUTION
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoMerkleDistribution {})?;
+    let key = (distribution.id, &info.sender);
+    if MERKLE_CLAIMED.has(deps.storage, key) {
+        return Err(ContractError::MerkleAlreadyClaimed {});
+    }
+
+    let leaf = Sha256::digest(format!("{}{}", info.sender, amount).as_bytes());
+    let root = proof.iter().try_fold(leaf.to_vec(), |hash, sibling| {
+        if sibling.len() != 32 {
+            return Err(ContractError::InvalidMerkleProof {});
+        }
+        let (first, second) = if hash.as_slice() <= sibling.as_slice() {
+            (hash.as_slice(), sibling.as_slice())
+        } else {
+            (sibling.as_slice(), hash.as_slice())
+        };
+        Ok(Sha256::digest([first, second].concat()).to_vec())
+    })?;
+    if root != distribution.merkle_root.as_slice() {
+        return Err(ContractError::InvalidMerkleProof {});
+    }
+    let claimed = distribution.claimed + amount;
+    if claimed > distribution.funded {
+        return Err(ContractError::MerkleUnderfunded {});
+    }
+    distribution.claimed = claimed;
+    MERKLE_DISTRIBUTION.save(deps.storage, &distribution)?;
+    MERKLE_CLAIMED.save(deps.storage, key, &true)?;
+    let claim = ClaimInFlight {
+        merkle: Some((distribution.id, amount)),
+        ..ClaimInFlight::new(info.sender.clone())
+    };
+
+    Ok(Response::new()
+        .add_submessage(send_claim(deps.storage, &config, claim)?)
+        .add_attribute("action", "claim_merkle")
+        .add_attribute("distribution_id", distribution.id.to_string())
+        .add_attribute("claimer", info.sender)
+        .add_attribute("amount", amount))
+}
+
+// Reopen a claim whose transfer failed
+pub(super) fn restore_merkle_claim(
+    storage: &mut dyn Storage,
+    claimer: &Addr,
+    id: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut distribution = MERKLE_DISTRIBUTION.load(storage)?;
+    distribution.claimed -= amount;
+    MERKLE_DISTRIBUTION.save(storage, &distribution)?;
+    MERKLE_CLAIMED.remove(storage, (id, claimer));
+    Ok(())
+}
@@ -0,0 +1,48 @@
+mod activity;
+mod admin;
+mod burn;
+mod claim;
+mod collections;
+mod commit_reveal;
+mod compound;
+mod croncat;
+mod drand;
+mod draw;
+mod exclusions;
+mod exit;
+mod fund;
+mod history;
+mod hooks;
+mod interval;
+mod lock;
+mod merkle;
+mod pause;
+mod receipt;
+mod rewards;
+mod stake;
+mod tiers;
+mod unstake;
+mod voting;
+
+pub use activity::*;
+pub use admin::*;
+pub use burn::*;
+pub use claim::*;
+pub use collections::*;
+pub use commit_reveal::*;
+pub use compound::*;
+pub use croncat::*;
+pub use drand::*;
+pub use draw::*;
+pub use exclusions::*;
+pub use exit::*;
+pub use fund::*;
+pub use hooks::{execute_add_hook, execute_remove_hook};
+pub use interval::*;
+pub use lock::*;
+pub use merkle::*;
+pub use pause::*;
+pub use rewards::*;
+pub use stake::*;
+pub use tiers::*;
+pub use unstake::*;
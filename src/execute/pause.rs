@@ -0,0 +1,113 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Storage};
+
+use super::admin::record_admin_action;
+use crate::error::ContractError;
+use crate::msg::ExecuteMsg;
+use crate::state::{PausableAction, CONFIG, PAUSED};
+
+const ALL_ACTIONS: [PausableAction; 5] = [
+    PausableAction::Stake,
+    PausableAction::Unstake,
+    PausableAction::Draw,
+    PausableAction::Claim,
+    PausableAction::Fund,
+];
+
+pub fn execute_pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    actions: Vec<PausableAction>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    
+    let mut paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+    for action in expand(actions) {
+        if !paused.contains(&action) {
+            paused.push(action);
+        }
+    }
+    PAUSED.save(deps.storage, &paused)?;
+    record_admin_action(deps.storage, &env)?;
+    
+    Ok(Response::new()
+        .add_attribute("action", "pause")
+        .add_attribute("paused", format!("{:?}", paused)))
+}
+
+pub fn execute_unpause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    actions: Vec<PausableAction>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    
+    let actions = expand(actions);
+    let mut paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+    paused.retain(|action| !actions.contains(action));
+    PAUSED.save(deps.storage, &paused)?;
+    record_admin_action(deps.storage, &env)?;
+    
+    Ok(Response::new()
+        .add_attribute("action", "unpause")
+        .add_attribute("paused", format!("{:?}", paused)))
+}
+
+pub(crate) fn ensure_not_paused(
+    storage: &dyn Storage,
+    action: PausableAction,
+) -> Result<(), ContractError> {
+    if PAUSED.may_load(storage)?.unwrap_or_default().contains(&action) {
+        return Err(ContractError::Paused { action });
+    }
+    Ok(())
+}
+
+// Action a message belongs to. cw20 Receive is checked by its embedded message instead.
+pub(crate) fn pausable_action(msg: &ExecuteMsg) -> Option<PausableAction> {
+    match msg {
+        ExecuteMsg::Stake { .. }
+        | ExecuteMsg::ReceiveNft(_)
+        | ExecuteMsg::StakeBatch { .. }
+        | ExecuteMsg::ExtendLock { .. }
+        | ExecuteMsg::BurnForTickets { .. }
+        | ExecuteMsg::SetAutoCompound { .. }
+        | ExecuteMsg::CheckIn {} => Some(PausableAction::Stake),
+        ExecuteMsg::Unstake {}
+        | ExecuteMsg::UnstakeToken { .. }
+        | ExecuteMsg::UnstakeBatch { .. }
+        | ExecuteMsg::AbandonmentExit {} => Some(PausableAction::Unstake),
+        ExecuteMsg::DrawWinner {}
+        | ExecuteMsg::NoisReceive { .. }
+        | ExecuteMsg::SubmitDrandBeacon { .. }
+        | ExecuteMsg::CommitEntropy { .. }
+        | ExecuteMsg::RevealEntropy { .. }
+        | ExecuteMsg::FinalizeDraw {} => Some(PausableAction::Draw),
+        ExecuteMsg::ClaimReward {}
+        | ExecuteMsg::ClaimStakingRewards {}
+        | ExecuteMsg::ClaimAll {}
+        | ExecuteMsg::ClaimMerkle { .. }
+        | ExecuteMsg::ClaimTierPrize { .. }
+        | ExecuteMsg::ClaimUnstaked {}
+        | ExecuteMsg::SweepExpiredPrizes { .. } => Some(PausableAction::Claim),
+        ExecuteMsg::FundPot {} | ExecuteMsg::FundPotByAllowance { .. } => {
+            Some(PausableAction::Fund)
+        }
+        _ => None,
+    }
+}
+
+fn expand(actions: Vec<PausableAction>) -> Vec<PausableAction> {
+    if actions.is_empty() {
+        ALL_ACTIONS.to_vec()
+    } else {
+        actions
+    }
+}
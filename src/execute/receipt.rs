@@ -0,0 +1,78 @@
+use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, StdResult, Timestamp, WasmMsg};
+
+use crate::msg::{MintMsg, ReceiptExecuteMsg, StakeReceiptExt};
+use crate::state::{Config, StakedNft};
+
+// Wallet NFTs get receipts numbered per staker, 1 for the first. They only leave once no
+// custody tokens are left, highest number first, so the ids held run 1..=their count.
+pub(crate) fn receipt_token_id(owner: &Addr, index: u64) -> String {
+    format!("stake_{}_{}", owner, index)
+}
+
+// A custody token's receipt is named after the token, so it stays with the token
+// whichever order tokens leave the position in
+pub(crate) fn custody_receipt_id(owner: &Addr, collection: &Addr, token_id: &str) -> String {
+    format!("stake_{}_{}_{}", owner, collection, token_id)
+}
+
+// Receipt of `staked`, or for a token from before receipts were named after their token,
+// the highest position-numbered one of a position holding `nft_count` NFTs
+pub(crate) fn staked_receipt_id(owner: &Addr, nft_count: u64, staked: &StakedNft) -> String {
+    staked
+        .receipt_id
+        .clone()
+        .unwrap_or_else(|| receipt_token_id(owner, nft_count))
+}
+
+// Mint `receipt_id` for an NFT staked at `staked_at`
+pub(crate) fn receipt_mint_msg(
+    config: &Config,
+    owner: &Addr,
+    receipt_id: String,
+    staked_at: Timestamp,
+    original_token: Option<(&Addr, &str)>,
+) -> StdResult<Option<CosmosMsg>> {
+    let Some(receipt_contract) = &config.stake_receipt_contract else {
+        return Ok(None);
+    };
+    let mint_msg = ReceiptExecuteMsg::Mint(MintMsg {
+        token_id: receipt_id,
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: StakeReceiptExt {
+            original_token_id: original_token.map(|(_, token_id)| token_id.to_string()),
+            staked_at,
+            nft_contract: original_token
+                .map_or(&config.nft_contract, |(collection, _)| collection)
+                .to_string(),
+        },
+    });
+    Ok(Some(
+        WasmMsg::Execute {
+            contract_addr: receipt_contract.to_string(),
+            msg: to_json_binary(&mint_msg)?,
+            funds: vec![],
+        }
+        .into(),
+    ))
+}
+
+pub(crate) fn receipt_burn_msg(
+    config: &Config,
+    receipt_id: String,
+) -> StdResult<Option<CosmosMsg>> {
+    let Some(receipt_contract) = &config.stake_receipt_contract else {
+        return Ok(None);
+    };
+    let burn_msg = ReceiptExecuteMsg::Burn {
+        token_id: receipt_id,
+    };
+    Ok(Some(
+        WasmMsg::Execute {
+            contract_addr: receipt_contract.to_string(),
+            msg: to_json_binary(&burn_msg)?,
+            funds: vec![],
+        }
+        .into(),
+    ))
+}
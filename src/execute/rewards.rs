@@ -0,0 +1,176 @@
+use cosmwasm_std::{
+    Addr, Decimal, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+};
+
+use super::admin::record_admin_action;
+use super::claim::send_claim;
+use crate::error::ContractError;
+use crate::state::{
+    stakers, ClaimInFlight, RewardStream, StakerRewards, CONFIG, REWARD_STREAM, STAKER_REWARDS,
+    TOTAL_POWER, VOTING_POWER,
+};
+
+// Move the index up to now. What was emitted is shared by the total power in effect
+// since the last update, and never more than the reserve holds.
+fn advance(
+    stream: &mut RewardStream,
+    env: &Env,
+    emission_per_second: Uint128,
+    total_power: u64,
+) -> StdResult<()> {
+    let elapsed = env.block.time.seconds().saturating_sub(stream.last_update.seconds());
+    if total_power > 0 {
+        let emitted = emission_per_second
+            .saturating_mul(Uint128::from(elapsed))
+            .min(stream.reserve);
+        let per_power = Decimal::checked_from_ratio(emitted, total_power)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        stream.index = stream.index.checked_add(per_power).map_err(StdError::from)?;
+        stream.reserve -= emitted;
+        stream.outstanding += emitted;
+    }
+    stream.last_update = env.block.time;
+    Ok(())
+}
+
+// What `rewards` has earned at `power` since it last settled
+fn earned(stream: &RewardStream, rewards: &StakerRewards, power: u64) -> Uint128 {
+    Uint128::from(power) * (stream.index - rewards.index)
+}
+
+pub(crate) fn accrue_stream(storage: &mut dyn Storage, env: &Env) -> StdResult<RewardStream> {
+    let config = CONFIG.load(storage)?;
+    let mut stream = REWARD_STREAM.may_load(storage)?.unwrap_or_default();
+    let total_power = TOTAL_POWER.may_load(storage)?.unwrap_or_default();
+    advance(&mut stream, env, config.emission_per_second, total_power)?;
+    REWARD_STREAM.save(storage, &stream)?;
+    Ok(stream)
+}
+
+// Voting power of `owner` as it stands. Positions unchanged since checkpointing began
+// have no checkpoint and hold their staker weight, which TOTAL_POWER was seeded with.
+fn current_power(storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+    match VOTING_POWER.may_load(storage, owner)? {
+        Some(power) => Ok(power),
+        None => Ok(stakers().may_load(storage, owner)?.map_or(0, |staker| staker.weight())),
+    }
+}
+
+// Credit `owner` with what `power`, the voting power they held since they last settled,
+// earned up to `stream`
+pub(crate) fn settle_rewards(
+    storage: &mut dyn Storage,
+    stream: &RewardStream,
+    owner: &Addr,
+    power: u64,
+) -> StdResult<StakerRewards> {
+    let mut rewards = STAKER_REWARDS.may_load(storage, owner)?.unwrap_or_default();
+    rewards.pending += earned(stream, &rewards, power);
+    rewards.index = stream.index;
+    STAKER_REWARDS.save(storage, owner, &rewards)?;
+    Ok(rewards)
+}
+
+// Claimable streaming rewards of `owner` as of this block, without touching storage
+pub(crate) fn pending_staking_rewards(
+    storage: &dyn Storage,
+    env: &Env,
+    owner: &Addr,
+) -> StdResult<(RewardStream, Uint128)> {
+    let config = CONFIG.load(storage)?;
+    let mut stream = REWARD_STREAM.may_load(storage)?.unwrap_or_default();
+    let total_power = TOTAL_POWER.may_load(storage)?.unwrap_or_default();
+    advance(&mut stream, env, config.emission_per_second, total_power)?;
+    let rewards = STAKER_REWARDS.may_load(storage, owner)?.unwrap_or_default();
+    let power = current_power(storage, owner)?;
+    let pending = rewards.pending + earned(&stream, &rewards, power);
+    Ok((stream, pending))
+}
+
+pub(super) fn fund_rewards(
+    storage: &mut dyn Storage,
+    env: &Env,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut stream = accrue_stream(storage, env)?;
+    stream.reserve = stream.reserve.checked_add(amount).map_err(StdError::from)?;
+    REWARD_STREAM.save(storage, &stream)?;
+
+    Ok(Response::new().add_attribute("reward_reserve", stream.reserve))
+}
+
+// Admin only. The stream is brought up to date at the old rate first.
+pub fn execute_set_emission_rate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    emission_per_second: Uint128,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    accrue_stream(deps.storage, &env)?;
+
+    config.emission_per_second = emission_per_second;
+    CONFIG.save(deps.storage, &config)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_emission_rate")
+        .add_attribute("emission_per_second", emission_per_second))
+}
+
+// Settle `owner` and clear what they are owed; the caller pays it out
+pub(super) fn take_staking_rewards(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &Addr,
+) -> StdResult<Uint128> {
+    let mut stream = accrue_stream(storage, env)?;
+    let power = current_power(storage, owner)?;
+    let mut rewards = settle_rewards(storage, &stream, owner, power)?;
+    let amount = std::mem::take(&mut rewards.pending);
+    if !amount.is_zero() {
+        STAKER_REWARDS.save(storage, owner, &rewards)?;
+        stream.outstanding -= amount;
+        REWARD_STREAM.save(storage, &stream)?;
+    }
+    Ok(amount)
+}
+
+// Hand back rewards taken for a claim whose transfer failed
+pub(super) fn restore_staking_rewards(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut rewards = STAKER_REWARDS.may_load(storage, owner)?.unwrap_or_default();
+    rewards.pending += amount;
+    STAKER_REWARDS.save(storage, owner, &rewards)?;
+    let mut stream = REWARD_STREAM.load(storage)?;
+    stream.outstanding += amount;
+    REWARD_STREAM.save(storage, &stream)
+}
+
+pub fn execute_claim_staking_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let amount = take_staking_rewards(deps.storage, &env, &info.sender)?;
+    if amount.is_zero() {
+        return Err(ContractError::NoStakingRewards {});
+    }
+    let claim = ClaimInFlight {
+        staking_rewards: amount,
+        ..ClaimInFlight::new(info.sender.clone())
+    };
+
+    Ok(Response::new()
+        .add_submessage(send_claim(deps.storage, &config, claim)?)
+        .add_attribute("action", "claim_staking_rewards")
+        .add_attribute("staker", info.sender)
+        .add_attribute("amount", amount))
+}
@@ -0,0 +1,414 @@
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Coin, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response,
+    StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+};
+use cw721::{
+    ApprovalResponse, Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg, OwnerOfResponse,
+};
+use cw_utils::must_pay;
+
+use crate::error::ContractError;
+use crate::msg::{ReceiveNftMsg, StakeChangedHookMsg};
+use super::activity::record_activity;
+use super::collections::{resolve_token_weight, token_allowed};
+use super::fund::{add_coins, coins_to_string};
+use super::history::record_event;
+use super::hooks::stake_changed_hooks;
+use super::lock::lock_token;
+use super::receipt::{custody_receipt_id, receipt_mint_msg, receipt_token_id, staked_receipt_id};
+use super::voting::checkpoint_power;
+use crate::state::{
+    stakers, Config, StakeEventType, StakedNft, Staker, COLLECTIONS, CONFIG, KNOWN_STAKERS,
+    PULLS_IN_FLIGHT, STAKED_NFTS, STATE,
+};
+
+// Most tokens StakeBatch and UnstakeBatch take in one call
+pub const MAX_BATCH_SIZE: usize = 30;
+
+pub const PULL_REPLY_ID: u64 = 5;
+
+// With token ids the tokens are pulled from nft_contract, where the sender has to own
+// them and have approved this contract, and each is only staked once its TransferNft has
+// succeeded. Without token ids this counts one NFT that stays in the sender's wallet,
+// which nothing verifies, so it needs allow_wallet_stakes. Only pulled tokens can be
+// locked.
+pub fn execute_stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+    lock_duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if token_ids.is_empty() {
+        if !config.allow_wallet_stakes {
+            return Err(ContractError::WalletStakesDisabled {});
+        }
+        if lock_duration.is_some() {
+            return Err(ContractError::LockRequiresCustody {});
+        }
+        let fee = collect_stake_fee(deps.storage, &config, &info, 1)?;
+        let response = add_stake(deps, &env, &info.sender, None, 1)?;
+        return Ok(response
+            .add_attribute("action", "stake")
+            .add_attribute("sender", info.sender)
+            .add_attribute("stake_fee", coins_to_string(&fee)));
+    }
+    if token_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE as u32,
+        });
+    }
+    if let Some(duration) = lock_duration {
+        config
+            .lock_tier(duration)
+            .ok_or(ContractError::UnknownLockTier { duration })?;
+    }
+    
+    // Checked again as each pull is confirmed; failing here saves the transfers
+    let staked = stakers()
+        .may_load(deps.storage, &info.sender)?
+        .map_or(0, |staker| staker.nft_count);
+    ensure_within_stake_cap(&config, staked, token_ids.len() as u64)?;
+    let fee = collect_stake_fee(deps.storage, &config, &info, token_ids.len() as u64)?;
+    
+    let collection = config.nft_contract;
+    let mut in_flight = Vec::with_capacity(token_ids.len());
+    let mut response = Response::new();
+    for token_id in &token_ids {
+        ensure_token_owner(deps.as_ref(), &collection, &info.sender, token_id)?;
+        if in_flight.iter().any(|(_, _, pulled, _)| pulled == token_id) {
+            return Err(ContractError::NotTokenOwner {
+                token_id: token_id.clone(),
+            });
+        }
+        let approval: StdResult<ApprovalResponse> = deps.querier.query_wasm_smart(
+            &collection,
+            &Cw721QueryMsg::Approval {
+                token_id: token_id.clone(),
+                spender: env.contract.address.to_string(),
+                include_expired: None,
+            },
+        );
+        if approval.is_err() {
+            return Err(ContractError::NotApproved {
+                token_id: token_id.clone(),
+            });
+        }
+        let msg = WasmMsg::Execute {
+            contract_addr: collection.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: env.contract.address.to_string(),
+                token_id: token_id.clone(),
+            })?,
+            funds: vec![],
+        };
+        response = response.add_submessage(SubMsg::reply_on_success(msg, PULL_REPLY_ID));
+        in_flight.push((info.sender.clone(), collection.clone(), token_id.clone(), lock_duration));
+    }
+    PULLS_IN_FLIGHT.save(deps.storage, &in_flight)?;
+    
+    Ok(response
+        .add_attribute("action", "stake_pull")
+        .add_attribute("sender", info.sender)
+        .add_attribute("token_ids", token_ids.join(","))
+        .add_attribute("stake_fee", coins_to_string(&fee)))
+}
+
+// The pulls run in the order they were queued, so each reply settles the oldest one
+pub(crate) fn reply_pull(deps: DepsMut, env: Env, _msg: Reply) -> Result<Response, ContractError> {
+    let mut in_flight = PULLS_IN_FLIGHT.load(deps.storage)?;
+    let (owner, collection, token_id, lock_duration) = in_flight.remove(0);
+    if in_flight.is_empty() {
+        PULLS_IN_FLIGHT.remove(deps.storage);
+    } else {
+        PULLS_IN_FLIGHT.save(deps.storage, &in_flight)?;
+    }
+    
+    let (response, weight) =
+        stake_custody_token(deps, &env, &owner, &collection, &token_id, lock_duration)?;
+    Ok(response
+        .add_attribute("action", "stake_pull_confirmed")
+        .add_attribute("sender", owner)
+        .add_attribute("collection", collection)
+        .add_attribute("token_id", token_id)
+        .add_attribute("weight", weight.to_string()))
+}
+
+// Stake an NFT sent to this contract with cw721 SendNft. Any registered collection can
+// call this, and the token stays here until it is unstaked. An empty message stakes it
+// without a lock.
+pub fn execute_receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    if CONFIG.load(deps.storage)?.stake_fee.is_some() {
+        return Err(ContractError::StakeFeeUnpaid {});
+    }
+    let owner = deps.api.addr_validate(&msg.sender)?;
+    let lock_duration = if msg.msg.is_empty() {
+        None
+    } else {
+        from_json::<ReceiveNftMsg>(&msg.msg)?.lock_duration
+    };
+    let (response, weight) =
+        stake_custody_token(deps, &env, &owner, &info.sender, &msg.token_id, lock_duration)?;
+    
+    Ok(response
+        .add_attribute("action", "receive_nft")
+        .add_attribute("sender", owner)
+        .add_attribute("collection", info.sender)
+        .add_attribute("token_id", msg.token_id)
+        .add_attribute("weight", weight.to_string()))
+}
+
+// Stake several tokens of one collection, from nft_contract unless `collection` says
+// otherwise. The sender has to own every token and have approved this contract, which
+// pulls them all with TransferNft in the same response.
+pub fn execute_stake_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: Option<String>,
+    token_ids: Vec<String>,
+    lock_duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    if token_ids.is_empty() {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+    if token_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE as u32,
+        });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let collection = match collection {
+        Some(address) => deps.api.addr_validate(&address)?,
+        None => config.nft_contract.clone(),
+    };
+    let fee = collect_stake_fee(deps.storage, &config, &info, token_ids.len() as u64)?;
+    
+    let mut response = Response::new();
+    let mut total_weight = 0u64;
+    for (index, token_id) in token_ids.iter().enumerate() {
+        // Repeats are refused before anything is recorded
+        ensure_token_owner(deps.as_ref(), &collection, &info.sender, token_id)?;
+        if token_ids[index + 1..].contains(token_id) {
+            return Err(ContractError::NotTokenOwner {
+                token_id: token_id.clone(),
+            });
+        }
+    }
+    for token_id in &token_ids {
+        if STAKED_NFTS.has(deps.storage, (&info.sender, &collection, token_id)) {
+            return Err(ContractError::NotTokenOwner {
+                token_id: token_id.clone(),
+            });
+        }
+        let (staked, weight) = stake_custody_token(
+            deps.branch(),
+            &env,
+            &info.sender,
+            &collection,
+            token_id,
+            lock_duration,
+        )?;
+        total_weight += weight;
+        response = response
+            .add_message(WasmMsg::Execute {
+                contract_addr: collection.to_string(),
+                msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: env.contract.address.to_string(),
+                    token_id: token_id.clone(),
+                })?,
+                funds: vec![],
+            })
+            .add_submessages(staked.messages);
+    }
+    
+    Ok(response
+        .add_attribute("action", "stake_batch")
+        .add_attribute("sender", info.sender)
+        .add_attribute("collection", collection)
+        .add_attribute("token_ids", token_ids.join(","))
+        .add_attribute("weight", total_weight.to_string())
+        .add_attribute("stake_fee", coins_to_string(&fee)))
+}
+
+// Staked tokens are owned by this contract, so this also turns away tokens in custody
+fn ensure_token_owner(
+    deps: Deps,
+    collection: &Addr,
+    sender: &Addr,
+    token_id: &str,
+) -> Result<(), ContractError> {
+    let owner: OwnerOfResponse = deps.querier.query_wasm_smart(
+        collection,
+        &Cw721QueryMsg::OwnerOf {
+            token_id: token_id.to_string(),
+            include_expired: None,
+        },
+    )?;
+    if owner.owner != sender.as_str() {
+        return Err(ContractError::NotTokenOwner {
+            token_id: token_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// Record `token_id` of `collection` as staked by `owner`, locked for `lock_duration` if
+// set, returning the response of the stake and the weight it was staked with. The caller
+// makes sure the token is, or is about to be, held by this contract.
+fn stake_custody_token(
+    deps: DepsMut,
+    env: &Env,
+    owner: &Addr,
+    address: &Addr,
+    token_id: &str,
+    lock_duration: Option<u64>,
+) -> Result<(Response, u64), ContractError> {
+    let mut collection = COLLECTIONS
+        .may_load(deps.storage, address)?
+        .ok_or_else(|| ContractError::UnknownCollection {
+            collection: address.to_string(),
+        })?;
+    if !token_allowed(deps.storage, address, &collection, token_id)? {
+        return Err(ContractError::TokenNotAllowed {
+            token_id: token_id.to_string(),
+        });
+    }
+    let base_weight = resolve_token_weight(deps.as_ref(), address, &collection, token_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let (weight, lock) = lock_token(&config, env, base_weight, lock_duration)?;
+    collection.staked += 1;
+    COLLECTIONS.save(deps.storage, address, &collection)?;
+    
+    // The weight is fixed here, so later trait weight changes don't touch this token
+    let staked = StakedNft {
+        staked_at: env.block.time,
+        weight,
+        lock,
+        receipt_id: Some(custody_receipt_id(owner, address, token_id)),
+    };
+    STAKED_NFTS.save(deps.storage, (owner, address, token_id), &staked)?;
+    let response = add_stake(deps, env, owner, Some((address, token_id, &staked)), weight)?;
+    Ok((response, weight))
+}
+
+// Move the stake fee for `count` NFTs from the attached funds into the native pot. The
+// funds have to be exactly the fee, in its denom only.
+fn collect_stake_fee(
+    storage: &mut dyn Storage,
+    config: &Config,
+    info: &MessageInfo,
+    count: u64,
+) -> Result<Vec<Coin>, ContractError> {
+    let Some(fee) = &config.stake_fee else {
+        return Ok(vec![]);
+    };
+    let expected = Coin {
+        denom: fee.denom.clone(),
+        amount: fee.amount.checked_mul(Uint128::from(count)).map_err(StdError::from)?,
+    };
+    if must_pay(info, &fee.denom)? != expected.amount {
+        return Err(ContractError::WrongStakeFee { expected });
+    }
+    let paid = vec![expected];
+    let mut state = STATE.load(storage)?;
+    add_coins(&mut state.native_pot, &paid)?;
+    STATE.save(storage, &state)?;
+    Ok(paid)
+}
+
+// Whether a position of `staked` NFTs can take `adding` more under max_nfts_per_staker
+fn ensure_within_stake_cap(config: &Config, staked: u64, adding: u64) -> Result<(), ContractError> {
+    let max = config.max_nfts_per_staker;
+    if max > 0 && staked + adding > max {
+        return Err(ContractError::StakeCapExceeded { max });
+    }
+    Ok(())
+}
+
+// Count one more NFT worth `weight` tickets for `owner`. Shared by both staking paths;
+// the caller adds the action attributes.
+fn add_stake(
+    deps: DepsMut,
+    env: &Env,
+    owner: &Addr,
+    token: Option<(&Addr, &str, &StakedNft)>,
+    weight: u64,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    
+    // Get or create staker info; a new position is a new staker
+    let existing = stakers().may_load(deps.storage, owner)?;
+    let previous = existing.as_ref().map_or(0, Staker::weight);
+    if existing.is_none() {
+        state.staker_count += 1;
+        if !KNOWN_STAKERS.has(deps.storage, owner) {
+            KNOWN_STAKERS.save(deps.storage, owner, &true)?;
+            state.unique_stakers += 1;
+        }
+    }
+    let mut staker = existing
+        .unwrap_or(Staker {
+            staked_at: env.block.time,
+            nft_count: 0,
+            bonus_weight: 0,
+            burned_weight: 0,
+            compounded_weight: 0,
+        });
+    
+    let config = CONFIG.load(deps.storage)?;
+    ensure_within_stake_cap(&config, staker.nft_count, 1)?;
+    
+    // Update staker info. staked_at stays at the start of the position; custody tokens
+    // mature on their own StakedNft::staked_at.
+    staker.nft_count += 1;
+    staker.bonus_weight += weight - 1;
+    stakers().save(deps.storage, owner, &staker)?;
+    
+    // Update state
+    state.total_staked += 1;
+    state.total_bonus_weight += weight - 1;
+    checkpoint_power(deps.storage, env, owner, previous, staker.weight(), state.total_weight())?;
+    record_event(
+        deps.storage,
+        &mut state,
+        env,
+        owner.as_str(),
+        StakeEventType::Staked,
+        staker.nft_count,
+        token.map(|(_, token_id, _)| token_id),
+    )?;
+    STATE.save(deps.storage, &state)?;
+    record_activity(deps.storage, owner, env)?;
+    
+    let hook_msg = StakeChangedHookMsg::Stake {
+        addr: owner.clone(),
+        amount: Uint128::from(weight),
+    };
+    let mut response = Response::new().add_submessages(stake_changed_hooks(deps.storage, hook_msg)?);
+    // A wallet NFT is numbered among the position's wallet NFTs and dated by the position
+    let (receipt_id, staked_at) = match token {
+        Some((_, _, staked)) => (
+            staked_receipt_id(owner, staker.nft_count, staked),
+            staked.staked_at,
+        ),
+        None => {
+            let custody = STAKED_NFTS
+                .sub_prefix(owner)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .count() as u64;
+            (receipt_token_id(owner, staker.nft_count - custody), staker.staked_at)
+        }
+    };
+    let original_token = token.map(|(collection, token_id, _)| (collection, token_id));
+    let mint_msg = receipt_mint_msg(&config, owner, receipt_id, staked_at, original_token)?;
+    response = response.add_messages(mint_msg);
+    
+    Ok(response)
+}
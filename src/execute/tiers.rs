@@ -0,0 +1,145 @@
+use cosmwasm_std::{
+    Addr, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+};
+use std::collections::HashSet;
+
+use super::admin::record_admin_action;
+use super::claim::send_claim;
+use crate::error::ContractError;
+use crate::lottery::{pick_winners, PoolTotals};
+use crate::state::{ClaimInFlight, Config, PrizeTier, CONFIG, TIER_POTS, TIER_PRIZES};
+
+// Replace the tier list. A tier can only be dropped once its pool is empty; pools of
+// tiers that stay keep their balance.
+pub fn execute_set_prize_tiers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tiers: Vec<PrizeTier>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut names = HashSet::new();
+    for tier in &tiers {
+        if tier.name.is_empty() || tier.winners == 0 || !names.insert(tier.name.as_str()) {
+            return Err(ContractError::InvalidPrizeTiers {});
+        }
+    }
+    for tier in &config.prize_tiers {
+        let pot = TIER_POTS.may_load(deps.storage, &tier.name)?.unwrap_or_default();
+        if !names.contains(tier.name.as_str()) && !pot.is_zero() {
+            return Err(ContractError::TierNotEmpty {
+                tier: tier.name.clone(),
+            });
+        }
+    }
+
+    config.prize_tiers = tiers;
+    CONFIG.save(deps.storage, &config)?;
+    record_admin_action(deps.storage, &env)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_prize_tiers")
+        .add_attribute("tiers", config.prize_tiers.len().to_string()))
+}
+
+// Credit a tier's pool; called from the cw20 Receive hook
+pub(super) fn fund_tier(
+    storage: &mut dyn Storage,
+    tier: &str,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(storage)?;
+    if !config.prize_tiers.iter().any(|configured| configured.name == tier) {
+        return Err(ContractError::UnknownTier {
+            tier: tier.to_string(),
+        });
+    }
+    let pot = TIER_POTS
+        .may_load(storage, tier)?
+        .unwrap_or_default()
+        .checked_add(amount)
+        .map_err(StdError::from)?;
+    TIER_POTS.save(storage, tier, &pot)?;
+
+    Ok(Response::new()
+        .add_attribute("tier", tier)
+        .add_attribute("tier_pot", pot))
+}
+
+// Each tier runs its own selection over the same pool, so a staker can win the main
+// pot and a tier in one draw. A tier's pool is shared evenly between its winners;
+// shares of places that couldn't be filled stay in the pool.
+pub(super) fn draw_tiers(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    totals: &PoolTotals,
+    entropy: &[u8],
+) -> StdResult<Vec<(String, Vec<String>)>> {
+    let mut results = Vec::new();
+    for tier in &config.prize_tiers {
+        let pot = TIER_POTS.may_load(deps.storage, &tier.name)?.unwrap_or_default();
+        if pot.is_zero() {
+            continue;
+        }
+        let seed = [entropy, b"tier:", tier.name.as_bytes()].concat();
+        let winners = pick_winners(
+            deps.as_ref(),
+            env,
+            config,
+            totals,
+            &seed,
+            tier.winners as usize,
+        )?;
+        let share = pot.multiply_ratio(1u128, tier.winners as u128);
+        let dust = pot - share * Uint128::from(tier.winners);
+        let mut paid = Uint128::zero();
+        for (place, winner) in winners.iter().enumerate() {
+            let prize = if place == 0 { share + dust } else { share };
+            let key = (tier.name.as_str(), &Addr::unchecked(winner));
+            TIER_PRIZES.update(deps.storage, key, |pending| -> StdResult<_> {
+                Ok(pending.unwrap_or_default().checked_add(prize)?)
+            })?;
+            paid += prize;
+        }
+        if pot == paid {
+            TIER_POTS.remove(deps.storage, &tier.name);
+        } else {
+            TIER_POTS.save(deps.storage, &tier.name, &(pot - paid))?;
+        }
+        results.push((tier.name.clone(), winners));
+    }
+    Ok(results)
+}
+
+// Pays out the sender's unclaimed prize from one tier. Goes through the same reply as
+// ClaimReward, so lifetime winnings include tier prizes.
+pub fn execute_claim_tier_prize(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    tier: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let key = (tier.as_str(), &info.sender);
+    let prize = TIER_PRIZES
+        .may_load(deps.storage, key)?
+        .ok_or(ContractError::NotTheWinner {})?;
+    TIER_PRIZES.remove(deps.storage, key);
+
+    let claim = ClaimInFlight {
+        tier_prizes: vec![(tier.clone(), prize)],
+        ..ClaimInFlight::new(info.sender.clone())
+    };
+
+    Ok(Response::new()
+        .add_submessage(send_claim(deps.storage, &config, claim)?)
+        .add_attribute("action", "claim_tier_prize")
+        .add_attribute("tier", tier)
+        .add_attribute("winner", info.sender)
+        .add_attribute("amount", prize))
+}
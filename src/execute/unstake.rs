@@ -0,0 +1,317 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, CosmosMsg, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    Storage, Timestamp, Uint128, WasmMsg,
+};
+use cw721::Cw721ExecuteMsg;
+
+use crate::error::ContractError;
+use crate::msg::StakeChangedHookMsg;
+use super::activity::record_activity;
+use super::stake::MAX_BATCH_SIZE;
+use super::history::record_event;
+use super::hooks::stake_changed_hooks;
+use super::lock::ensure_unlocked;
+use super::receipt::{receipt_burn_msg, receipt_token_id, staked_receipt_id};
+use super::voting::checkpoint_power;
+use crate::state::{
+    stakers, Config, StakeEventType, StakedNft, Staker, UnbondingNft, COLLECTIONS, CONFIG,
+    STAKED_NFTS, STATE, UNBONDING,
+};
+
+pub fn execute_unstake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Get staker info
+    let staker = stakers()
+        .may_load(deps.storage, &info.sender)?
+        .filter(|staker| staker.nft_count > 0)
+        .ok_or(ContractError::NothingStaked {})?;
+    
+    // Hand back the most recently received token, if this position holds any. It has
+    // to have matured itself; a wallet NFT goes by the position's staked_at.
+    let config = CONFIG.load(deps.storage)?;
+    let token = latest_staked_token(deps.storage, &info.sender)?;
+    match &token {
+        Some((_, token_id, staked)) => {
+            ensure_min_stake_period(&env, &config, staked.staked_at)?;
+            ensure_unlocked(&env, token_id, staked)?;
+        }
+        None => ensure_min_stake_period(&env, &config, staker.staked_at)?,
+    }
+    let response = remove_stake(deps, &env, &info.sender, staker, token, true)?;
+    
+    Ok(response
+        .add_attribute("action", "unstake")
+        .add_attribute("sender", info.sender))
+}
+
+// Withdraw one specific custody token, from nft_contract unless `collection` says
+// otherwise. The minimum period runs from when that token was received, not from the
+// start of the position, and a locked token also has to reach its unlock time.
+pub fn execute_unstake_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: Option<String>,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let collection = match collection {
+        Some(address) => deps.api.addr_validate(&address)?,
+        None => config.nft_contract.clone(),
+    };
+    let staked = STAKED_NFTS
+        .may_load(deps.storage, (&info.sender, &collection, &token_id))?
+        .ok_or_else(|| ContractError::TokenNotStaked {
+            token_id: token_id.clone(),
+        })?;
+    ensure_min_stake_period(&env, &config, staked.staked_at)?;
+    ensure_unlocked(&env, &token_id, &staked)?;
+    let staker = stakers().load(deps.storage, &info.sender)?;
+    
+    let token = Some((collection, token_id, staked));
+    let response = remove_stake(deps, &env, &info.sender, staker, token, true)?;
+    
+    Ok(response
+        .add_attribute("action", "unstake_token")
+        .add_attribute("sender", info.sender))
+}
+
+// Withdraw several custody tokens of one collection in one response, each under the
+// same rules as UnstakeToken
+pub fn execute_unstake_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: Option<String>,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    if token_ids.is_empty() {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+    if token_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE as u32,
+        });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let collection = match collection {
+        Some(address) => deps.api.addr_validate(&address)?,
+        None => config.nft_contract.clone(),
+    };
+    
+    let mut response = Response::new();
+    for token_id in &token_ids {
+        let staked = STAKED_NFTS
+            .may_load(deps.storage, (&info.sender, &collection, token_id))?
+            .ok_or_else(|| ContractError::TokenNotStaked {
+                token_id: token_id.clone(),
+            })?;
+        ensure_min_stake_period(&env, &config, staked.staked_at)?;
+        ensure_unlocked(&env, token_id, &staked)?;
+        let staker = stakers().load(deps.storage, &info.sender)?;
+        let token = Some((collection.clone(), token_id.clone(), staked));
+        let removed = remove_stake(deps.branch(), &env, &info.sender, staker, token, true)?;
+        response = response.add_submessages(removed.messages);
+    }
+    
+    Ok(response
+        .add_attribute("action", "unstake_batch")
+        .add_attribute("sender", info.sender)
+        .add_attribute("collection", collection)
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
+// Take one NFT off `owner`'s position, returning `token` if it is a custody token.
+// Without `unbond` the token is returned right away even if an unbonding period is set.
+// The caller checks eligibility and adds the action attributes.
+pub(super) fn remove_stake(
+    deps: DepsMut,
+    env: &Env,
+    owner: &Addr,
+    mut staker: Staker,
+    token: Option<(Addr, String, StakedNft)>,
+    unbond: bool,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    
+    // A custody token takes its own receipt along. Wallet NFTs only leave once no custody
+    // tokens are left, so the highest numbered receipt goes.
+    let config = CONFIG.load(deps.storage)?;
+    let receipt_id = match &token {
+        Some((_, _, staked)) => staked_receipt_id(owner, staker.nft_count, staked),
+        None => receipt_token_id(owner, staker.nft_count),
+    };
+    let burn_msg = receipt_burn_msg(&config, receipt_id)?;
+    
+    // With an unbonding period the token stays here until ClaimUnstaked
+    let return_msg = match &token {
+        Some((collection, token_id, _)) => {
+            STAKED_NFTS.remove(deps.storage, (owner, collection, token_id));
+            let mut registered = COLLECTIONS.load(deps.storage, collection)?;
+            registered.staked -= 1;
+            COLLECTIONS.save(deps.storage, collection, &registered)?;
+            if !unbond || config.unbonding_seconds == 0 {
+                Some(return_nft_msg(collection, owner, token_id)?)
+            } else {
+                let mut unbonding = UNBONDING.may_load(deps.storage, owner)?.unwrap_or_default();
+                unbonding.push(UnbondingNft {
+                    collection: collection.clone(),
+                    token_id: token_id.clone(),
+                    release_at: env.block.time.plus_seconds(config.unbonding_seconds),
+                });
+                UNBONDING.save(deps.storage, owner, &unbonding)?;
+                None
+            }
+        }
+        None => None,
+    };
+    
+    // Update staker info
+    let weight = token.as_ref().map_or(1, |(_, _, staked)| staked.weight);
+    let previous = staker.weight();
+    staker.nft_count -= 1;
+    staker.bonus_weight -= weight - 1;
+    // Burned and compounded tickets keep an emptied position in the draw
+    if staker.nft_count == 0 && staker.permanent_weight() == 0 {
+        stakers().remove(deps.storage, owner)?;
+        state.staker_count -= 1;
+    } else {
+        stakers().save(deps.storage, owner, &staker)?;
+    }
+    
+    // Update state
+    state.total_staked -= 1;
+    state.total_bonus_weight -= weight - 1;
+    checkpoint_power(deps.storage, env, owner, previous, staker.weight(), state.total_weight())?;
+    record_event(
+        deps.storage,
+        &mut state,
+        env,
+        owner.as_str(),
+        StakeEventType::Unstaked,
+        staker.nft_count,
+        token.as_ref().map(|(_, token_id, _)| token_id.as_str()),
+    )?;
+    STATE.save(deps.storage, &state)?;
+    record_activity(deps.storage, owner, env)?;
+    
+    let hook_msg = StakeChangedHookMsg::Unstake {
+        addr: owner.clone(),
+        amount: Uint128::from(weight),
+    };
+    let mut response = Response::new()
+        .add_messages(return_msg)
+        .add_messages(burn_msg)
+        .add_submessages(stake_changed_hooks(deps.storage, hook_msg)?);
+    if let Some((collection, token_id, _)) = token {
+        response = response
+            .add_attribute("collection", collection)
+            .add_attribute("token_id", token_id);
+    }
+    Ok(response)
+}
+
+// Unstake the latest NFT of `owner` without waiting out the minimum period. A lock still
+// holds; its multiplier was paid for with the lock itself. Called from the cw20 Receive
+// hook, which credits the penalty to the pot.
+pub(super) fn emergency_unstake(
+    deps: DepsMut,
+    env: &Env,
+    owner: &Addr,
+    paid: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.early_unstake_penalty.is_zero() {
+        return Err(ContractError::EarlyUnstakeDisabled {});
+    }
+    if paid < config.early_unstake_penalty {
+        return Err(ContractError::InsufficientPenalty {
+            required: config.early_unstake_penalty,
+        });
+    }
+    let staker = stakers()
+        .may_load(deps.storage, owner)?
+        .filter(|staker| staker.nft_count > 0)
+        .ok_or(ContractError::NothingStaked {})?;
+    
+    let token = latest_staked_token(deps.storage, owner)?;
+    if let Some((_, token_id, staked)) = &token {
+        ensure_unlocked(env, token_id, staked)?;
+    }
+    remove_stake(deps, env, owner, staker, token, true)
+}
+
+// Tokens that are still unbonding stay queued
+pub fn execute_claim_unstaked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let unbonding = UNBONDING.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    let (released, pending): (Vec<_>, Vec<_>) = unbonding
+        .into_iter()
+        .partition(|nft| nft.release_at <= env.block.time);
+    if released.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    if pending.is_empty() {
+        UNBONDING.remove(deps.storage, &info.sender);
+    } else {
+        UNBONDING.save(deps.storage, &info.sender, &pending)?;
+    }
+    
+    let mut response = Response::new()
+        .add_attribute("action", "claim_unstaked")
+        .add_attribute("sender", info.sender.clone());
+    for nft in &released {
+        response = response
+            .add_message(return_nft_msg(&nft.collection, &info.sender, &nft.token_id)?)
+            .add_attribute("token_id", nft.token_id.clone());
+    }
+    Ok(response)
+}
+
+fn ensure_min_stake_period(
+    env: &Env,
+    config: &Config,
+    staked_at: Timestamp,
+) -> Result<(), ContractError> {
+    let matures_at = staked_at.plus_seconds(config.min_staking_seconds());
+    if env.block.time < matures_at {
+        return Err(ContractError::MinStakePeriodNotMet {
+            remaining_secs: matures_at.seconds() - env.block.time.seconds(),
+        });
+    }
+    Ok(())
+}
+
+// Token of `owner` received last, ties broken by the highest (collection, token_id)
+fn latest_staked_token(
+    storage: &dyn Storage,
+    owner: &Addr,
+) -> StdResult<Option<(Addr, String, StakedNft)>> {
+    let tokens = STAKED_NFTS
+        .sub_prefix(owner)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(tokens
+        .into_iter()
+        .max_by_key(|(_, staked)| staked.staked_at)
+        .map(|((collection, token_id), staked)| (collection, token_id, staked)))
+}
+
+// Transfer a custody token back to its owner
+pub(crate) fn return_nft_msg(collection: &Addr, owner: &Addr, token_id: &str) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: owner.to_string(),
+            token_id: token_id.to_string(),
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
@@ -0,0 +1,27 @@
+use cosmwasm_std::{Addr, Env, StdResult, Storage};
+
+use super::rewards::{accrue_stream, settle_rewards};
+use crate::state::{TOTAL_POWER, VOTING_POWER};
+
+// Checkpoint `owner`'s new weight and the new total for the ...AtHeight queries. Their
+// streaming rewards are settled at `previous`, the weight they held until now. Callers
+// pass it in because positions unchanged since checkpointing began have no checkpoint
+// to read it from, and the position itself is already saved.
+pub(crate) fn checkpoint_power(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &Addr,
+    previous: u64,
+    weight: u64,
+    total_weight: u64,
+) -> StdResult<()> {
+    let stream = accrue_stream(storage, env)?;
+    settle_rewards(storage, &stream, owner, previous)?;
+    let height = env.block.height;
+    if weight == 0 {
+        VOTING_POWER.remove(storage, owner, height)?;
+    } else {
+        VOTING_POWER.save(storage, owner, &weight, height)?;
+    }
+    TOTAL_POWER.save(storage, &total_weight, height)
+}
@@ -0,0 +1,380 @@
+use cosmwasm_std::{Addr, Deps, Env, Order, StdResult, Timestamp};
+use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
+
+use crate::state::{
+    stakers, Config, Staker, DRAW_EXCLUSIONS, LAST_ACTIVITY, LAST_WIN_ROUND, SECONDS_IN_DAY,
+    STAKED_NFTS, STATE,
+};
+
+// Size of the draw pool, counted in one pass over the positions without holding on to it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct PoolTotals {
+    pub entries: u64,
+    // Sum of the effective weights before the win probability cap
+    pub uncapped_weight: u128,
+}
+
+// Draw candidates as (address, position, effective_weight) in order of staked_at, then
+// address. Read straight from the positions so the pool can't drift from them: a
+// staker qualifies once at least one of their NFTs has matured and, if an activity
+// timeout is set, they were active recently enough. No NFT of a position is older than
+// the position, so the scan stops at the first one opened too recently to have matured.
+// Weights are not capped yet; one staker is deserialized at a time. With
+// max_entries_per_round set only that many candidates are yielded, so later positions
+// wait until earlier ones leave; a caller resuming from start_after bounds the rest by
+// PoolTotals::entries.
+fn candidates<'a>(
+    deps: Deps<'a>,
+    env: &'a Env,
+    config: &'a Config,
+    start_after: Option<(u64, &'a Addr)>,
+) -> impl Iterator<Item = StdResult<(Addr, Staker, u64)>> + 'a {
+    let now = env.block.time.seconds();
+    let matured_by = now.saturating_sub(config.min_staking_seconds());
+    let start = start_after.map(Bound::exclusive);
+    let max_entries = match config.max_entries_per_round {
+        0 => usize::MAX,
+        max => max as usize,
+    };
+    stakers()
+        .idx
+        .staked_at
+        .range(deps.storage, start, None, Order::Ascending)
+        .take_while(move |item| match item {
+            Ok((_, staker)) => staker.staked_at.seconds() <= matured_by,
+            Err(_) => true,
+        })
+        .filter_map(move |item| {
+            item.and_then(|(address, staker)| {
+                let weight = eligible_weight(deps, env, config, &address, &staker)?;
+                Ok(weight.map(|weight| (address, staker, weight)))
+            })
+            .transpose()
+        })
+        .take(max_entries)
+}
+
+pub(crate) fn pool_totals(deps: Deps, env: &Env, config: &Config) -> StdResult<PoolTotals> {
+    let mut totals = PoolTotals::default();
+    for item in candidates(deps, env, config, None) {
+        let (_, _, weight) = item?;
+        totals.entries += 1;
+        totals.uncapped_weight += weight as u128;
+    }
+    Ok(totals)
+}
+
+// The whole pool with capped weights. Only for queries that return it; draws stream it.
+pub(crate) fn collect_eligible_stakers(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+) -> StdResult<Vec<(String, u64)>> {
+    let pool = candidates(deps, env, config, None).collect::<StdResult<Vec<_>>>()?;
+    let totals = PoolTotals {
+        entries: pool.len() as u64,
+        uncapped_weight: pool.iter().map(|(_, _, weight)| *weight as u128).sum(),
+    };
+    Ok(pool
+        .into_iter()
+        .map(|(address, _, weight)| {
+            let weight = capped_weight(weight, &totals, config.max_win_probability_bps);
+            (address.to_string(), weight)
+        })
+        .collect())
+}
+
+// Up to `limit` candidates after `start_after`, a position's (staked_at seconds,
+// address), with capped weights. Lets a caller that writes to storage for every
+// candidate go through the pool a page at a time.
+pub(crate) fn candidate_page(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    totals: &PoolTotals,
+    start_after: Option<(u64, &Addr)>,
+    limit: usize,
+) -> StdResult<Vec<(Addr, Staker, u64)>> {
+    let max_bps = config.max_win_probability_bps;
+    candidates(deps, env, config, start_after)
+        .take(limit)
+        .map(|item| {
+            let (address, staker, weight) = item?;
+            Ok((address, staker, capped_weight(weight, totals, max_bps)))
+        })
+        .collect()
+}
+
+// Sum of the capped weights, the tickets in the draw
+pub(crate) fn pool_tickets(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    totals: &PoolTotals,
+) -> StdResult<u64> {
+    let mut tickets = 0u64;
+    for item in candidates(deps, env, config, None) {
+        let (_, _, weight) = item?;
+        tickets += capped_weight(weight, totals, config.max_win_probability_bps);
+    }
+    Ok(tickets)
+}
+
+// Cap `weight` so that weight / (weight + everyone else's weight) stays within max_bps.
+// Caps are computed against the uncapped weights of the others, so the resulting total
+// only goes down. A cap never drops a staker below weight 1.
+pub(crate) fn capped_weight(weight: u64, totals: &PoolTotals, max_bps: u16) -> u64 {
+    if max_bps == 0 || max_bps >= 10_000 {
+        return weight;
+    }
+    let others = totals.uncapped_weight - weight as u128;
+    let cap = others * max_bps as u128 / (10_000 - max_bps) as u128;
+    (weight as u128).min(cap.max(1)) as u64
+}
+
+// Tickets `address` holds in the current draw pool, None if it isn't a candidate
+pub(crate) fn pool_weight(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    address: &Addr,
+) -> StdResult<Option<u64>> {
+    let Some(staker) = stakers().may_load(deps.storage, address)? else {
+        return Ok(None);
+    };
+    let Some(weight) = eligible_weight(deps, env, config, address, &staker)? else {
+        return Ok(None);
+    };
+    if config.max_entries_per_round > 0 && !within_entry_cap(deps, env, config, address)? {
+        return Ok(None);
+    }
+    let totals = pool_totals(deps, env, config)?;
+    let max_bps = config.max_win_probability_bps;
+    Ok(Some(capped_weight(weight, &totals, max_bps)))
+}
+
+// Whether `address` is one of the max_entries_per_round candidates that enter the draw
+fn within_entry_cap(deps: Deps, env: &Env, config: &Config, address: &Addr) -> StdResult<bool> {
+    for item in candidates(deps, env, config, None) {
+        if item?.0 == *address {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Effective weight of a staker who qualifies for the next draw, None otherwise. Only
+// matured NFTs count.
+fn eligible_weight(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    address: &Addr,
+    staker: &Staker,
+) -> StdResult<Option<u64>> {
+    if DRAW_EXCLUSIONS.has(deps.storage, address) {
+        return Ok(None);
+    }
+    if sits_out_after_win(deps, config, address)? {
+        return Ok(None);
+    }
+    let tickets = matured_tickets(deps, config, address, staker, env.block.time)?;
+    if tickets == 0 {
+        return Ok(None);
+    }
+    let last_activity = last_activity(deps, address.as_str(), staker)?;
+    if let Some(timeout) = config.activity_timeout_seconds {
+        if last_activity.plus_seconds(timeout) < env.block.time {
+            return Ok(None);
+        }
+    }
+    let weight = decayed_weight(tickets, last_activity, env.block.time, config);
+    Ok(Some(weight))
+}
+
+// Whether `address` won within the last winner_cooldown_rounds rounds. The round being
+// drawn is the one after draw_count, which complete_draw only saves once the pool has
+// been read, so this round's own winners are not excluded from its prize tiers.
+fn sits_out_after_win(deps: Deps, config: &Config, address: &Addr) -> StdResult<bool> {
+    if config.winner_cooldown_rounds == 0 {
+        return Ok(false);
+    }
+    let Some(won_in) = LAST_WIN_ROUND.may_load(deps.storage, address)? else {
+        return Ok(false);
+    };
+    let next_round = STATE.load(deps.storage)?.draw_count + 1;
+    Ok(won_in < next_round && next_round - won_in <= config.winner_cooldown_rounds as u64)
+}
+
+// Tickets of `owner`'s NFTs that have been staked for the minimum period, each custody
+// token with the duration bonus of its own staked_at. Wallet NFTs and permanent tickets
+// have no token timestamp and go by the position's staked_at.
+pub(crate) fn matured_tickets(
+    deps: Deps,
+    config: &Config,
+    owner: &Addr,
+    staker: &Staker,
+    now: Timestamp,
+) -> StdResult<u128> {
+    let matured =
+        |staked_at: Timestamp| staked_at.plus_seconds(config.min_staking_seconds()) <= now;
+    let mut tickets = 0u128;
+    let mut custody = 0u64;
+    for item in STAKED_NFTS
+        .sub_prefix(owner)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, staked) = item?;
+        custody += 1;
+        if matured(staked.staked_at) {
+            let multiplier = 1 + duration_bonus(config, staked.staked_at, now) as u128;
+            tickets += staked.weight as u128 * multiplier;
+        }
+    }
+    if matured(staker.staked_at) {
+        let untracked = staker.nft_count.saturating_sub(custody) + staker.permanent_weight();
+        let multiplier = 1 + duration_bonus(config, staker.staked_at, now) as u128;
+        tickets += untracked as u128 * multiplier;
+    }
+    Ok(tickets)
+}
+
+// When the first NFT of `owner`'s position matures, which may already have passed.
+// Custody tokens go by their own staked_at; wallet NFTs and permanent tickets by the
+// position's.
+pub(crate) fn first_maturity(
+    deps: Deps,
+    config: &Config,
+    owner: &Addr,
+    staker: &Staker,
+) -> StdResult<Timestamp> {
+    let mut custody = 0u64;
+    let mut earliest: Option<Timestamp> = None;
+    for item in STAKED_NFTS
+        .sub_prefix(owner)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, staked) = item?;
+        custody += 1;
+        earliest = Some(earliest.map_or(staked.staked_at, |at| at.min(staked.staked_at)));
+    }
+    let untracked = staker.nft_count.saturating_sub(custody) + staker.permanent_weight();
+    let staked_at = match earliest {
+        Some(at) if untracked == 0 => at,
+        _ => staker.staked_at,
+    };
+    Ok(staked_at.plus_seconds(config.min_staking_seconds()))
+}
+
+// Stakers from before activity tracking count as active from when they staked
+pub(crate) fn last_activity(deps: Deps, address: &str, staker: &Staker) -> StdResult<Timestamp> {
+    let timestamp = LAST_ACTIVITY.may_load(deps.storage, address.to_string())?;
+    Ok(timestamp.unwrap_or(staker.staked_at))
+}
+
+// The staker's collection-weighted tickets, multiplied by any duration bonus and reduced
+// by the configured decay for every full week without activity, for a position whose
+// NFTs were all staked at staker.staked_at. A staked position never decays below a
+// single ticket.
+pub fn compute_effective_weight(
+    staker: &Staker,
+    last_activity: Timestamp,
+    now: Timestamp,
+    config: &Config,
+) -> u64 {
+    let duration_multiplier = 1 + duration_bonus(config, staker.staked_at, now) as u128;
+    let base_weight = staker.weight() as u128 * duration_multiplier;
+    decayed_weight(base_weight, last_activity, now, config)
+}
+
+// `base_weight` less the decay for every full week since `last_activity`, at least 1
+fn decayed_weight(
+    base_weight: u128,
+    last_activity: Timestamp,
+    now: Timestamp,
+    config: &Config,
+) -> u64 {
+    let weeks_inactive = now.seconds().saturating_sub(last_activity.seconds()) / (7 * SECONDS_IN_DAY);
+    let decay = base_weight * config.weight_decay_rate_bps_per_week as u128 * weeks_inactive as u128
+        / 10_000;
+    base_weight.saturating_sub(decay).max(1).min(u64::MAX as u128) as u64
+}
+
+// Extra tickets per NFT earned by an NFT staked since `staked_at`
+fn duration_bonus(config: &Config, staked_at: Timestamp, now: Timestamp) -> u64 {
+    let Some(weighting) = &config.duration_weighting else {
+        return 0;
+    };
+    let periods = now.seconds().saturating_sub(staked_at.seconds()) / weighting.period_seconds;
+    let bonus = periods.saturating_mul(weighting.tickets_per_period);
+    if weighting.max_bonus_tickets == 0 {
+        bonus
+    } else {
+        bonus.min(weighting.max_bonus_tickets)
+    }
+}
+
+// Draw up to `count` distinct winners. First place uses `entropy` as is, so it matches
+// SimulateDraw; later places skip the earlier winners and hash the place number into
+// the entropy.
+pub(crate) fn pick_winners(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    totals: &PoolTotals,
+    entropy: &[u8],
+    count: usize,
+) -> StdResult<Vec<String>> {
+    let mut winners = Vec::new();
+    for place in 0..count as u64 {
+        let seed = match place {
+            0 => entropy.to_vec(),
+            _ => [entropy, &place.to_be_bytes()].concat(),
+        };
+        let Some(winner) = pick_winner(deps, env, config, totals, &seed, &winners)? else {
+            break;
+        };
+        winners.push(winner);
+    }
+    Ok(winners)
+}
+
+// Weighted reservoir sampling over the candidates: each one replaces the current pick
+// with probability weight / (weight of everyone seen so far), so every candidate ends
+// up picked in proportion to its weight after a single pass, without building the
+// pool. Candidates in `skip` are left out. Returns None if nobody is left.
+pub(crate) fn pick_winner(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    totals: &PoolTotals,
+    entropy: &[u8],
+    skip: &[String],
+) -> StdResult<Option<String>> {
+    let digest = Sha256::digest(entropy);
+    let mut random = u64::from_be_bytes(digest[0..8].try_into().unwrap());
+    let mut seen = 0u128;
+    let mut picked = None;
+    for item in candidates(deps, env, config, None) {
+        let (address, _, weight) = item?;
+        if skip.iter().any(|winner| winner == address.as_str()) {
+            continue;
+        }
+        let weight = capped_weight(weight, totals, config.max_win_probability_bps) as u128;
+        seen += weight;
+        if next_random(&mut random) as u128 % seen < weight {
+            picked = Some(address.to_string());
+        }
+    }
+    Ok(picked)
+}
+
+// splitmix64, enough to spread one digest over a pass of the pool
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,200 @@
+use cosmwasm_std::{from_json, DepsMut, Env, Order, StdError, StdResult, Timestamp, Uint128};
+use cw_utils::Duration;
+
+use crate::state::{
+    stakers, Collection, ConfigMinStakingDaysV1, StakedNft, State, StateV1, UnbondingNft,
+    COLLECTIONS, CONFIG, KNOWN_STAKERS, LEGACY_STAKED_NFTS, LEGACY_STAKERS, LEGACY_STAKER_SET,
+    LEGACY_UNBONDING, SECONDS_IN_DAY, STAKED_NFTS, STATE, TOTAL_POWER, UNBONDING,
+};
+
+// Save the v1 State without its stakers HashSet, keeping only its size as staker_count.
+// Membership comes from the positions, which migrate_stakers_key_format moves into the
+// positions map. State that no longer parses as v1 has already been migrated and is left alone.
+pub(crate) fn migrate_state_v1_to_v2(deps: DepsMut) -> StdResult<u64> {
+    let raw = deps
+        .storage
+        .get(STATE.as_slice())
+        .ok_or_else(|| StdError::not_found("State"))?;
+    let old: StateV1 = match from_json(&raw) {
+        Ok(old) => old,
+        Err(_) => return Ok(0),
+    };
+
+    let state = State {
+        total_staked: old.total_staked,
+        current_pot: old.current_pot,
+        last_winner: old.last_winner,
+        staker_count: old.stakers.len() as u64,
+        staker_event_count: 0,
+        next_draw_interval_seconds: SECONDS_IN_DAY,
+        draw_count: 0,
+        last_draw_time: None,
+        last_admin_action_time: Timestamp::default(),
+        native_pot: vec![],
+        pending_draw_job: None,
+        pending_drand_round: None,
+        pending_commit_reveal: None,
+        total_bonus_weight: 0,
+        total_burned_weight: 0,
+        total_burns: 0,
+        total_prizes_paid: Uint128::zero(),
+        unique_stakers: 0,
+        jackpot: Uint128::zero(),
+        last_jackpot: None,
+        total_compounded_weight: 0,
+    };
+    STATE.save(deps.storage, &state)?;
+
+    Ok(state.staker_count)
+}
+
+// Drop the staker_set entries written by earlier migrations and stakes. Returns the
+// number removed.
+pub(crate) fn clear_staker_set(deps: DepsMut) -> StdResult<u32> {
+    let addresses = LEGACY_STAKER_SET
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for address in &addresses {
+        LEGACY_STAKER_SET.remove(deps.storage, address.clone());
+    }
+    Ok(addresses.len() as u32)
+}
+
+// Start the addresses that have ever staked from the current positions, on contracts
+// from before they were tracked. Who staked and left before then isn't known. Returns
+// the number of addresses recorded.
+pub(crate) fn seed_unique_stakers(deps: DepsMut) -> StdResult<u64> {
+    let mut state = STATE.load(deps.storage)?;
+    if state.unique_stakers > 0 {
+        return Ok(0);
+    }
+    let addresses = stakers()
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for address in &addresses {
+        KNOWN_STAKERS.save(deps.storage, address, &true)?;
+    }
+    state.unique_stakers = addresses.len() as u64;
+    STATE.save(deps.storage, &state)?;
+    Ok(state.unique_stakers)
+}
+
+// Turn a stored min_staking_days into min_staking_period. Config saved since then has
+// no min_staking_days left to convert. Returns whether anything was converted.
+pub(crate) fn migrate_min_staking_period(deps: DepsMut) -> StdResult<bool> {
+    let raw = deps
+        .storage
+        .get(CONFIG.as_slice())
+        .ok_or_else(|| StdError::not_found("Config"))?;
+    let Some(days) = from_json::<ConfigMinStakingDaysV1>(&raw)?.min_staking_days else {
+        return Ok(false);
+    };
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.min_staking_period = Duration::Time(days * SECONDS_IN_DAY);
+    CONFIG.save(deps.storage, &config)?;
+    Ok(true)
+}
+
+// Write the index entries of positions saved before their indexes existed. Once every
+// index has entries the positions are left alone. Returns the number indexed.
+pub(crate) fn index_staker_positions(deps: DepsMut) -> StdResult<u32> {
+    let indexes = stakers().idx;
+    let indexed = [&indexes.staked_at, &indexes.weight].iter().all(|index| {
+        index
+            .range_raw(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+    });
+    if indexed {
+        return Ok(0);
+    }
+    let positions = stakers()
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (address, staker) in &positions {
+        // Saving over the entry drops whatever index entries it has and writes them all
+        stakers().save(deps.storage, address, staker)?;
+    }
+    Ok(positions.len() as u32)
+}
+
+// Re-write every entry under the old String-keyed "stakers" namespace into the
+// positions map, validating each address on the way. Returns the number of entries moved.
+pub fn migrate_stakers_key_format(deps: DepsMut) -> StdResult<u32> {
+    let legacy = LEGACY_STAKERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut migrated = 0u32;
+    for (address, staker) in legacy {
+        let addr = deps.api.addr_validate(&address)?;
+        stakers().save(deps.storage, &addr, &staker)?;
+        LEGACY_STAKERS.remove(deps.storage, address);
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+// Re-key custody tokens from the single-collection layout under nft_contract, which is
+// registered with weight 1 if it isn't yet, and record the collection of unbonding
+// tokens. Returns the number of custody tokens moved.
+pub(crate) fn migrate_custody_collections(deps: DepsMut) -> StdResult<u32> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut collection = COLLECTIONS
+        .may_load(deps.storage, &config.nft_contract)?
+        .unwrap_or(Collection {
+            weight: 1,
+            staked: 0,
+            trait_weights: vec![],
+            allowlisted: 0,
+        });
+
+    let legacy = LEGACY_STAKED_NFTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut migrated = 0u32;
+    for ((owner, token_id), staked_at) in legacy {
+        let staked = StakedNft {
+            staked_at,
+            weight: 1,
+            lock: None,
+            receipt_id: None,
+        };
+        STAKED_NFTS.save(deps.storage, (&owner, &config.nft_contract, &token_id), &staked)?;
+        LEGACY_STAKED_NFTS.remove(deps.storage, (&owner, &token_id));
+        collection.staked += 1;
+        migrated += 1;
+    }
+    COLLECTIONS.save(deps.storage, &config.nft_contract, &collection)?;
+
+    // Entries that already record their collection don't parse as the old layout
+    let legacy_unbonding = LEGACY_UNBONDING
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    for (owner, nfts) in legacy_unbonding {
+        let nfts: Vec<UnbondingNft> = nfts
+            .into_iter()
+            .map(|nft| UnbondingNft {
+                collection: config.nft_contract.clone(),
+                token_id: nft.token_id,
+                release_at: nft.release_at,
+            })
+            .collect();
+        UNBONDING.save(deps.storage, &owner, &nfts)?;
+    }
+
+    Ok(migrated)
+}
+
+// Start the total power checkpoints at the migration height, so TotalPowerAtHeight is
+// exact from here on. Contracts that already checkpoint are left alone.
+pub(crate) fn seed_total_power(deps: DepsMut, env: &Env) -> StdResult<()> {
+    if TOTAL_POWER.may_load(deps.storage)?.is_some() {
+        return Ok(());
+    }
+    let state = STATE.load(deps.storage)?;
+    TOTAL_POWER.save(deps.storage, &state.total_weight(), env.block.height)
+}
@@ -0,0 +1,754 @@
+use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Decimal, HexBinary, Timestamp, Uint128};
+use cw2::ContractVersion;
+use cw20::Cw20ReceiveMsg;
+use cw721::Cw721ReceiveMsg;
+use cw_utils::Duration;
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_schema::schemars;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{
+    Config, DrandConfig, DurationWeighting, JackpotConfig, JackpotHit, LockTier, PausableAction,
+    PendingPrize, PotSplit, PrizeTier, Staker, TokenLock, TraitWeight, UnbondingNft,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub admin: String,
+    pub nft_contract: String,
+    pub reward_token: String,
+    pub activity_timeout_seconds: Option<u64>,
+    #[serde(default)]
+    pub weight_decay_rate_bps_per_week: u16,
+    pub min_draw_interval_seconds: Option<u64>,
+    pub max_draw_interval_seconds: Option<u64>,
+    pub badge_nft_contract: Option<String>,
+    pub target_pot_size: Option<Uint128>,
+    pub accelerated_cooldown_seconds: Option<u64>,
+    pub abandonment_timeout_seconds: Option<u64>,
+    #[serde(default)]
+    pub max_win_probability_bps: u16,
+    pub stake_receipt_contract: Option<String>,
+    // Defaults to seven days. Only time durations are accepted.
+    pub min_staking_period: Option<Duration>,
+    pub duration_weighting: Option<DurationWeighting>,
+    pub nois_proxy: Option<String>,
+    pub drand: Option<DrandConfig>,
+    pub commit_reveal: Option<CommitRevealSettings>,
+    pub claim_window_seconds: Option<u64>,
+    #[serde(default)]
+    pub prize_split_bps: Vec<u16>,
+    pub unbonding_seconds: Option<u64>,
+    pub early_unstake_penalty: Option<Uint128>,
+    #[serde(default)]
+    pub keeper_reward_bps: u16,
+    pub croncat: Option<CronCatSettings>,
+    #[serde(default)]
+    pub burn_multiplier: u64,
+    #[serde(default)]
+    pub sacrifice_bps: u16,
+    #[serde(default)]
+    pub allow_wallet_stakes: bool,
+    #[serde(default)]
+    pub lock_tiers: Vec<LockTier>,
+    #[serde(default)]
+    pub winner_cooldown_rounds: u32,
+    #[serde(default)]
+    pub max_nfts_per_staker: u64,
+    #[serde(default)]
+    pub max_entries_per_round: u64,
+    pub stake_fee: Option<Coin>,
+    #[serde(default)]
+    pub protocol_fee_bps: u16,
+    pub treasury: Option<String>,
+    #[serde(default)]
+    pub pot_split: PotSplit,
+    pub jackpot: Option<JackpotConfig>,
+    #[serde(default)]
+    pub emission_per_second: Uint128,
+    #[serde(default)]
+    pub compound_rate: Uint128,
+    #[serde(default)]
+    pub push_payouts: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommitRevealSettings {
+    pub contributors: Vec<String>,
+    pub commit_window_seconds: u64,
+    pub reveal_window_seconds: u64,
+    pub min_reveals: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CronCatSettings {
+    pub tasks: String,
+    pub manager: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum QueryMsg {
+    // Stakers past the minimum staking period, oldest position first; start_after is the
+    // last address of the previous page and an empty page means the end
+    GetEligibleStakers { start_after: Option<String>, limit: Option<u32> },
+    GetState {},
+    GetConfig {},
+    // The queued minimum staking period, if any
+    GetPendingMinStakingPeriod {},
+    // The queued protocol fee, if any
+    GetPendingProtocolFee {},
+    // Current jackpot, its settings and the last time it paid out
+    GetJackpot {},
+    // Streaming rewards `address` can claim as of this block
+    GetStakingRewards { address: String },
+    // StakerSettings of `address`, the defaults if never set
+    GetStakerSettings { address: String },
+    // The current merkle distribution, if one has been posted
+    GetMerkleDistribution {},
+    // Whether `address` has claimed from the current merkle distribution
+    MerkleClaimed { address: String },
+    GetStaker { address: String },
+    // Custody tokens of `address` in (collection, token_id) order; start_after is the
+    // (collection, token_id) of the last token of the previous page
+    GetStakedTokens {
+        address: String,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    },
+    // Collections accepted for staking, with their weights and tokens in custody
+    ListCollections { start_after: Option<String>, limit: Option<u32> },
+    // Custody tokens of `address` per collection
+    GetStakerCollections { address: String },
+    // Whether the token's collection is registered and the token passes its allowlist
+    IsStakeable { collection: String, token_id: String },
+    // Unstaked tokens of `address` still in custody, in unstaking order
+    GetUnbondingClaims { address: String },
+    // Every staker in address order; start_after is the last address of the previous page
+    ListStakers { start_after: Option<String>, limit: Option<u32> },
+    // How many more NFTs `address` can stake under max_nfts_per_staker
+    GetStakeCap { address: String },
+    // Addresses kept out of draws, in address order
+    GetDrawExclusions { start_after: Option<String>, limit: Option<u32> },
+    // Stakers by weight, from the staker weight index; heaviest first when descending
+    ListStakersByWeight { descending: bool, limit: Option<u32> },
+    // Entries the address holds in the next draw: its collection weights, after decay and
+    // the win cap
+    GetTickets { address: String },
+    // Size of the next draw's pool without the pool itself
+    GetEligibleCount {},
+    // Seconds until `address` is eligible for draws and until each of its custody tokens
+    // has been staked for the minimum period, 0 for what already has
+    EligibleIn { address: String },
+    // Tickets of `address` against the whole pool, and its chance of first place
+    GetOdds { address: String },
+    // Lifetime totals of the contract in one call
+    Stats {},
+    // Position, custody tokens, tickets, unclaimed prize and unbonding tokens of
+    // `address`, with how long until it is eligible and when the next draw can run
+    Dashboard { address: String },
+    // Everything won by `address` and not yet claimed: its share of drawn pots and its
+    // prizes from each configured tier
+    GetPendingRewards { address: String },
+    // When DrawWinner can next run. Draws are only gated on time, not block height.
+    NextDraw {},
+    // Run the draw selection on `entropy_override` without saving anything. A draw's own
+    // randomness only arrives from its source, so there is nothing to fall back on.
+    SimulateDraw { entropy_override: Option<Binary> },
+    // Top addresses by lifetime winnings, highest first
+    GetPrizeLeaderboard { limit: Option<u32> },
+    GetLifetimeWinnings { address: String },
+    // Top addresses by NFTs burned with BurnForTickets, most first
+    GetBurnLeaderboard { limit: Option<u32> },
+    // Largest stakers by weight, heaviest first, with the places each has won
+    Leaderboard { limit: Option<u32> },
+    ContractInfo {},
+    GetOwnership {},
+    // Balance of every configured tier's pool, in configuration order
+    GetTierPots {},
+    GetBadgeContract {},
+    // Contracts notified of stake changes, in registration order
+    GetHooks {},
+    GetPaused {},
+    // Hash of the CronCat task scheduling draws, None until one is registered
+    GetDrawTask {},
+    ProveEligibility { address: String },
+    // Events in sequence order, returned as (sequence, event) pairs
+    GetStakerHistory { address: String, start_after: Option<u64>, limit: Option<u32> },
+    GetIntervalVotes {},
+    // Weight an address had in a past draw, None if it wasn't a candidate
+    GetDrawSnapshot { round_id: u64, address: String },
+    // Record of a completed draw, None for a round that hasn't been drawn
+    GetRound { round_id: u64 },
+    // Past draws, newest first; start_after is the round_id of the last entry of the
+    // previous page
+    ListWinners { start_after: Option<u64>, limit: Option<u32> },
+    GetFullDrawSnapshot { round_id: u64, start_after: Option<String>, limit: Option<u32> },
+    // DAO DAO voting module interface, under the snake_case keys proposal modules send.
+    // Voting power is one per staked NFT.
+    #[serde(rename = "voting_power_at_height")]
+    VotingPowerAtHeight { address: String, height: Option<u64> },
+    #[serde(rename = "total_power_at_height")]
+    TotalPowerAtHeight { height: Option<u64> },
+    #[serde(rename = "info")]
+    Info {},
+    #[serde(rename = "dao")]
+    Dao {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum ExecuteMsg {
+    // With up to 30 token_ids, pulls those tokens from nft_contract, where the sender owns
+    // them and has approved this contract, and stakes each once its transfer has gone
+    // through. Without token_ids, counts one NFT kept in the sender's wallet, if
+    // allow_wallet_stakes is set. lock_duration picks one of lock_tiers and needs
+    // token_ids.
+    Stake {
+        #[serde(default)]
+        token_ids: Vec<String>,
+        lock_duration: Option<u64>,
+    },
+    // cw721 SendNft hook; stakes the token for the original sender, locked if the
+    // embedded message is a ReceiveNftMsg with a lock_duration. Renamed to the snake_case
+    // key cw721 contracts send.
+    #[serde(rename = "receive_nft")]
+    ReceiveNft(Cw721ReceiveMsg),
+    Unstake {},
+    // Withdraw one custody token once it has been staked for the minimum period.
+    // collection defaults to nft_contract.
+    UnstakeToken { collection: Option<String>, token_id: String },
+    // Stake up to 30 tokens the sender owns and has approved this contract for, pulled
+    // with TransferNft. collection defaults to nft_contract, and every token gets the
+    // lock of lock_duration, if any.
+    StakeBatch {
+        collection: Option<String>,
+        token_ids: Vec<String>,
+        lock_duration: Option<u64>,
+    },
+    // Relock up to 30 custody tokens for new_duration, one of lock_tiers, starting now.
+    // Their staked_at is kept. collection defaults to nft_contract.
+    ExtendLock {
+        collection: Option<String>,
+        token_ids: Vec<String>,
+        new_duration: u64,
+    },
+    // UnstakeToken for up to 30 tokens of one collection
+    UnstakeBatch { collection: Option<String>, token_ids: Vec<String> },
+    // Return every unstaked token whose unbonding period has passed
+    ClaimUnstaked {},
+    // Irreversibly burn a custody token for burn_multiplier times its tickets, kept
+    // across rounds even after every NFT is unstaked. collection defaults to
+    // nft_contract.
+    BurnForTickets { collection: Option<String>, token_id: String },
+    // Starts a draw from the configured randomness source, failing without one. With a
+    // Nois proxy this requests randomness; attached funds pay the proxy and the draw
+    // completes in NoisReceive. With drand it picks the next round and the draw completes
+    // in SubmitDrandBeacon. With commit-reveal it opens the commit window and the draw
+    // completes in FinalizeDraw. With a keeper reward set anyone can call it once the
+    // cooldown has passed and is paid that cut of the pot.
+    DrawWinner {},
+    // Randomness callback from the Nois proxy, under the key the proxy sends
+    #[serde(rename = "nois_receive")]
+    NoisReceive { callback: NoisCallback },
+    // Completes a drand draw with the beacon of the round DrawWinner picked. Anyone can
+    // submit it since the signature is verified against the configured network.
+    SubmitDrandBeacon { round: u64, previous_signature: HexBinary, signature: HexBinary },
+    // sha256 of a secret, from a registered contributor during the commit window
+    CommitEntropy { commitment: HexBinary },
+    // The committed secret, during the reveal window
+    RevealEntropy { secret: HexBinary },
+    // Mixes the revealed secrets into the draw seed once the reveals are in. Callable by
+    // anyone.
+    FinalizeDraw {},
+    ClaimReward {},
+    // Pays out the reward tokens streamed to the sender so far
+    ClaimStakingRewards {},
+    // Pays out every prize and streaming reward the sender is owed in one transfer
+    ClaimAll {},
+    // Opt in or out of turning main pot prizes into compounded tickets at compound_rate
+    SetAutoCompound { enabled: bool },
+    // Admin only; posts the root of an off-chain computed reward token distribution,
+    // replacing the current one
+    PostMerkleRoot { merkle_root: HexBinary },
+    // Claim `amount` from the current merkle distribution. Leaves are
+    // sha256(address + amount), siblings hashed in ascending byte order.
+    ClaimMerkle { proof: Vec<HexBinary>, amount: Uint128 },
+    // Admin only; reward tokens streamed to stakers per second from now on
+    SetEmissionRate { emission_per_second: Uint128 },
+    // Return prizes whose claim window has passed to the pot. Callable by anyone. Scans up
+    // to `limit` winners per call; pass the previous call's last_key as start_after.
+    SweepExpiredPrizes { start_after: Option<String>, limit: Option<u32> },
+    // Admin only; replaces the prize tiers drawn alongside the main pot
+    SetPrizeTiers { tiers: Vec<PrizeTier> },
+    // Admin only; replaces the lock tiers offered to new stakes. Tokens already locked
+    // keep their terms.
+    SetLockTiers { tiers: Vec<LockTier> },
+    // Admin only; shares of each drawn pot carried into the next round and burned, from
+    // the next draw on. The winners get the rest.
+    SetPotSplit { rollover_bps: u16, burn_bps: u16 },
+    ClaimTierPrize { tier: String },
+    // A different admin is only proposed, as with TransferAdmin. The addresses are held to
+    // the same rules as in UpdateConfig.
+    SetConfig { admin: String, nft_contract: String, reward_token: String },
+    // Admin only. Propose a new admin, who has to AcceptAdmin before anything changes
+    TransferAdmin { new_admin: String },
+    AcceptAdmin {},
+    FundPot {},
+    // cw20 Send hook; the embedded message is a ReceiveMsg
+    #[serde(rename = "receive")]
+    Receive(Cw20ReceiveMsg),
+    // Pull `amount` of the reward token via TransferFrom; needs a prior IncreaseAllowance
+    FundPotByAllowance { amount: Uint128 },
+    ValidateStateInvariants {},
+    CheckIn {},
+    VoteDrawInterval { preferred_seconds: u64 },
+    // Admin only. Re-sums nft_count over the positions in batches and rewrites total_staked once
+    // the last batch is counted. Pass the previous call's last_key as start_after.
+    RecalculateTotalStaked { start_after: Option<String>, batch_size: u32 },
+    // Once the admin has been silent past abandonment_timeout_seconds, close the sender's
+    // position and pay out their nft_count share of the pot
+    AbandonmentExit {},
+    // Admin only. Returns up to `limit` custody tokens, unbonding ones included, to their
+    // owners and closes the matching stakes. Repeat until is_complete is true.
+    EmergencyReturnNfts { limit: u32 },
+    // Admin only. An empty list pauses or unpauses every action.
+    Pause { actions: Vec<PausableAction> },
+    Unpause { actions: Vec<PausableAction> },
+    // Admin only. Registers a CronCat task that calls DrawWinner on the cron schedule,
+    // paid with `funding` taken from the native pot. Replaces any earlier task.
+    ScheduleDraws { cron: String, funding: Coin },
+    // Admin only. Registered contracts are sent a StakeChangedExecuteMsg on every stake
+    // and unstake.
+    AddHook { addr: String },
+    RemoveHook { addr: String },
+    // Admin only. Registers a collection, or changes its weight for tokens staked from
+    // now on. Each of its NFTs counts `weight` tickets.
+    AddCollection { address: String, weight: u64 },
+    // Admin only. Fails while any of the collection's tokens are in custody.
+    RemoveCollection { address: String },
+    // Admin only. Replaces the collection's trait weights, which apply to tokens staked
+    // from now on. Each token counts its highest matching trait weight, or the collection
+    // weight if no trait matches.
+    SetTraitWeights { collection: String, trait_weights: Vec<TraitWeight> },
+    // Admin only. While a collection's allowlist has entries only those tokens can be
+    // staked; removing every entry opens it to all tokens again.
+    AllowTokens { collection: String, token_ids: Vec<String> },
+    DisallowTokens { collection: String, token_ids: Vec<String> },
+    // Admin only. Excluded addresses can stake and vote but never enter a draw.
+    ExcludeFromDraws { addresses: Vec<String> },
+    IncludeInDraws { addresses: Vec<String> },
+    // Admin only. Queues a new minimum staking period, which anyone can apply with
+    // ApplyMinStakingPeriod once the three day timelock has passed. Replaces any period
+    // already queued.
+    ProposeMinStakingPeriod { period: Duration },
+    ApplyMinStakingPeriod {},
+    // Admin only. Queues a new protocol fee and treasury, which anyone can apply with
+    // ApplyProtocolFee once the three day timelock has passed. A fee of 0 turns it off.
+    ProposeProtocolFee { fee_bps: u16, treasury: Option<String> },
+    ApplyProtocolFee {},
+    // Admin only. Every field is optional and left unchanged when omitted.
+    // draw_interval_seconds overrides the voted interval until the next draw.
+    UpdateConfig {
+        nft_contract: Option<String>,
+        reward_token: Option<String>,
+        min_draw_interval_seconds: Option<u64>,
+        max_draw_interval_seconds: Option<u64>,
+        draw_interval_seconds: Option<u64>,
+    },
+}
+
+// Messages embedded in a cw20 Send to this contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum ReceiveMsg {
+    FundPot {},
+    // Credit the pool of one prize tier instead of the main pot
+    FundTier { tier: String },
+    // Add to the reserve streamed to stakers at emission_per_second
+    FundRewards {},
+    // Fund the current merkle distribution
+    FundMerkleDistribution {},
+    // Unstake the sender's latest NFT before the minimum period. The amount sent has to
+    // cover early_unstake_penalty and all of it goes into the pot.
+    EmergencyUnstake {},
+}
+
+// Optional message embedded in a cw721 SendNft to this contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReceiveNftMsg {
+    // One of lock_tiers, in seconds
+    pub lock_duration: Option<u64>,
+}
+
+// Result of ValidateStateInvariants, returned as the response data
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InvariantReport {
+    pub passed: bool,
+    // One entry per violated invariant
+    pub details: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateDrawResponse {
+    pub would_win: String,
+    pub eligible_staker_count: u64,
+    pub total_weight: u64,
+    pub staker_weights: Vec<(String, u64)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IntervalVotesResponse {
+    // (preferred_seconds, total staker weight voting for it), shortest interval first
+    pub histogram: Vec<(u64, u64)>,
+    pub total_weight: u64,
+    pub next_draw_interval_seconds: u64,
+}
+
+// Metadata of the badge minted to a draw winner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WinnerBadgeExt {
+    pub round: u64,
+    pub prize: Uint128,
+    pub eligible_stakers: u64,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintMsg<T = WinnerBadgeExt> {
+    pub token_id: String,
+    pub owner: String,
+    pub token_uri: Option<String>,
+    pub extension: T,
+}
+
+// Request to the Nois proxy; the beacon is delivered to ExecuteMsg::NoisReceive
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NoisProxyExecuteMsg {
+    GetNextRandomness { job_id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NoisCallback {
+    pub job_id: String,
+    pub published: Timestamp,
+    pub randomness: HexBinary,
+}
+
+// Subset of the CronCat tasks contract API used to schedule draws
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CronCatTasksExecuteMsg {
+    CreateTask { task: Box<CronCatTaskRequest> },
+    RemoveTask { task_hash: String },
+}
+
+// The optional boundary, queries, transforms and cw20 fields are left unset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CronCatTaskRequest {
+    pub interval: CronCatInterval,
+    pub stop_on_fail: bool,
+    pub actions: Vec<CronCatAction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CronCatInterval {
+    Once,
+    Immediate,
+    Block(u64),
+    Cron(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CronCatAction {
+    pub msg: CosmosMsg,
+    pub gas_limit: Option<u64>,
+}
+
+// The part of a cw721 token's on-chain metadata read for trait weights, in the layout of
+// cw721-metadata-onchain. Other extension fields are ignored.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftMetadata {
+    pub attributes: Option<Vec<NftTrait>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftTrait {
+    pub trait_type: String,
+    pub value: String,
+}
+
+// Mint message of the badge cw721 contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeExecuteMsg {
+    Mint(MintMsg),
+}
+
+// Sent to every registered hook; amount is the voting power gained or lost. Same shape
+// as the DAO DAO stake hooks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeChangedHookMsg {
+    Stake { addr: Addr, amount: Uint128 },
+    Unstake { addr: Addr, amount: Uint128 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeChangedExecuteMsg {
+    StakeChangeHook(StakeChangedHookMsg),
+}
+
+// Metadata of the receipt minted for every staked NFT. original_token_id is None for
+// stakes made with Stake without token_ids, which don't transfer a token.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeReceiptExt {
+    pub original_token_id: Option<String>,
+    pub staked_at: Timestamp,
+    pub nft_contract: String,
+}
+
+// Mint and burn messages of the stake receipt cw721 contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptExecuteMsg {
+    Mint(MintMsg<StakeReceiptExt>),
+    Burn { token_id: String },
+}
+
+// Self-contained eligibility statement for off-chain verifiers.
+// proof_hash = sha256(address ++ block_height as big-endian u64 ++ effective_weight as big-endian u64)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibilityProof {
+    pub address: String,
+    pub is_eligible: bool,
+    pub staked_nfts: Vec<String>,
+    pub individual_token_timestamps: Vec<Timestamp>,
+    pub effective_weight: u64,
+    pub contract_address: String,
+    pub block_height: u64,
+    pub block_time: Timestamp,
+    pub proof_hash: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedToken {
+    pub collection: Addr,
+    pub token_id: String,
+    pub staked_at: Timestamp,
+    // Tickets the token counts for
+    pub weight: u64,
+    // Whether the token has been staked for the minimum period and can be unstaked
+    pub eligible: bool,
+    // Lock tier and unlock time, if the token was staked with a lock
+    pub lock: Option<TokenLock>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionInfo {
+    pub address: Addr,
+    pub weight: u64,
+    pub staked: u64,
+    pub trait_weights: Vec<TraitWeight>,
+    // Size of the token allowlist, 0 when every token can be staked
+    pub allowlisted: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerCollection {
+    pub collection: Addr,
+    pub staked: u64,
+    // Tickets the staker's tokens from this collection count for
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WinnerRecord {
+    pub round_id: u64,
+    // First place of the round
+    pub winner: String,
+    pub prize: Uint128,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DashboardResponse {
+    // None without a position
+    pub staker: Option<Staker>,
+    // First page of GetStakedTokens
+    pub staked_tokens: Vec<StakedToken>,
+    // As GetTickets
+    pub tickets: u64,
+    pub pending_prize: Option<PendingPrize>,
+    pub unbonding: Vec<UnbondingNft>,
+    // Seconds until the first NFT of the position matures, 0 once one has; None without
+    // a position
+    pub eligible_in_seconds: Option<u64>,
+    // End of the draw cooldown, None before the first draw
+    pub next_draw_time: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRewardsResponse {
+    // Claimed with ClaimReward, None when nothing is owed
+    pub prize: Option<PendingPrize>,
+    // Claimed with ClaimTierPrize, only tiers with something owed
+    pub tier_prizes: Vec<(String, Uint128)>,
+    pub total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NextDrawResponse {
+    // End of the draw cooldown, None before the first draw
+    pub next_draw_time: Option<Timestamp>,
+    // 0 once the cooldown has passed
+    pub seconds_remaining: u64,
+    // A draw is waiting on its randomness and no other can start
+    pub draw_in_progress: bool,
+    // Whether the pot has reached target_pot_size and the accelerated cooldown applies;
+    // always false without a target
+    pub pot_threshold_met: bool,
+    pub current_pot: Uint128,
+    pub target_pot_size: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub rounds_drawn: u64,
+    // Reward token paid out by claims, tier prizes included
+    pub total_prizes_paid: Uint128,
+    pub current_pot: Uint128,
+    // Addresses that have ever opened a position
+    pub unique_stakers: u64,
+    pub nfts_staked: u64,
+    pub nfts_burned: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardEntry {
+    pub address: String,
+    pub nft_count: u64,
+    // Staker::weight, the tickets before duration bonus and decay
+    pub weight: u64,
+    // Places won in the main draw
+    pub wins: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibleCountResponse {
+    pub eligible_stakers: u64,
+    // Sum of the candidates' tickets after decay and the win cap
+    pub total_tickets: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibleInResponse {
+    // Until the first NFT of the position matures, None without a position
+    pub seconds: Option<u64>,
+    // Custody tokens in (collection, token_id) order
+    pub tokens: Vec<TokenEligibleIn>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenEligibleIn {
+    pub collection: Addr,
+    pub token_id: String,
+    pub seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeCapResponse {
+    // 0 when uncapped
+    pub max_nfts_per_staker: u64,
+    pub nft_count: u64,
+    // None when uncapped
+    pub remaining: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OddsResponse {
+    // As GetTickets
+    pub tickets: u64,
+    // As GetEligibleCount
+    pub total_tickets: u64,
+    // tickets / total_tickets, 0 for an empty pool
+    pub win_probability: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TicketsResponse {
+    pub address: String,
+    pub nft_count: u64,
+    // 0 while the stake is still maturing or the staker is inactive
+    pub tickets: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipResponse {
+    pub admin: String,
+    pub pending_admin: Option<String>,
+}
+
+// The stored config with the values derived from it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub config: Config,
+    pub min_staking_seconds: u64,
+    // Share of the pot per place in basis points, first place first
+    pub prize_places: Vec<u16>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotResponse {
+    pub jackpot: Uint128,
+    // None while the jackpot is off
+    pub settings: Option<JackpotConfig>,
+    pub last_hit: Option<JackpotHit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingRewardsResponse {
+    pub pending: Uint128,
+    pub emission_per_second: Uint128,
+    // Funded and not yet emitted; the stream stops when it runs out
+    pub reserve: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InfoResponse {
+    pub info: ContractVersion,
+}
+
+// cw2 name and version of the deployed code
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractInfoResponse {
+    pub name: String,
+    pub version: String,
+}
+
+// Messages the chain itself can send, through an end-blocker hook or governance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    // Same as DrawWinner from the admin, minus the admin activity: the cooldown and
+    // pause still apply
+    TriggerDraw {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    // Skip the cw2 version guard, for deployments that never stored a version
+    pub skip_version_check: bool,
+}
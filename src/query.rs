@@ -0,0 +1,795 @@
+use cosmwasm_std::{Addr, Binary, Decimal, Deps, Env, Order, StdError, StdResult, Uint128};
+use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::execute::{
+    draw_cooldown_seconds, interval_vote_histogram, pending_staking_rewards, token_allowed,
+};
+use crate::lottery::{
+    collect_eligible_stakers, first_maturity, matured_tickets, pick_winner, pool_tickets,
+    pool_totals, pool_weight,
+};
+use crate::msg::{
+    CollectionInfo, ConfigResponse, ContractInfoResponse, DashboardResponse, EligibilityProof,
+    EligibleCountResponse, EligibleInResponse, InfoResponse, IntervalVotesResponse, JackpotResponse,
+    LeaderboardEntry, NextDrawResponse, OddsResponse, OwnershipResponse, PendingRewardsResponse,
+    SimulateDrawResponse, StakeCapResponse, StakedToken, StakerCollection, StakingRewardsResponse,
+    StatsResponse, TicketsResponse, TokenEligibleIn, TotalPowerAtHeightResponse,
+    VotingPowerAtHeightResponse, WinnerRecord,
+};
+use crate::state::{
+    stakers, MerkleDistribution, PausableAction, PendingMinStakingPeriod, PendingProtocolFee, Round,
+    StakeEvent, Staker, StakerSettings, State, UnbondingNft, BURN_LEADERBOARD, COLLECTIONS, CONFIG,
+    DRAW_EXCLUSIONS, DRAW_SNAPSHOTS, DRAW_TASK, HOOKS, LEADERBOARD_SIZE, MERKLE_CLAIMED,
+    MERKLE_DISTRIBUTION, PAUSED, PENDING_ADMIN, PENDING_MIN_STAKING_PERIOD, PENDING_PRIZES,
+    PENDING_PROTOCOL_FEE, PRIZE_LEADERBOARD, ROUNDS, STAKED_NFTS, STAKER_HISTORY, STAKER_SETTINGS,
+    STATE, TIER_POTS, TIER_PRIZES, TOTAL_POWER, TOTAL_WINNINGS, UNBONDING, VOTING_POWER, WIN_COUNTS,
+};
+
+// Page sizes for paginated queries, and for SweepExpiredPrizes
+pub(crate) const DEFAULT_LIMIT: u32 = 10;
+pub(crate) const MAX_LIMIT: u32 = 100;
+
+pub(crate) fn query_eligible_stakers(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Staker)>> {
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // Pages go by the staked_at index, so the page resumes from the last position's
+    // place in it
+    let start_after = match start_after {
+        Some(address) => {
+            let address = deps.api.addr_validate(&address)?;
+            let staker = stakers().load(deps.storage, &address)?;
+            Some((staker.staked_at.seconds(), address))
+        }
+        None => None,
+    };
+    let start = start_after
+        .as_ref()
+        .map(|(staked_at, address)| Bound::exclusive((*staked_at, address)));
+    let now = env.block.time.seconds();
+    let matured_by = now.saturating_sub(config.min_staking_seconds());
+    let mut eligible_stakers: Vec<(String, Staker)> = Vec::new();
+    
+    for item in stakers()
+        .idx
+        .staked_at
+        .range(deps.storage, start, None, Order::Ascending)
+    {
+        if eligible_stakers.len() == limit {
+            break;
+        }
+        let (address, staker) = item?;
+        // Later positions are too new for any of their NFTs to have matured
+        if staker.staked_at.seconds() > matured_by {
+            break;
+        }
+        // At least one NFT has to have been staked for the minimum period
+        if matured_tickets(deps, &config, &address, &staker, env.block.time)? > 0 {
+            eligible_stakers.push((address.to_string(), staker));
+        }
+    }
+    
+    Ok(eligible_stakers)
+}
+
+// Add helper function to get total staked NFTs for DAO DAO
+pub fn get_total_staked_nfts(deps: Deps) -> StdResult<u64> {
+    let state = STATE.load(deps.storage)?;
+    Ok(state.total_staked)
+}
+
+// Add helper function to get staker weight for DAO DAO
+pub fn get_staker_weight(deps: Deps, address: String) -> StdResult<u64> {
+    let address = deps.api.addr_validate(&address)?;
+    let staker = stakers().may_load(deps.storage, &address)?;
+    Ok(staker.map_or(0, |s| s.weight()))
+}
+
+// Power at the start of `height`, so stake changes count from the following block
+pub(crate) fn query_voting_power_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<VotingPowerAtHeightResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+    let power = match VOTING_POWER.may_load_at_height(deps.storage, &address, height)? {
+        Some(power) => power,
+        // Never checkpointed: the position hasn't changed since checkpointing began
+        None if VOTING_POWER.may_load(deps.storage, &address)?.is_none() => {
+            get_staker_weight(deps, address.to_string())?
+        }
+        None => 0,
+    };
+
+    Ok(VotingPowerAtHeightResponse {
+        power: Uint128::from(power),
+        height,
+    })
+}
+
+// Heights from before checkpointing began read the current total
+pub(crate) fn query_total_power_at_height(
+    deps: Deps,
+    env: Env,
+    height: Option<u64>,
+) -> StdResult<TotalPowerAtHeightResponse> {
+    let height = height.unwrap_or(env.block.height);
+    let power = match TOTAL_POWER.may_load_at_height(deps.storage, height)? {
+        Some(power) => power,
+        None => STATE.load(deps.storage)?.total_weight(),
+    };
+
+    Ok(TotalPowerAtHeightResponse {
+        power: Uint128::from(power),
+        height,
+    })
+}
+
+pub(crate) fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let info = cw2::get_contract_version(deps.storage)?;
+    Ok(InfoResponse { info })
+}
+
+pub(crate) fn query_dao(deps: Deps) -> StdResult<Addr> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(config.dao.unwrap_or(config.admin))
+}
+
+pub(crate) fn query_state(deps: Deps) -> StdResult<State> {
+    let state = STATE.load(deps.storage)?;
+    Ok(state)
+}
+
+pub(crate) fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        min_staking_seconds: config.min_staking_seconds(),
+        prize_places: config.prize_places(),
+        config,
+    })
+}
+
+pub(crate) fn query_pending_min_staking_period(
+    deps: Deps,
+) -> StdResult<Option<PendingMinStakingPeriod>> {
+    PENDING_MIN_STAKING_PERIOD.may_load(deps.storage)
+}
+
+pub(crate) fn query_pending_protocol_fee(deps: Deps) -> StdResult<Option<PendingProtocolFee>> {
+    PENDING_PROTOCOL_FEE.may_load(deps.storage)
+}
+
+pub(crate) fn query_jackpot(deps: Deps) -> StdResult<JackpotResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    Ok(JackpotResponse {
+        jackpot: state.jackpot,
+        settings: config.jackpot,
+        last_hit: state.last_jackpot,
+    })
+}
+
+pub(crate) fn query_staking_rewards(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<StakingRewardsResponse> {
+    let owner = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let (stream, pending) = pending_staking_rewards(deps.storage, &env, &owner)?;
+    Ok(StakingRewardsResponse {
+        pending,
+        emission_per_second: config.emission_per_second,
+        reserve: stream.reserve,
+    })
+}
+
+pub(crate) fn query_staker_settings(deps: Deps, address: String) -> StdResult<StakerSettings> {
+    let owner = deps.api.addr_validate(&address)?;
+    Ok(STAKER_SETTINGS.may_load(deps.storage, &owner)?.unwrap_or_default())
+}
+
+pub(crate) fn query_merkle_distribution(deps: Deps) -> StdResult<Option<MerkleDistribution>> {
+    MERKLE_DISTRIBUTION.may_load(deps.storage)
+}
+
+pub(crate) fn query_merkle_claimed(deps: Deps, address: String) -> StdResult<bool> {
+    let claimer = deps.api.addr_validate(&address)?;
+    let Some(distribution) = MERKLE_DISTRIBUTION.may_load(deps.storage)? else {
+        return Ok(false);
+    };
+    Ok(MERKLE_CLAIMED.has(deps.storage, (distribution.id, &claimer)))
+}
+
+pub(crate) fn query_staker(deps: Deps, address: String) -> StdResult<Option<Staker>> {
+    let address = deps.api.addr_validate(&address)?;
+    let staker = stakers().may_load(deps.storage, &address)?;
+    Ok(staker)
+}
+
+pub(crate) fn query_list_stakers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Staker)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    stakers()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(address, staker)| (address.to_string(), staker)))
+        .collect()
+}
+
+pub(crate) fn query_stake_cap(deps: Deps, address: String) -> StdResult<StakeCapResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let max = CONFIG.load(deps.storage)?.max_nfts_per_staker;
+    let nft_count = stakers()
+        .may_load(deps.storage, &address)?
+        .map_or(0, |staker| staker.nft_count);
+
+    Ok(StakeCapResponse {
+        max_nfts_per_staker: max,
+        nft_count,
+        remaining: (max > 0).then(|| max.saturating_sub(nft_count)),
+    })
+}
+
+pub(crate) fn query_draw_exclusions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<String>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    DRAW_EXCLUSIONS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|address| address.to_string()))
+        .collect()
+}
+
+pub(crate) fn query_stakers_by_weight(
+    deps: Deps,
+    descending: bool,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Staker)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order = match descending {
+        true => Order::Descending,
+        false => Order::Ascending,
+    };
+
+    stakers()
+        .idx
+        .weight
+        .range(deps.storage, None, None, order)
+        .take(limit)
+        .map(|item| item.map(|(address, staker)| (address.to_string(), staker)))
+        .collect()
+}
+
+pub(crate) fn query_staked_tokens(
+    deps: Deps,
+    env: Env,
+    address: String,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<StakedToken>> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|(collection, token_id)| deps.api.addr_validate(&collection).map(|c| (c, token_id)))
+        .transpose()?;
+    let start = start_after
+        .as_ref()
+        .map(|(collection, token_id)| Bound::exclusive((collection, token_id.as_str())));
+
+    STAKED_NFTS
+        .sub_prefix(&address)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let ((collection, token_id), staked) = item?;
+            Ok(StakedToken {
+                collection,
+                token_id,
+                staked_at: staked.staked_at,
+                weight: staked.weight,
+                eligible: staked.staked_at.plus_seconds(config.min_staking_seconds())
+                    <= env.block.time
+                    && staked.lock.iter().all(|lock| lock.unlock_at <= env.block.time),
+                lock: staked.lock,
+            })
+        })
+        .collect()
+}
+
+// Registered collections in address order
+pub(crate) fn query_collections(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<CollectionInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    COLLECTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, collection) = item?;
+            Ok(CollectionInfo {
+                address,
+                weight: collection.weight,
+                staked: collection.staked,
+                trait_weights: collection.trait_weights,
+                allowlisted: collection.allowlisted,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn query_is_stakeable(deps: Deps, collection: String, token_id: String) -> StdResult<bool> {
+    let address = deps.api.addr_validate(&collection)?;
+    match COLLECTIONS.may_load(deps.storage, &address)? {
+        Some(registered) => token_allowed(deps.storage, &address, &registered, &token_id),
+        None => Ok(false),
+    }
+}
+
+// Custody tokens of `address` per collection, in collection order. Positions staked
+// without custody (Stake without token_ids) aren't broken down.
+pub(crate) fn query_staker_collections(
+    deps: Deps,
+    address: String,
+) -> StdResult<Vec<StakerCollection>> {
+    let address = deps.api.addr_validate(&address)?;
+    let mut breakdown: Vec<StakerCollection> = vec![];
+    for item in STAKED_NFTS
+        .sub_prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let ((collection, _), staked) = item?;
+        match breakdown.last_mut() {
+            Some(entry) if entry.collection == collection => {
+                entry.staked += 1;
+                entry.weight += staked.weight;
+            }
+            _ => breakdown.push(StakerCollection {
+                collection,
+                staked: 1,
+                weight: staked.weight,
+            }),
+        }
+    }
+    // Storage length-prefixes the collection in the key, so sort by address here
+    breakdown.sort_by(|a, b| a.collection.cmp(&b.collection));
+    Ok(breakdown)
+}
+
+pub(crate) fn query_unbonding_claims(deps: Deps, address: String) -> StdResult<Vec<UnbondingNft>> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(UNBONDING.may_load(deps.storage, &address)?.unwrap_or_default())
+}
+
+pub(crate) fn query_simulate_draw(
+    deps: Deps,
+    env: Env,
+    entropy_override: Option<Binary>,
+) -> StdResult<SimulateDrawResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pool = collect_eligible_stakers(deps, &env, &config)?;
+    let entropy = match entropy_override {
+        Some(entropy) => entropy.to_vec(),
+        None => {
+            let err = ContractError::NoEntropyAvailable {};
+            return Err(StdError::generic_err(err.to_string()));
+        }
+    };
+    // The same streaming pick as a draw's first place, not one over `pool`
+    let totals = pool_totals(deps, &env, &config)?;
+    let would_win = pick_winner(deps, &env, &config, &totals, &entropy, &[])?
+        .ok_or_else(|| StdError::generic_err(ContractError::NoEligibleStakers {}.to_string()))?;
+
+    Ok(SimulateDrawResponse {
+        would_win,
+        eligible_staker_count: pool.len() as u64,
+        total_weight: pool.iter().map(|(_, weight)| weight).sum(),
+        staker_weights: pool,
+    })
+}
+
+pub(crate) fn query_prize_leaderboard(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let limit = limit.map_or(LEADERBOARD_SIZE, |limit| limit as usize);
+    let mut entries = PRIZE_LEADERBOARD
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .entries;
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+pub(crate) fn query_burn_leaderboard(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let limit = limit.map_or(LEADERBOARD_SIZE, |limit| limit as usize);
+    let mut entries = BURN_LEADERBOARD
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .entries;
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+pub(crate) fn query_leaderboard(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<Vec<LeaderboardEntry>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    stakers()
+        .idx
+        .weight
+        .range(deps.storage, None, None, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            let (address, staker) = item?;
+            let wins = WIN_COUNTS.may_load(deps.storage, &address)?.unwrap_or_default();
+            Ok(LeaderboardEntry {
+                address: address.to_string(),
+                nft_count: staker.nft_count,
+                weight: staker.weight(),
+                wins,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn query_lifetime_winnings(deps: Deps, address: String) -> StdResult<Uint128> {
+    let address = deps.api.addr_validate(&address)?;
+    let total = TOTAL_WINNINGS.may_load(deps.storage, address.to_string())?;
+    Ok(total.unwrap_or_default())
+}
+
+pub(crate) fn query_staker_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, StakeEvent)>> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    STAKER_HISTORY
+        .prefix(address.to_string())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+pub(crate) fn query_interval_votes(deps: Deps) -> StdResult<IntervalVotesResponse> {
+    let state = STATE.load(deps.storage)?;
+    let histogram = interval_vote_histogram(deps.storage)?;
+    Ok(IntervalVotesResponse {
+        total_weight: histogram.iter().map(|(_, weight)| weight).sum(),
+        histogram,
+        next_draw_interval_seconds: state.next_draw_interval_seconds,
+    })
+}
+
+pub(crate) fn query_draw_snapshot(deps: Deps, round_id: u64, address: String) -> StdResult<Option<u64>> {
+    let address = deps.api.addr_validate(&address)?;
+    DRAW_SNAPSHOTS.may_load(deps.storage, (round_id, address.to_string()))
+}
+
+pub(crate) fn query_full_draw_snapshot(
+    deps: Deps,
+    round_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, u64)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    DRAW_SNAPSHOTS
+        .prefix(round_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+pub(crate) fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
+    let version = cw2::get_contract_version(deps.storage)?;
+    Ok(ContractInfoResponse {
+        name: version.contract,
+        version: version.version,
+    })
+}
+
+pub(crate) fn query_hooks(deps: Deps) -> StdResult<Vec<Addr>> {
+    Ok(HOOKS.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub(crate) fn query_paused(deps: Deps) -> StdResult<Vec<PausableAction>> {
+    Ok(PAUSED.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub(crate) fn query_draw_task(deps: Deps) -> StdResult<Option<String>> {
+    DRAW_TASK.may_load(deps.storage)
+}
+
+pub(crate) fn query_badge_contract(deps: Deps) -> StdResult<Option<Addr>> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(config.badge_nft_contract)
+}
+
+pub(crate) fn query_prove_eligibility(deps: Deps, env: Env, address: String) -> StdResult<EligibilityProof> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    // Counted against the full pool so the weight reflects the win probability cap
+    let effective_weight = pool_weight(deps, &env, &config, &address)?;
+    let is_eligible = effective_weight.is_some();
+    let effective_weight = effective_weight.unwrap_or(0);
+
+    // Positions staked without custody have no token ids to list
+    let (staked_nfts, individual_token_timestamps) = STAKED_NFTS
+        .sub_prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .map(|((_, token_id), staked)| (token_id, staked.staked_at))
+        .unzip();
+
+    let mut hasher = Sha256::new();
+    hasher.update(address.as_bytes());
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(effective_weight.to_be_bytes());
+
+    Ok(EligibilityProof {
+        address: address.to_string(),
+        is_eligible,
+        staked_nfts,
+        individual_token_timestamps,
+        effective_weight,
+        contract_address: env.contract.address.to_string(),
+        block_height: env.block.height,
+        block_time: env.block.time,
+        proof_hash: Binary::from(hasher.finalize().to_vec()),
+    })
+}
+
+pub(crate) fn query_ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?;
+    Ok(OwnershipResponse {
+        admin: config.admin.to_string(),
+        pending_admin: pending_admin.map(|address| address.to_string()),
+    })
+}
+
+pub(crate) fn query_tickets(deps: Deps, env: Env, address: String) -> StdResult<TicketsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let nft_count = stakers()
+        .may_load(deps.storage, &address)?
+        .map_or(0, |staker| staker.nft_count);
+    let tickets = pool_weight(deps, &env, &config, &address)?.unwrap_or(0);
+
+    Ok(TicketsResponse {
+        address: address.to_string(),
+        nft_count,
+        tickets,
+    })
+}
+
+pub(crate) fn query_dashboard(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<DashboardResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let staker = stakers().may_load(deps.storage, &address)?;
+    let eligible_in_seconds = match &staker {
+        Some(staker) => {
+            let matures_at = first_maturity(deps, &config, &address, staker)?;
+            Some(matures_at.seconds().saturating_sub(env.block.time.seconds()))
+        }
+        None => None,
+    };
+    let cooldown = draw_cooldown_seconds(&config, &state);
+    let next_draw_time = state.last_draw_time.map(|time| time.plus_seconds(cooldown));
+
+    Ok(DashboardResponse {
+        staked_tokens: query_staked_tokens(deps, env.clone(), address.to_string(), None, None)?,
+        tickets: pool_weight(deps, &env, &config, &address)?.unwrap_or(0),
+        pending_prize: PENDING_PRIZES.may_load(deps.storage, &address)?,
+        unbonding: UNBONDING.may_load(deps.storage, &address)?.unwrap_or_default(),
+        staker,
+        eligible_in_seconds,
+        next_draw_time,
+    })
+}
+
+pub(crate) fn query_pending_rewards(
+    deps: Deps,
+    address: String,
+) -> StdResult<PendingRewardsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let prize = PENDING_PRIZES.may_load(deps.storage, &address)?;
+    let mut total = prize.as_ref().map_or(Uint128::zero(), |prize| prize.amount);
+    let mut tier_prizes = vec![];
+    for tier in config.prize_tiers {
+        if let Some(amount) = TIER_PRIZES.may_load(deps.storage, (&tier.name, &address))? {
+            total = total.checked_add(amount)?;
+            tier_prizes.push((tier.name, amount));
+        }
+    }
+
+    Ok(PendingRewardsResponse {
+        prize,
+        tier_prizes,
+        total,
+    })
+}
+
+pub(crate) fn query_eligible_in(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<EligibleInResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let seconds = match stakers().may_load(deps.storage, &address)? {
+        Some(staker) => {
+            let matures_at = first_maturity(deps, &config, &address, &staker)?;
+            Some(matures_at.seconds().saturating_sub(now))
+        }
+        None => None,
+    };
+    let tokens = STAKED_NFTS
+        .sub_prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let ((collection, token_id), staked) = item?;
+            let matures_at = staked.staked_at.plus_seconds(config.min_staking_seconds());
+            Ok(TokenEligibleIn {
+                collection,
+                token_id,
+                seconds: matures_at.seconds().saturating_sub(now),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(EligibleInResponse { seconds, tokens })
+}
+
+pub(crate) fn query_eligible_count(deps: Deps, env: Env) -> StdResult<EligibleCountResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let totals = pool_totals(deps, &env, &config)?;
+
+    Ok(EligibleCountResponse {
+        eligible_stakers: totals.entries,
+        total_tickets: pool_tickets(deps, &env, &config, &totals)?,
+    })
+}
+
+pub(crate) fn query_odds(deps: Deps, env: Env, address: String) -> StdResult<OddsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let totals = pool_totals(deps, &env, &config)?;
+    let tickets = pool_weight(deps, &env, &config, &address)?.unwrap_or(0);
+    let total_tickets = pool_tickets(deps, &env, &config, &totals)?;
+    let win_probability = if total_tickets == 0 {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(tickets, total_tickets)
+    };
+
+    Ok(OddsResponse {
+        tickets,
+        total_tickets,
+        win_probability,
+    })
+}
+
+pub(crate) fn query_next_draw(deps: Deps, env: Env) -> StdResult<NextDrawResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let cooldown = draw_cooldown_seconds(&config, &state);
+    let next_draw_time = state.last_draw_time.map(|time| time.plus_seconds(cooldown));
+    let seconds_remaining = next_draw_time.map_or(0, |time| {
+        time.seconds().saturating_sub(env.block.time.seconds())
+    });
+
+    Ok(NextDrawResponse {
+        next_draw_time,
+        seconds_remaining,
+        draw_in_progress: state.pending_draw_job.is_some()
+            || state.pending_drand_round.is_some()
+            || state.pending_commit_reveal.is_some(),
+        pot_threshold_met: !config.target_pot_size.is_zero()
+            && state.current_pot >= config.target_pot_size,
+        current_pot: state.current_pot,
+        target_pot_size: config.target_pot_size,
+    })
+}
+
+pub(crate) fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(StatsResponse {
+        rounds_drawn: state.draw_count,
+        total_prizes_paid: state.total_prizes_paid,
+        current_pot: state.current_pot,
+        unique_stakers: state.unique_stakers,
+        nfts_staked: state.total_staked,
+        nfts_burned: state.total_burns,
+    })
+}
+
+pub(crate) fn query_tier_pots(deps: Deps) -> StdResult<Vec<(String, Uint128)>> {
+    let config = CONFIG.load(deps.storage)?;
+    config
+        .prize_tiers
+        .into_iter()
+        .map(|tier| {
+            let pot = TIER_POTS.may_load(deps.storage, &tier.name)?.unwrap_or_default();
+            Ok((tier.name, pot))
+        })
+        .collect()
+}
+
+pub(crate) fn query_round(deps: Deps, round_id: u64) -> StdResult<Option<Round>> {
+    ROUNDS.may_load(deps.storage, round_id)
+}
+
+pub(crate) fn query_list_winners(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<WinnerRecord>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive);
+
+    ROUNDS
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            let (round_id, round) = item?;
+            let (winner, prize) = round.winners.into_iter().next().unwrap_or_default();
+            Ok(WinnerRecord {
+                round_id,
+                winner,
+                prize,
+                timestamp: round.drawn_at,
+            })
+        })
+        .collect()
+}
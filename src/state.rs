@@ -0,0 +1,873 @@
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, HexBinary, Timestamp, Uint128};
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_schema::schemars;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, Map, MultiIndex, SnapshotItem, SnapshotMap, Strategy,
+};
+use cw_utils::Duration;
+
+pub(crate) const SECONDS_IN_DAY: u64 = 86400; 
+pub(crate) const MIN_STAKING_PERIOD: Duration = Duration::Time(7 * SECONDS_IN_DAY);
+// Notice stakers get before a new minimum staking period applies
+pub(crate) const MIN_STAKING_PERIOD_TIMELOCK: u64 = 3 * SECONDS_IN_DAY;
+// Delay before a proposed protocol fee takes effect
+pub(crate) const PROTOCOL_FEE_TIMELOCK: u64 = 3 * SECONDS_IN_DAY;
+
+pub(crate) const DEFAULT_MIN_DRAW_INTERVAL: u64 = 3600;
+pub(crate) const DEFAULT_MAX_DRAW_INTERVAL: u64 = 30 * SECONDS_IN_DAY;
+pub(crate) const DEFAULT_ACCELERATED_COOLDOWN: u64 = 3600;
+
+fn default_min_draw_interval() -> u64 {
+    DEFAULT_MIN_DRAW_INTERVAL
+}
+
+fn default_max_draw_interval() -> u64 {
+    DEFAULT_MAX_DRAW_INTERVAL
+}
+
+fn default_draw_interval() -> u64 {
+    SECONDS_IN_DAY
+}
+
+fn default_min_staking_period() -> Duration {
+    MIN_STAKING_PERIOD
+}
+
+fn default_accelerated_cooldown() -> u64 {
+    DEFAULT_ACCELERATED_COOLDOWN
+}
+
+// Represents a staker's information
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Staker {
+    pub staked_at: Timestamp,
+    pub nft_count: u64,
+    // Tickets beyond one per NFT, from collections weighted above 1
+    #[serde(default)]
+    pub bonus_weight: u64,
+    // Permanent tickets from NFTs burned with BurnForTickets. A position with burned
+    // tickets stays open after its last NFT is unstaked.
+    #[serde(default)]
+    pub burned_weight: u64,
+    // Permanent tickets bought with prizes while auto_compound was on. They keep an
+    // emptied position open like burned tickets.
+    #[serde(default)]
+    pub compounded_weight: u64,
+}
+
+impl Staker {
+    // Tickets before duration bonus and decay, also the staker's voting power
+    pub fn weight(&self) -> u64 {
+        self.nft_count + self.bonus_weight + self.permanent_weight()
+    }
+
+    // Tickets that don't belong to any NFT
+    pub fn permanent_weight(&self) -> u64 {
+        self.burned_weight + self.compounded_weight
+    }
+}
+
+pub(crate) struct StakerIndexes<'a> {
+    // Positions by staked_at seconds, so a scan for matured ones can stop at the first
+    // position that is still too new
+    pub staked_at: MultiIndex<'a, u64, Staker, &'a Addr>,
+    // Positions by Staker::weight, for listing the largest stakers
+    pub weight: MultiIndex<'a, u64, Staker, &'a Addr>,
+}
+
+impl IndexList<Staker> for StakerIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Staker>> + '_> {
+        let v: Vec<&dyn Index<Staker>> = vec![&self.staked_at, &self.weight];
+        Box::new(v.into_iter())
+    }
+}
+
+// Staker positions, also indexed by when they were opened and by weight
+pub(crate) fn stakers<'a>() -> IndexedMap<'a, &'a Addr, Staker, StakerIndexes<'a>> {
+    let indexes = StakerIndexes {
+        staked_at: MultiIndex::new(
+            |_pk, staker| staker.staked_at.seconds(),
+            "staker_positions",
+            "staker_positions__staked_at",
+        ),
+        weight: MultiIndex::new(
+            |_pk, staker| staker.weight(),
+            "staker_positions",
+            "staker_positions__weight",
+        ),
+    };
+    IndexedMap::new("staker_positions", indexes)
+}
+
+// Staker information keyed by unvalidated address strings, as stored before v2
+pub(crate) const LEGACY_STAKERS: Map<String, Staker> = Map::new("stakers");
+
+// A token held in custody, with the tickets it was staked for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedNft {
+    pub staked_at: Timestamp,
+    pub weight: u64,
+    // Lock picked when staking; None leaves only the minimum period to wait out
+    #[serde(default)]
+    pub lock: Option<TokenLock>,
+    // Stake receipt minted for this token. Tokens staked before receipts were named
+    // after their token have none and hold a position-numbered receipt.
+    #[serde(default)]
+    pub receipt_id: Option<String>,
+}
+
+impl StakedNft {
+    // Tickets the token counts for without its lock multiplier
+    pub fn base_weight(&self) -> u64 {
+        self.lock.as_ref().map_or(self.weight, |lock| lock.base_weight)
+    }
+}
+
+// A custody token held to unlock_at for its lock tier's multiplier, which is already
+// part of StakedNft::weight
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenLock {
+    pub duration_seconds: u64,
+    pub unlock_at: Timestamp,
+    // Tickets of the token before the multiplier
+    pub base_weight: u64,
+}
+
+// Tokens held in custody, keyed by (owner, collection, token_id)
+pub(crate) const STAKED_NFTS: Map<(&Addr, &Addr, &str), StakedNft> = Map::new("staked_tokens");
+
+// Custody tokens keyed by (owner, token_id), as stored when only one collection was
+// accepted
+pub(crate) const LEGACY_STAKED_NFTS: Map<(&Addr, &str), Timestamp> = Map::new("staked_nfts");
+
+// A collection accepted for staking. Each NFT from it counts `weight` tickets, or the
+// highest matching trait weight; `staked` is the number of its tokens in custody.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Collection {
+    pub weight: u64,
+    pub staked: u64,
+    #[serde(default)]
+    pub trait_weights: Vec<TraitWeight>,
+    // Entries in TOKEN_ALLOWLIST. While there are any, only those tokens can be staked.
+    #[serde(default)]
+    pub allowlisted: u64,
+}
+
+// Tickets for an NFT whose on-chain metadata has the attribute trait_type = value,
+// e.g. { trait_type: "rarity", value: "Legendary", weight: 5 }
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitWeight {
+    pub trait_type: String,
+    pub value: String,
+    pub weight: u64,
+}
+
+pub(crate) const COLLECTIONS: Map<&Addr, Collection> = Map::new("collections");
+
+// Token ids that may be staked, keyed by (collection, token_id)
+pub(crate) const TOKEN_ALLOWLIST: Map<(&Addr, &str), bool> = Map::new("token_allowlist");
+
+// An unstaked token that no longer counts for draws or voting and is returned by
+// ClaimUnstaked from release_at on
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingNft {
+    pub collection: Addr,
+    pub token_id: String,
+    pub release_at: Timestamp,
+}
+
+pub(crate) const UNBONDING: Map<&Addr, Vec<UnbondingNft>> = Map::new("unbonding");
+
+// Unbonding entries from before the collection was recorded. deny_unknown_fields keeps
+// current entries from parsing as this.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UnbondingNftV1 {
+    pub token_id: String,
+    pub release_at: Timestamp,
+}
+
+pub(crate) const LEGACY_UNBONDING: Map<&Addr, Vec<UnbondingNftV1>> = Map::new("unbonding");
+
+// Addresses with a position, kept alongside the positions before membership was read
+// from the positions themselves. Only migration touches it, to clear it out.
+pub(crate) const LEGACY_STAKER_SET: Map<String, bool> = Map::new("staker_set");
+
+// State structure
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub total_staked: u64,
+    pub current_pot: Uint128,
+    pub last_winner: Option<String>,
+    pub staker_count: u64,
+    // Sequence number for the next STAKER_HISTORY entry
+    #[serde(default)]
+    pub staker_event_count: u64,
+    // Weighted median of the interval votes, recomputed after every draw
+    #[serde(default = "default_draw_interval")]
+    pub next_draw_interval_seconds: u64,
+    // Number of completed draws, also the round_id of the latest draw
+    #[serde(default)]
+    pub draw_count: u64,
+    // Block time of the latest draw, None before the first one
+    #[serde(default)]
+    pub last_draw_time: Option<Timestamp>,
+    // Block time of the latest admin-gated execute, the clock for abandonment exits
+    #[serde(default)]
+    pub last_admin_action_time: Timestamp,
+    // Native coins in the pot, one entry per denom sorted by denom. current_pot is the
+    // reward token side.
+    #[serde(default)]
+    pub native_pot: Vec<Coin>,
+    // Nois job of a draw waiting for its randomness callback
+    #[serde(default)]
+    pub pending_draw_job: Option<String>,
+    // drand round whose beacon will seed the pending draw
+    #[serde(default)]
+    pub pending_drand_round: Option<u64>,
+    // Commit-reveal round collecting entropy for the pending draw
+    #[serde(default)]
+    pub pending_commit_reveal: Option<CommitRevealRound>,
+    // Sum of Staker::bonus_weight
+    #[serde(default)]
+    pub total_bonus_weight: u64,
+    // Sum of Staker::burned_weight
+    #[serde(default)]
+    pub total_burned_weight: u64,
+    // NFTs burned with BurnForTickets
+    #[serde(default)]
+    pub total_burns: u64,
+    // Reward token paid out by claims, tier prizes included
+    #[serde(default)]
+    pub total_prizes_paid: Uint128,
+    // Addresses that have ever opened a position, the size of KNOWN_STAKERS
+    #[serde(default)]
+    pub unique_stakers: u64,
+    // Reward tokens held for the progressive jackpot, outside current_pot
+    #[serde(default)]
+    pub jackpot: Uint128,
+    #[serde(default)]
+    pub last_jackpot: Option<JackpotHit>,
+    // Sum of Staker::compounded_weight
+    #[serde(default)]
+    pub total_compounded_weight: u64,
+}
+
+impl State {
+    // Total tickets before duration bonus and decay, also the total voting power
+    pub fn total_weight(&self) -> u64 {
+        self.total_staked
+            + self.total_bonus_weight
+            + self.total_burned_weight
+            + self.total_compounded_weight
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommitRevealRound {
+    pub commit_deadline: Timestamp,
+    pub reveal_deadline: Timestamp,
+}
+
+// State layout of v1 deployments, which kept every staker address in the Item
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateV1 {
+    pub total_staked: u64,
+    pub current_pot: Uint128,
+    pub last_winner: Option<String>,
+    pub stakers: HashSet<String>,
+}
+
+// Config structure for contract settings
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub admin: Addr,
+    // Collection registered with weight 1 at instantiation. Further collections are
+    // added with AddCollection.
+    pub nft_contract: Addr,
+    pub reward_token: Addr,
+    // Stakers who haven't staked, unstaked or checked in within this window sit out draws
+    pub activity_timeout_seconds: Option<u64>,
+    // Share of draw weight lost per week without activity, 0 disables decay
+    #[serde(default)]
+    pub weight_decay_rate_bps_per_week: u16,
+    // Bounds for draw interval votes and the admin override
+    #[serde(default = "default_min_draw_interval")]
+    pub min_draw_interval_seconds: u64,
+    #[serde(default = "default_max_draw_interval")]
+    pub max_draw_interval_seconds: u64,
+    // cw721 contract that mints a soulbound badge to every draw winner
+    pub badge_nft_contract: Option<Addr>,
+    // Once the pot reaches target_pot_size the next draw only waits
+    // accelerated_cooldown_seconds instead of the voted interval. Zero disables this.
+    #[serde(default)]
+    pub target_pot_size: Uint128,
+    #[serde(default = "default_accelerated_cooldown")]
+    pub accelerated_cooldown_seconds: u64,
+    // Silence from the admin for longer than this lets stakers exit with their share of
+    // the pot. Zero disables abandonment exits.
+    #[serde(default)]
+    pub abandonment_timeout_seconds: u64,
+    // Highest draw odds any one staker can have, in basis points. 0 disables the cap.
+    #[serde(default)]
+    pub max_win_probability_bps: u16,
+    // cw721 contract that mints a soulbound receipt per staked NFT and burns it on unstake
+    #[serde(default)]
+    pub stake_receipt_contract: Option<Addr>,
+    // How long an NFT has to be staked before it can win or be unstaked. Always a time
+    // duration, since stakes are timestamped.
+    #[serde(default = "default_min_staking_period")]
+    pub min_staking_period: Duration,
+    // Bonus tickets for long-term holders, None keeps one ticket per NFT
+    #[serde(default)]
+    pub duration_weighting: Option<DurationWeighting>,
+    // Nois proxy that supplies draw randomness. Draws need it, a drand network or
+    // commit-reveal; without any of them they are refused.
+    #[serde(default)]
+    pub nois_proxy: Option<Addr>,
+    // drand network whose verified beacons seed draws
+    #[serde(default)]
+    pub drand: Option<DrandConfig>,
+    // Contributors whose revealed secrets seed draws
+    #[serde(default)]
+    pub commit_reveal: Option<CommitRevealConfig>,
+    // Time a winner has to claim before SweepExpiredPrizes can return the prize to the
+    // pot. Zero lets prizes wait forever.
+    #[serde(default)]
+    pub claim_window_seconds: u64,
+    // Share of the pot per winner in basis points, first place first, summing to 10000.
+    // Empty draws a single winner who takes the whole pot.
+    #[serde(default)]
+    pub prize_split_bps: Vec<u16>,
+    // Extra pools drawn alongside the main pot, set with SetPrizeTiers
+    #[serde(default)]
+    pub prize_tiers: Vec<PrizeTier>,
+    // Instantiator, reported by the Dao voting module query. None for contracts
+    // instantiated before it was recorded, which report the admin instead.
+    #[serde(default)]
+    pub dao: Option<Addr>,
+    // Time an unstaked token stays in custody before ClaimUnstaked returns it. Zero
+    // returns tokens with the unstake.
+    #[serde(default)]
+    pub unbonding_seconds: u64,
+    // Reward tokens paid into the pot to unstake before the minimum period. Zero disables
+    // early unstaking.
+    #[serde(default)]
+    pub early_unstake_penalty: Uint128,
+    // Cut of the pot, in basis points, paid to anyone other than the admin who triggers a
+    // draw. 0 keeps DrawWinner admin only.
+    #[serde(default)]
+    pub keeper_reward_bps: u16,
+    // CronCat deployment that runs the scheduled DrawWinner task
+    #[serde(default)]
+    pub croncat: Option<CronCatConfig>,
+    // Permanent tickets per ticket of a burned NFT. 0 disables BurnForTickets.
+    #[serde(default)]
+    pub burn_multiplier: u64,
+    // Share of the pot, in basis points, paid to the owner of the custody token each draw
+    // burns at the stake. 0 turns sacrifices off.
+    #[serde(default)]
+    pub sacrifice_bps: u16,
+    // Accept Stake without token_ids, which counts an NFT nobody has checked the sender
+    // owns. Off by default; stakes then have to go through custody.
+    #[serde(default)]
+    pub allow_wallet_stakes: bool,
+    // Locks stakers can pick for custody tokens, shortest first. Empty turns locking off.
+    #[serde(default)]
+    pub lock_tiers: Vec<LockTier>,
+    // Rounds after a win that the winner sits out of draws, still staked. 0 lets winners
+    // win again in the next round.
+    #[serde(default)]
+    pub winner_cooldown_rounds: u32,
+    // Most NFTs one position can hold, wallet and custody alike. 0 leaves it uncapped.
+    #[serde(default)]
+    pub max_nfts_per_staker: u64,
+    // Most candidates one draw takes, oldest positions first. The rest keep their voting
+    // power and enter once places free up. 0 lets every candidate in.
+    #[serde(default)]
+    pub max_entries_per_round: u64,
+    // Native coins paid into the pot per NFT staked with Stake or StakeBatch. SendNft
+    // can't attach funds, so ReceiveNft stakes are refused while a fee is set.
+    #[serde(default)]
+    pub stake_fee: Option<Coin>,
+    // Share of each drawn reward token pot, in basis points, sent to treasury before the
+    // split. Changed with ProposeProtocolFee after PROTOCOL_FEE_TIMELOCK.
+    #[serde(default)]
+    pub protocol_fee_bps: u16,
+    #[serde(default)]
+    pub treasury: Option<Addr>,
+    // How each drawn pot is split between the winners, the next round and a burn
+    #[serde(default)]
+    pub pot_split: PotSplit,
+    // Progressive jackpot fed from every drawn pot. None leaves it off.
+    #[serde(default)]
+    pub jackpot: Option<JackpotConfig>,
+    // Reward tokens streamed to stakers per second, shared by voting power, on top of
+    // the draws. Paid from what ReceiveMsg::FundRewards has put in RewardStream::reserve.
+    #[serde(default)]
+    pub emission_per_second: Uint128,
+    // Reward tokens one compounded ticket costs. A prize won with auto_compound on buys
+    // as many tickets as it covers, the tokens go back into the pot and the rest is
+    // claimed as usual. 0 turns auto-compounding off.
+    #[serde(default)]
+    pub compound_rate: Uint128,
+    // Send main pot prizes to the winners as part of the draw instead of holding them
+    // for ClaimReward. A transfer that fails leaves the prize to be claimed as usual.
+    #[serde(default)]
+    pub push_payouts: bool,
+}
+
+// A separately funded prize pool, e.g. a runner-up or participation pool, shared evenly
+// between its winners
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PrizeTier {
+    pub name: String,
+    pub winners: u32,
+}
+
+// A chained drand network such as the League of Entropy mainnet. Round r is published
+// at genesis_time + (r - 1) * period_seconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DrandConfig {
+    // Group public key, a compressed G1 point
+    pub pubkey: HexBinary,
+    pub genesis_time: u64,
+    pub period_seconds: u64,
+}
+
+// DrawWinner opens a commit window of commit_window_seconds, followed by a reveal window
+// of reveal_window_seconds. The draw goes ahead once at least min_reveals secrets are in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommitRevealConfig {
+    pub contributors: Vec<Addr>,
+    pub commit_window_seconds: u64,
+    pub reveal_window_seconds: u64,
+    pub min_reveals: u32,
+}
+
+// CreateTask goes to the tasks contract; task actions are executed by the manager, so
+// DrawWinner from the manager is a scheduled draw.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CronCatConfig {
+    pub tasks: Addr,
+    pub manager: Addr,
+}
+
+// Tokens locked for duration_seconds count multiplier_bps / 10000 times their tickets,
+// for the draw and for voting power. { duration_seconds: 2592000, multiplier_bps: 15000 }
+// is 1.5x for a 30 day lock.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockTier {
+    pub duration_seconds: u64,
+    pub multiplier_bps: u32,
+}
+
+// Shares of a drawn reward token pot, in basis points, carried into the next round and
+// burned. The winners get the rest, so the default of zeros pays them everything.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PotSplit {
+    pub rollover_bps: u16,
+    pub burn_bps: u16,
+}
+
+// share_bps of each drawn reward token pot, after the protocol fee and compensation, goes
+// into the jackpot. Each draw has a 1 in `odds` chance of paying the whole jackpot to
+// the first place winner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotConfig {
+    pub share_bps: u16,
+    pub odds: u32,
+}
+
+// The last time the jackpot was won
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotHit {
+    pub round_id: u64,
+    pub winner: String,
+    pub amount: Uint128,
+    pub triggered_at: Timestamp,
+}
+
+// Every NFT earns tickets_per_period extra tickets for each full period_seconds its
+// position has been staked, up to max_bonus_tickets (0 leaves the bonus uncapped).
+// { period_seconds: 604800, tickets_per_period: 1, max_bonus_tickets: 0 } is +1 per week.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DurationWeighting {
+    pub period_seconds: u64,
+    pub tickets_per_period: u64,
+    pub max_bonus_tickets: u64,
+}
+
+impl Config {
+    pub fn min_staking_seconds(&self) -> u64 {
+        match self.min_staking_period {
+            Duration::Time(seconds) => seconds,
+            // Refused wherever the period is set
+            Duration::Height(_) => 0,
+        }
+    }
+
+    pub fn lock_tier(&self, duration_seconds: u64) -> Option<&LockTier> {
+        self.lock_tiers
+            .iter()
+            .find(|tier| tier.duration_seconds == duration_seconds)
+    }
+
+    pub fn prize_places(&self) -> Vec<u16> {
+        if self.prize_split_bps.is_empty() {
+            vec![10_000]
+        } else {
+            self.prize_split_bps.clone()
+        }
+    }
+}
+
+pub(crate) const CONFIG: Item<Config> = Item::new("config");
+// Proposed admin, who becomes Config::admin only after accepting
+pub(crate) const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+pub(crate) const STATE: Item<State> = Item::new("state");
+
+// Lifetime prize total per address, updated once a claim transfer succeeds
+pub(crate) const TOTAL_WINNINGS: Map<String, Uint128> = Map::new("total_winnings");
+
+// Every address that has ever opened a position
+pub(crate) const KNOWN_STAKERS: Map<&Addr, bool> = Map::new("known_stakers");
+
+// Places won in the main draw per address, counted as draws settle
+pub(crate) const WIN_COUNTS: Map<&Addr, u32> = Map::new("win_counts");
+
+pub const LEADERBOARD_SIZE: usize = 100;
+
+// Top addresses by lifetime winnings, highest first
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct BoundedLeaderboard {
+    pub entries: Vec<(String, Uint128)>,
+}
+
+impl BoundedLeaderboard {
+    // Set an address's lifetime total, keeping only the top LEADERBOARD_SIZE entries
+    pub fn record(&mut self, address: String, total: Uint128) {
+        self.entries.retain(|(entry, _)| *entry != address);
+        let position = self.entries.partition_point(|(_, amount)| *amount >= total);
+        self.entries.insert(position, (address, total));
+        self.entries.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+pub(crate) const PRIZE_LEADERBOARD: Item<BoundedLeaderboard> = Item::new("prize_leaderboard");
+
+// NFTs burned per address, and the top burners by that count
+pub(crate) const BURN_COUNTS: Map<&Addr, u64> = Map::new("burn_counts");
+pub(crate) const BURN_LEADERBOARD: Item<BoundedLeaderboard> = Item::new("burn_leaderboard");
+
+// Addresses whose stakes never enter draws, though they still count for voting power.
+// Starts out with the admin and the contract itself.
+pub(crate) const DRAW_EXCLUSIONS: Map<&Addr, bool> = Map::new("draw_exclusions");
+
+// Round of each address's latest win in the main draw, at any place
+pub(crate) const LAST_WIN_ROUND: Map<&Addr, u64> = Map::new("last_win_round");
+
+// Reward token prizes won but not yet claimed. Each draw moves the pot in here, so the
+// pot and this ledger never hold the same tokens.
+pub(crate) const PENDING_PRIZES: Map<&Addr, PendingPrize> = Map::new("pending_prizes");
+
+// A new win adds to the unclaimed amount and restarts the claim window for all of it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingPrize {
+    pub amount: Uint128,
+    // None when prizes don't expire
+    pub claim_deadline: Option<Timestamp>,
+}
+
+// Balance of each prize tier's pool, keyed by tier name
+pub(crate) const TIER_POTS: Map<&str, Uint128> = Map::new("tier_pots");
+// Unclaimed tier prizes, keyed by (tier name, winner)
+pub(crate) const TIER_PRIZES: Map<(&str, &Addr), Uint128> = Map::new("tier_prizes");
+
+// The claim transfer awaiting its reply
+pub(crate) const CLAIM_IN_FLIGHT: Item<ClaimInFlight> = Item::new("claim_in_flight");
+
+// What a claim took off the ledgers to pay `claimer`. All of it goes back if the
+// transfer fails.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimInFlight {
+    pub claimer: Addr,
+    // Main pot prize, with the claim deadline it had
+    pub prize: Option<PendingPrize>,
+    // (tier name, amount) of each tier prize
+    pub tier_prizes: Vec<(String, Uint128)>,
+    pub staking_rewards: Uint128,
+    // (distribution id, amount) of a merkle claim
+    pub merkle: Option<(u64, Uint128)>,
+}
+
+impl ClaimInFlight {
+    pub fn new(claimer: Addr) -> Self {
+        ClaimInFlight {
+            claimer,
+            prize: None,
+            tier_prizes: vec![],
+            staking_rewards: Uint128::zero(),
+            merkle: None,
+        }
+    }
+
+    // Prizes count towards lifetime winnings; streaming rewards and airdrops don't
+    pub fn winnings(&self) -> Uint128 {
+        let prize = self.prize.as_ref().map_or(Uint128::zero(), |prize| prize.amount);
+        self.tier_prizes.iter().fold(prize, |total, (_, amount)| total + amount)
+    }
+
+    pub fn amount(&self) -> Uint128 {
+        let merkle = self.merkle.map_or(Uint128::zero(), |(_, amount)| amount);
+        self.winnings() + self.staking_rewards + merkle
+    }
+}
+
+// Winners and amounts of the push payout transfers awaiting their replies, in the order
+// the transfers were sent
+pub(crate) const PUSH_PAYOUTS_IN_FLIGHT: Item<Vec<(Addr, Uint128)>> =
+    Item::new("push_payouts_in_flight");
+
+// Payouts out of the pot awaiting their replies, in the order the transfers were sent
+pub(crate) const POT_PAYOUTS_IN_FLIGHT: Item<Vec<PotPayout>> = Item::new("pot_payouts_in_flight");
+
+// Reward tokens taken off the pot and native coins taken off the native pot to pay
+// `recipient`. Both go back if the transfer fails.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PotPayout {
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub native: Vec<Coin>,
+}
+
+// Funder and amount of the allowance pull awaiting its reply
+pub(crate) const FUND_IN_FLIGHT: Item<(Addr, Uint128)> = Item::new("fund_in_flight");
+
+// A new minimum staking period and when it can be applied
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingMinStakingPeriod {
+    pub period: Duration,
+    pub effective_at: Timestamp,
+}
+
+pub(crate) const PENDING_MIN_STAKING_PERIOD: Item<PendingMinStakingPeriod> =
+    Item::new("pending_min_staking_period");
+
+// A new protocol fee and treasury and when they can be applied
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingProtocolFee {
+    pub fee_bps: u16,
+    pub treasury: Option<Addr>,
+    pub effective_at: Timestamp,
+}
+
+pub(crate) const PENDING_PROTOCOL_FEE: Item<PendingProtocolFee> =
+    Item::new("pending_protocol_fee");
+
+// Just the field of Config that min_staking_period replaced
+#[derive(Deserialize)]
+pub(crate) struct ConfigMinStakingDaysV1 {
+    pub min_staking_days: Option<u64>,
+}
+
+// (owner, collection, token_id, lock duration) of the Stake pulls awaiting their
+// replies, oldest first
+pub(crate) const PULLS_IN_FLIGHT: Item<Vec<PullInFlight>> = Item::new("pulls_in_flight");
+
+pub(crate) type PullInFlight = (Addr, Addr, String, Option<u64>);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum StakeEventType {
+    Staked,
+    Unstaked,
+    WonDraw,
+    ClaimedReward,
+    ConsolationClaimed,
+    Burned,
+    // Burned at the stake by a draw
+    Sacrificed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeEvent {
+    pub event_type: StakeEventType,
+    pub token_id: Option<String>,
+    pub block_height: u64,
+    pub timestamp: Timestamp,
+    pub nft_count_after: u64,
+    // Round open when the event happened; a win belongs to the round just drawn
+    #[serde(default)]
+    pub round_id: u64,
+}
+
+// Per-address event log keyed by (address, sequence), sequences are global and increasing
+pub(crate) const STAKER_HISTORY: Map<(String, u64), StakeEvent> = Map::new("staker_history");
+
+// Record of a completed draw. round_id is State::draw_count at the time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Round {
+    pub round_id: u64,
+    // Both pots as they stood at draw time, before the split
+    pub pot: Uint128,
+    pub native_pot: Vec<Coin>,
+    // Main pot winners with their reward token prize, first place first
+    pub winners: Vec<(String, Uint128)>,
+    pub entropy: Binary,
+    pub entry_count: u64,
+    pub total_tickets: u64,
+    // Time of the previous draw, None for the first round
+    pub started_at: Option<Timestamp>,
+    pub drawn_at: Timestamp,
+    pub block_height: u64,
+    // The custody token burned at the stake this round, if sacrifices are on
+    #[serde(default)]
+    pub sacrifice: Option<SacrificedNft>,
+    // Reward tokens sent to the treasury out of the pot
+    #[serde(default)]
+    pub protocol_fee: Uint128,
+    // The three legs of the pot split: what the places were worth, what was carried
+    // into the next round and what was burned
+    #[serde(default)]
+    pub winner_share: Uint128,
+    #[serde(default)]
+    pub rollover: Uint128,
+    #[serde(default)]
+    pub burned: Uint128,
+    // Slice of the pot added to the jackpot, and the jackpot paid to first place if it
+    // triggered this round (already included in the first place prize)
+    #[serde(default)]
+    pub jackpot_contribution: Uint128,
+    #[serde(default)]
+    pub jackpot_paid: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SacrificedNft {
+    pub owner: String,
+    pub collection: Addr,
+    pub token_id: String,
+    // Reward tokens paid to the owner out of the pot
+    pub compensation: Uint128,
+}
+
+pub(crate) const ROUNDS: Map<u64, Round> = Map::new("rounds");
+
+// Groups of messages the admin can pause. Admin messages and AbandonmentExit are never
+// paused.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PausableAction {
+    // Stake, ReceiveNft, StakeBatch, ExtendLock and BurnForTickets
+    Stake,
+    // Unstake, UnstakeToken, UnstakeBatch and EmergencyUnstake
+    Unstake,
+    // DrawWinner and the messages that complete a pending draw
+    Draw,
+    // ClaimReward, ClaimStakingRewards, ClaimAll, ClaimMerkle, ClaimTierPrize,
+    // ClaimUnstaked and SweepExpiredPrizes
+    Claim,
+    // FundPot, FundPotByAllowance and the cw20 funding hooks
+    Fund,
+}
+
+pub(crate) const PAUSED: Item<Vec<PausableAction>> = Item::new("paused");
+
+// Contracts sent a StakeChangedHookMsg on every stake and unstake
+pub(crate) const HOOKS: Item<Vec<Addr>> = Item::new("hooks");
+
+// Hash of the CronCat task scheduling draws, set once CreateTask replies
+pub(crate) const DRAW_TASK: Item<String> = Item::new("draw_task");
+
+// Staker::weight per address, checkpointed on every change so voting power can be read
+// at past heights. Addresses untouched since the upgrade that added it have no entry.
+pub(crate) const VOTING_POWER: SnapshotMap<&Addr, u64> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock,
+);
+
+// State::total_weight, checkpointed alongside VOTING_POWER
+pub(crate) const TOTAL_POWER: SnapshotItem<u64> = SnapshotItem::new(
+    "total_power",
+    "total_power__checkpoints",
+    "total_power__changelog",
+    Strategy::EveryBlock,
+);
+
+// Global side of the streaming rewards. index grows by what one unit of voting power
+// earned each time it is brought up to date, so a staker only has to settle when their
+// weight changes or they claim.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RewardStream {
+    pub index: Decimal,
+    pub last_update: Timestamp,
+    // Funded and not yet emitted
+    pub reserve: Uint128,
+    // Emitted and not yet claimed
+    pub outstanding: Uint128,
+}
+
+pub(crate) const REWARD_STREAM: Item<RewardStream> = Item::new("reward_stream");
+
+// A staker's streaming rewards as of the last time they settled at RewardStream::index
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct StakerRewards {
+    pub index: Decimal,
+    pub pending: Uint128,
+}
+
+pub(crate) const STAKER_REWARDS: Map<&Addr, StakerRewards> = Map::new("staker_rewards");
+
+// Choices each staker makes for themselves, kept apart from the position so they
+// outlive it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct StakerSettings {
+    // Turn main pot prizes into compounded tickets at Config::compound_rate
+    pub auto_compound: bool,
+}
+
+pub(crate) const STAKER_SETTINGS: Map<&Addr, StakerSettings> = Map::new("staker_settings");
+
+// Reward tokens distributed off-chain under a merkle root, claimed with ClaimMerkle.
+// Posting a new root starts a new distribution id, so earlier claims don't block the
+// new one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleDistribution {
+    pub id: u64,
+    pub merkle_root: HexBinary,
+    // Reward tokens sent in with ReceiveMsg::FundMerkleDistribution, plus what the
+    // previous root left unclaimed
+    pub funded: Uint128,
+    pub claimed: Uint128,
+}
+
+pub(crate) const MERKLE_DISTRIBUTION: Item<MerkleDistribution> = Item::new("merkle_distribution");
+// (distribution id, claimer) pairs that have claimed
+pub(crate) const MERKLE_CLAIMED: Map<(u64, &Addr), bool> = Map::new("merkle_claimed");
+
+// Effective weight of every draw candidate keyed by (round_id, address), written at draw time
+pub(crate) const DRAW_SNAPSHOTS: Map<(u64, String), u64> = Map::new("draw_snapshots");
+
+// Progress of a batched RecalculateTotalStaked run
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ReconcileState {
+    pub accumulated: u64,
+    pub last_key: Option<String>,
+    pub completed: bool,
+}
+
+pub(crate) const RECONCILE_STATE: Item<ReconcileState> = Item::new("reconcile_state");
+
+// Each staker's preferred draw interval in seconds
+pub(crate) const INTERVAL_VOTES: Map<String, u64> = Map::new("interval_votes");
+
+// Last time each staker staked, unstaked or checked in
+pub(crate) const LAST_ACTIVITY: Map<String, Timestamp> = Map::new("last_activity");
+
+// sha256 commitments and revealed secrets of the open commit-reveal round
+pub(crate) const ENTROPY_COMMITS: Map<&Addr, HexBinary> = Map::new("entropy_commits");
+pub(crate) const ENTROPY_REVEALS: Map<&Addr, HexBinary> = Map::new("entropy_reveals");
@@ -0,0 +1,374 @@
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_json, to_json_binary, to_json_vec, Addr, ContractResult, CosmosMsg, Deps, DepsMut, Env,
+    HexBinary, MessageInfo, Order, OwnedDeps, Record, Response, Storage, SystemError, SystemResult,
+    Uint128, WasmMsg, WasmQuery,
+};
+use cw20::BalanceResponse;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use crate::contract::{execute, instantiate};
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, InvariantReport, NoisCallback};
+use crate::query::query_eligible_stakers;
+use crate::state::{
+    stakers, PotSplit, StateV1, LEGACY_STAKERS, PENDING_PRIZES, SECONDS_IN_DAY, STATE,
+};
+
+pub const ADMIN: &str = "admin";
+pub const NFT_CONTRACT: &str = "nft_contract";
+pub const REWARD_TOKEN: &str = "reward_token";
+pub const NOIS_PROXY: &str = "nois_proxy";
+// What the fixture's Nois proxy answers every draw with
+pub const DRAW_RANDOMNESS: [u8; 32] = [7; 32];
+
+// Builder for a contract instance with stakers, a funded pot and elapsed time
+#[derive(Clone, Debug)]
+pub struct TestFixture {
+    msg: InstantiateMsg,
+    stakers: u32,
+    pot: Uint128,
+    days: u64,
+}
+
+impl Default for TestFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestFixture {
+    pub fn new() -> Self {
+        TestFixture {
+            msg: InstantiateMsg {
+                admin: ADMIN.to_string(),
+                nft_contract: NFT_CONTRACT.to_string(),
+                reward_token: REWARD_TOKEN.to_string(),
+                activity_timeout_seconds: None,
+                weight_decay_rate_bps_per_week: 0,
+                min_draw_interval_seconds: None,
+                max_draw_interval_seconds: None,
+                badge_nft_contract: None,
+                target_pot_size: None,
+                accelerated_cooldown_seconds: None,
+                abandonment_timeout_seconds: None,
+                max_win_probability_bps: 0,
+                stake_receipt_contract: None,
+                min_staking_period: None,
+                duration_weighting: None,
+                nois_proxy: Some(NOIS_PROXY.to_string()),
+                drand: None,
+                commit_reveal: None,
+                claim_window_seconds: None,
+                prize_split_bps: vec![],
+                unbonding_seconds: None,
+                early_unstake_penalty: None,
+                keeper_reward_bps: 0,
+                croncat: None,
+                burn_multiplier: 0,
+                sacrifice_bps: 0,
+                allow_wallet_stakes: true,
+                lock_tiers: vec![],
+                winner_cooldown_rounds: 0,
+                max_nfts_per_staker: 0,
+                max_entries_per_round: 0,
+                stake_fee: None,
+                protocol_fee_bps: 0,
+                treasury: None,
+                pot_split: PotSplit::default(),
+                jackpot: None,
+                emission_per_second: Uint128::zero(),
+                compound_rate: Uint128::zero(),
+                push_payouts: false,
+            },
+            stakers: 0,
+            pot: Uint128::zero(),
+            days: 0,
+        }
+    }
+
+    // Adjust the InstantiateMsg before the contract is created
+    pub fn configure(mut self, f: impl FnOnce(&mut InstantiateMsg)) -> Self {
+        f(&mut self.msg);
+        self
+    }
+
+    // Stake one NFT each for `n` stakers named staker0, staker1, ...
+    pub fn with_stakers(mut self, n: u32) -> Self {
+        self.stakers = n;
+        self
+    }
+
+    pub fn with_pot(mut self, amount: Uint128) -> Self {
+        self.pot = amount;
+        self
+    }
+
+    // Move the returned env forward after the stakers have staked
+    pub fn advance_days(mut self, days: u64) -> Self {
+        self.days += days;
+        self
+    }
+
+    pub fn as_admin(&self) -> MessageInfo {
+        mock_info(ADMIN, &[])
+    }
+
+    pub fn as_staker(&self, index: usize) -> MessageInfo {
+        mock_info(&staker_addr(index), &[])
+    }
+
+    // Returns the dependencies, the advanced env and one MessageInfo per staker
+    pub fn build(&self) -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env, Vec<MessageInfo>) {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        instantiate(deps.as_mut(), env.clone(), self.as_admin(), self.msg.clone()).unwrap();
+
+        let infos: Vec<MessageInfo> = (0..self.stakers as usize)
+            .map(|index| self.as_staker(index))
+            .collect();
+        for info in &infos {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                ExecuteMsg::Stake {
+                    token_ids: vec![],
+                    lock_duration: None,
+                },
+            )
+            .unwrap();
+        }
+
+        if !self.pot.is_zero() {
+            let mut state = STATE.load(&deps.storage).unwrap();
+            state.current_pot = self.pot;
+            STATE.save(&mut deps.storage, &state).unwrap();
+        }
+
+        env.block.time = env.block.time.plus_seconds(self.days * SECONDS_IN_DAY);
+
+        (deps, env, infos)
+    }
+}
+
+// DrawWinner from `info`, completed by the Nois proxy in the same block
+pub fn draw(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let started = execute(deps.branch(), env.clone(), info, ExecuteMsg::DrawWinner {})?;
+    deliver_randomness(deps, env, started)
+}
+
+// Answer the randomness request of a draw that just started with DRAW_RANDOMNESS. The
+// returned response holds the messages and attributes of both, the request to the proxy
+// itself left out.
+pub fn deliver_randomness(
+    deps: DepsMut,
+    env: Env,
+    started: Response,
+) -> Result<Response, ContractError> {
+    let Some(job_id) = STATE.load(deps.storage)?.pending_draw_job else {
+        return Ok(started);
+    };
+    let callback = ExecuteMsg::NoisReceive {
+        callback: NoisCallback {
+            job_id,
+            published: env.block.time,
+            randomness: HexBinary::from(DRAW_RANDOMNESS),
+        },
+    };
+    let completed = execute(deps, env, mock_info(NOIS_PROXY, &[]), callback)?;
+    let messages = started.messages.into_iter().filter(|sub| {
+        !matches!(&sub.msg, CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. })
+            if contract_addr == NOIS_PROXY)
+    });
+    let attributes = started
+        .attributes
+        .into_iter()
+        .filter(|attr| attr.key != "action" && attr.key != "job_id");
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_submessages(completed.messages)
+        .add_attributes(completed.attributes)
+        .add_attributes(attributes)
+        .add_events(completed.events))
+}
+
+pub fn staker_addr(index: usize) -> String {
+    format!("staker{}", index)
+}
+
+pub fn assert_staker_eligible(deps: &Deps, env: &Env, addr: &str) {
+    let mut start_after = None;
+    loop {
+        let page = query_eligible_stakers(*deps, env.clone(), start_after, None).unwrap();
+        match page.last() {
+            None => panic!("{} is not eligible", addr),
+            Some(_) if page.iter().any(|(address, _)| address == addr) => return,
+            Some((last, _)) => start_after = Some(last.clone()),
+        }
+    }
+}
+
+pub fn assert_pot_balance(deps: &Deps, expected: Uint128) {
+    let state = STATE.load(deps.storage).unwrap();
+    assert_eq!(state.current_pot, expected);
+}
+
+// Prize `addr` has won and not yet claimed
+pub fn assert_pending_claim(deps: &Deps, addr: &str, expected: Uint128) {
+    let pending = PENDING_PRIZES
+        .may_load(deps.storage, &Addr::unchecked(addr))
+        .unwrap()
+        .map_or(Uint128::zero(), |pending| pending.amount);
+    assert_eq!(pending, expected);
+}
+
+// Answer CW20 balance queries to the reward token with a fixed amount
+pub fn mock_reward_balance(querier: &mut MockQuerier, balance: Uint128) {
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr == REWARD_TOKEN => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&BalanceResponse { balance }).unwrap(),
+            ))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+}
+
+// Run ValidateStateInvariants as the admin and fail with its details if any check broke
+pub fn assert_invariants(deps: DepsMut, env: &Env) {
+    let res = execute(
+        deps,
+        env.clone(),
+        mock_info(ADMIN, &[]),
+        ExecuteMsg::ValidateStateInvariants {},
+    )
+    .unwrap();
+    let report: InvariantReport = from_json(res.data.unwrap()).unwrap();
+    assert!(report.passed, "invariants violated: {:?}", report.details);
+}
+
+// Rewrite storage into the v1 layout: stakers held in State, String-keyed staker
+// positions and no cw2 version
+pub fn downgrade_to_v1(storage: &mut dyn Storage) {
+    let positions: Vec<_> = stakers()
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.unwrap())
+        .collect();
+    let mut staker_set = HashSet::new();
+    for (address, staker) in positions {
+        stakers().remove(storage, &address).unwrap();
+        LEGACY_STAKERS
+            .save(storage, address.to_string(), &staker)
+            .unwrap();
+        staker_set.insert(address.to_string());
+    }
+
+    let state = STATE.load(storage).unwrap();
+
+    let old = StateV1 {
+        total_staked: state.total_staked,
+        current_pot: state.current_pot,
+        last_winner: state.last_winner,
+        stakers: staker_set,
+    };
+    storage.set(STATE.as_slice(), &to_json_vec(&old).unwrap());
+    storage.remove(b"contract_info");
+}
+
+// Drop every voting power checkpoint, as on a contract from before checkpointing began
+pub fn clear_power_checkpoints(storage: &mut dyn Storage) {
+    let keys: Vec<Vec<u8>> = storage
+        .range(None, None, Order::Ascending)
+        .map(|(key, _)| key)
+        .filter(|key| {
+            let namespace = key.get(2..).unwrap_or_default();
+            key.starts_with(b"total_power")
+                || namespace.starts_with(b"voting_power")
+                || namespace.starts_with(b"total_power")
+        })
+        .collect();
+    for key in keys {
+        storage.remove(&key);
+    }
+}
+
+// Storage wrapper that counts reads and writes, which is what gas mostly tracks
+pub struct CountingStorage {
+    inner: MockStorage,
+    reads: Cell<u64>,
+    writes: u64,
+}
+
+impl CountingStorage {
+    pub fn new(inner: MockStorage) -> Self {
+        CountingStorage {
+            inner,
+            reads: Cell::new(0),
+            writes: 0,
+        }
+    }
+
+    // Each key fetched with get and each entry yielded by range counts as one read
+    pub fn reads(&self) -> u64 {
+        self.reads.get()
+    }
+
+    pub fn writes(&self) -> u64 {
+        self.writes
+    }
+
+    pub fn reset(&mut self) {
+        self.reads.set(0);
+        self.writes = 0;
+    }
+}
+
+impl Storage for CountingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get(key)
+    }
+
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'a> {
+        Box::new(
+            self.inner
+                .range(start, end, order)
+                .inspect(move |_| self.reads.set(self.reads.get() + 1)),
+        )
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes += 1;
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes += 1;
+        self.inner.remove(key)
+    }
+}
+
+// Swap the storage of built dependencies for a counting one, keeping its contents
+pub fn with_counting_storage(
+    deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
+) -> OwnedDeps<CountingStorage, MockApi, MockQuerier> {
+    OwnedDeps {
+        storage: CountingStorage::new(deps.storage),
+        api: deps.api,
+        querier: deps.querier,
+        custom_query_type: PhantomData,
+    }
+}
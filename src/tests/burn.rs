@@ -0,0 +1,174 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, to_json_binary, Addr, Binary, CosmosMsg, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg, TicketsResponse};
+use nft_staking_lottery::state::{Round, SacrificedNft, Staker, State};
+use nft_staking_lottery::testing::{
+    assert_invariants, assert_pending_claim, draw, mock_reward_balance, TestFixture, NFT_CONTRACT,
+    REWARD_TOKEN,
+};
+use nft_staking_lottery::ContractError;
+
+fn send_nft(token_id: &str) -> ExecuteMsg {
+    ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "alice".to_string(),
+        token_id: token_id.to_string(),
+        msg: Binary::default(),
+    })
+}
+
+fn burn(token_id: &str) -> ExecuteMsg {
+    ExecuteMsg::BurnForTickets {
+        collection: None,
+        token_id: token_id.to_string(),
+    }
+}
+
+#[test]
+fn burned_nfts_leave_permanent_tickets() {
+    let fixture = TestFixture::new().configure(|msg| msg.burn_multiplier = 3);
+    let (mut deps, mut env, _) = fixture.build();
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("1")).unwrap();
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), burn("2")).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TokenNotStaked {
+            token_id: "2".to_string()
+        }
+    );
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), burn("1")).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: NFT_CONTRACT.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::Burn {
+                token_id: "1".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The emptied position stays in the draw with its burned tickets
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStaker {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let staker = staker.unwrap();
+    assert_eq!(staker.nft_count, 0);
+    assert_eq!(staker.burned_weight, 3);
+    let state: State =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.total_staked, 0);
+    assert_eq!(state.total_burns, 1);
+    assert_eq!(state.total_weight(), 3);
+
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    let tickets: TicketsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetTickets {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tickets.tickets, 3);
+
+    // Nothing is left in custody to unstake
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Unstake {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingStaked {});
+
+    let leaderboard: Vec<(String, Uint128)> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetBurnLeaderboard { limit: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(leaderboard, vec![("alice".to_string(), Uint128::new(1))]);
+    mock_reward_balance(&mut deps.querier, Uint128::zero());
+    assert_invariants(deps.as_mut(), &env);
+}
+
+#[test]
+fn burning_is_off_without_a_multiplier() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("1")).unwrap();
+
+    let err = execute(deps.as_mut(), env, mock_info("alice", &[]), burn("1")).unwrap_err();
+    assert_eq!(err, ContractError::BurnDisabled {});
+}
+
+#[test]
+fn each_draw_burns_a_custody_token_at_the_stake() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.sacrifice_bps = 1_000)
+        .with_pot(Uint128::new(1_000));
+    let (mut deps, mut env, _) = fixture.build();
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("1")).unwrap();
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let messages: Vec<CosmosMsg> = res.messages.into_iter().map(|sub| sub.msg).collect();
+    assert!(messages.contains(&CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: NFT_CONTRACT.to_string(),
+        msg: to_json_binary(&Cw721ExecuteMsg::Burn {
+            token_id: "1".to_string(),
+        })
+        .unwrap(),
+        funds: vec![],
+    })));
+    assert!(messages.contains(&CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: REWARD_TOKEN.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: "alice".to_string(),
+            amount: Uint128::new(100),
+        })
+        .unwrap(),
+        funds: vec![],
+    })));
+    // The winner draws from what is left after the compensation
+    assert_pending_claim(&deps.as_ref(), "alice", Uint128::new(900));
+
+    let round: Option<Round> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetRound { round_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        round.unwrap().sacrifice,
+        Some(SacrificedNft {
+            owner: "alice".to_string(),
+            collection: Addr::unchecked(NFT_CONTRACT),
+            token_id: "1".to_string(),
+            compensation: Uint128::new(100),
+        })
+    );
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetStaker {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker, None);
+}
@@ -0,0 +1,416 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{
+    from_json, to_json_binary, CosmosMsg, HexBinary, Reply, ReplyOn, Response, SubMsgResponse,
+    SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use nft_staking_lottery::contract::{execute, query, reply};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg, ReceiveMsg};
+use nft_staking_lottery::testing::{
+    assert_invariants, assert_pending_claim, assert_pot_balance, draw, mock_reward_balance,
+    staker_addr, TestFixture, REWARD_TOKEN,
+};
+use nft_staking_lottery::{ContractError, CLAIM_REPLY_ID, FUND_REPLY_ID};
+use sha2::{Digest, Sha256};
+
+#[test]
+fn draw_escrows_the_prize_until_the_winner_claims_it() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(700))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ClaimReward {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoWinner {});
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winner")
+        .unwrap()
+        .value
+        .clone();
+    assert_pot_balance(&deps.as_ref(), Uint128::zero());
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(700));
+    mock_reward_balance(&mut deps.querier, Uint128::new(700));
+    assert_invariants(deps.as_mut(), &env);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&winner, &[]),
+        ExecuteMsg::ClaimReward {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: winner.clone(),
+                amount: Uint128::new(700),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::zero());
+
+    // Nothing left to pay out a second time
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&winner, &[]),
+        ExecuteMsg::ClaimReward {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotTheWinner {});
+}
+
+#[test]
+fn unclaimed_prizes_accumulate_across_draws() {
+    let fixture = TestFixture::new()
+        .with_stakers(1)
+        .with_pot(Uint128::new(300))
+        .advance_days(8);
+    let (mut deps, mut env, _) = fixture.build();
+
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::FundPotByAllowance {
+            amount: Uint128::new(200),
+        },
+    )
+    .unwrap();
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: FUND_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(86_400);
+    draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+
+    assert_pending_claim(&deps.as_ref(), &staker_addr(0), Uint128::new(500));
+}
+
+#[test]
+fn expired_prizes_are_swept_back_into_the_pot() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.claim_window_seconds = Some(86_400))
+        .with_stakers(1)
+        .with_pot(Uint128::new(700))
+        .advance_days(8);
+    let (mut deps, mut env, _) = fixture.build();
+    let winner = staker_addr(0);
+
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let deadline = env.block.time.plus_seconds(86_400);
+
+    // Nothing has expired yet
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::SweepExpiredPrizes {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(700));
+
+    env.block.time = deadline.plus_seconds(1);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&winner, &[]),
+        ExecuteMsg::ClaimReward {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ClaimWindowExpired { deadline });
+
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("anyone", &[]),
+        ExecuteMsg::SweepExpiredPrizes {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::zero());
+    assert_pot_balance(&deps.as_ref(), Uint128::new(700));
+}
+
+#[test]
+fn expired_prizes_are_swept_a_page_at_a_time() {
+    let fixture = TestFixture::new()
+        .configure(|msg| {
+            msg.claim_window_seconds = Some(86_400);
+            msg.prize_split_bps = vec![6_000, 3_000, 1_000];
+        })
+        .with_stakers(3)
+        .with_pot(Uint128::new(1_001))
+        .advance_days(8);
+    let (mut deps, mut env, _) = fixture.build();
+
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    env.block.time = env.block.time.plus_seconds(86_401);
+
+    let sweep = |start_after: Option<String>| ExecuteMsg::SweepExpiredPrizes {
+        start_after,
+        limit: Some(2),
+    };
+    let attr = |res: &Response, key: &str| {
+        res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone()
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), sweep(None)).unwrap();
+    assert_eq!(attr(&res, "prizes"), "2");
+    let last_key = attr(&res, "last_key");
+    assert!((0..3).any(|index| staker_addr(index) == last_key));
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), sweep(Some(last_key)))
+        .unwrap();
+    assert_eq!(attr(&res, "prizes"), "1");
+    assert_pot_balance(&deps.as_ref(), Uint128::new(1_001));
+    for index in 0..3 {
+        assert_pending_claim(&deps.as_ref(), &staker_addr(index), Uint128::zero());
+    }
+
+    // Past the last winner there is nothing left to scan
+    let last_key = attr(&res, "last_key");
+    let res =
+        execute(deps.as_mut(), env, mock_info("anyone", &[]), sweep(Some(last_key))).unwrap();
+    assert_eq!(attr(&res, "prizes"), "0");
+    assert_eq!(attr(&res, "last_key"), "");
+}
+
+#[test]
+fn pot_is_split_between_distinct_winners() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.prize_split_bps = vec![6_000, 3_000, 1_000])
+        .with_stakers(3)
+        .with_pot(Uint128::new(1_001))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let res = draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+    let winners: Vec<String> = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winners")
+        .unwrap()
+        .value
+        .split(',')
+        .map(str::to_string)
+        .collect();
+    assert_eq!(winners.len(), 3);
+    assert!(winners.iter().all(|winner| winners.iter().filter(|w| *w == winner).count() == 1));
+
+    // The rounding dust goes to first place
+    assert_pending_claim(&deps.as_ref(), &winners[0], Uint128::new(601));
+    assert_pending_claim(&deps.as_ref(), &winners[1], Uint128::new(300));
+    assert_pending_claim(&deps.as_ref(), &winners[2], Uint128::new(100));
+    assert_pot_balance(&deps.as_ref(), Uint128::zero());
+}
+
+#[test]
+fn unfilled_places_leave_their_share_in_the_pot() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.prize_split_bps = vec![6_000, 3_000, 1_000])
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+    assert_pot_balance(&deps.as_ref(), Uint128::new(100));
+}
+
+#[test]
+fn claim_all_pays_prizes_and_streaming_rewards_in_one_transfer() {
+    let fixture = TestFixture::new()
+        .with_stakers(1)
+        .with_pot(Uint128::new(300))
+        .advance_days(8)
+        .configure(|msg| msg.emission_per_second = Uint128::new(1));
+    let (mut deps, mut env, infos) = fixture.build();
+
+    let err = execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::ClaimAll {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingOwed {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: fixture.as_admin().sender.to_string(),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg::FundRewards {}).unwrap(),
+        }),
+    )
+    .unwrap();
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    env.block.time = env.block.time.plus_seconds(40);
+
+    let res = execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::ClaimAll {})
+        .unwrap();
+    assert_eq!(res.messages[0].id, CLAIM_REPLY_ID);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: staker_addr(0),
+                amount: Uint128::new(340),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_pending_claim(&deps.as_ref(), &staker_addr(0), Uint128::zero());
+
+    let err = execute(deps.as_mut(), env, infos[0].clone(), ExecuteMsg::ClaimAll {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingOwed {});
+}
+
+#[test]
+fn merkle_claims_pay_out_once_against_the_posted_root() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+    let leaf = |address: &str, amount: u128| {
+        Sha256::digest(format!("{}{}", address, amount)).to_vec()
+    };
+    let (alice, bob) = (leaf("alice", 100), leaf("bob", 50));
+    let pair = if alice <= bob {
+        [alice.as_slice(), bob.as_slice()]
+    } else {
+        [bob.as_slice(), alice.as_slice()]
+    };
+    let root = HexBinary::from(Sha256::digest(pair.concat()).to_vec());
+    let claim = |amount| ExecuteMsg::ClaimMerkle {
+        proof: vec![HexBinary::from(bob.clone())],
+        amount: Uint128::new(amount),
+    };
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim(100))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoMerkleDistribution {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::PostMerkleRoot { merkle_root: root },
+    )
+    .unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim(100))
+        .unwrap_err();
+    assert_eq!(err, ContractError::MerkleUnderfunded {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "funder".to_string(),
+            amount: Uint128::new(150),
+            msg: to_json_binary(&ReceiveMsg::FundMerkleDistribution {}).unwrap(),
+        }),
+    )
+    .unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim(200))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim(100)).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "alice".to_string(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    let claimed: bool = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::MerkleClaimed {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(claimed);
+
+    let err = execute(deps.as_mut(), env, mock_info("alice", &[]), claim(100)).unwrap_err();
+    assert_eq!(err, ContractError::MerkleAlreadyClaimed {});
+}
+
+#[test]
+fn a_failed_claim_transfer_puts_the_prize_back() {
+    let fixture = TestFixture::new()
+        .with_stakers(1)
+        .with_pot(Uint128::new(700))
+        .advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+
+    let claim_reply = |result| Reply {
+        id: CLAIM_REPLY_ID,
+        result,
+    };
+    let res = execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::ClaimReward {})
+        .unwrap();
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Always);
+    assert_pending_claim(&deps.as_ref(), &staker_addr(0), Uint128::zero());
+
+    // A frozen token fails the transfer; the prize can be claimed again
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        claim_reply(SubMsgResult::Err("frozen".to_string())),
+    )
+    .unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.value == "claim_failed"));
+    assert_pending_claim(&deps.as_ref(), &staker_addr(0), Uint128::new(700));
+
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::ClaimReward {}).unwrap();
+    let res = reply(
+        deps.as_mut(),
+        env,
+        claim_reply(SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        })),
+    )
+    .unwrap();
+    let lifetime = res.attributes.iter().find(|attr| attr.key == "lifetime_winnings").unwrap();
+    assert_eq!(lifetime.value, "700");
+    assert_pending_claim(&deps.as_ref(), &staker_addr(0), Uint128::zero());
+}
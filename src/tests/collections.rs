@@ -0,0 +1,353 @@
+use cosmwasm_std::testing::{mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, ContractResult, CosmosMsg, OwnedDeps,
+    SystemError, SystemResult, WasmMsg, WasmQuery,
+};
+use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg, NftInfoResponse};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{
+    CollectionInfo, ExecuteMsg, NftMetadata, NftTrait, QueryMsg, StakedToken, StakerCollection,
+    TicketsResponse,
+};
+use nft_staking_lottery::state::TraitWeight;
+use nft_staking_lottery::testing::{TestFixture, NFT_CONTRACT};
+use nft_staking_lottery::ContractError;
+
+const RARE: &str = "rare_nfts";
+
+fn send_nft(token_id: &str) -> ExecuteMsg {
+    ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "alice".to_string(),
+        token_id: token_id.to_string(),
+        msg: Binary::default(),
+    })
+}
+
+fn add_rare(weight: u64) -> ExecuteMsg {
+    ExecuteMsg::AddCollection {
+        address: RARE.to_string(),
+        weight,
+    }
+}
+
+#[test]
+fn each_registered_collection_stakes_at_its_own_weight() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info(RARE, &[]), send_nft("1")).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownCollection {
+            collection: RARE.to_string()
+        }
+    );
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), add_rare(0)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidCollectionWeight {});
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), add_rare(3)).unwrap();
+
+    // The same token id from two collections is two separate stakes
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("1")).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info(RARE, &[]), send_nft("1")).unwrap();
+
+    let collections: Vec<CollectionInfo> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ListCollections {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        collections,
+        vec![
+            CollectionInfo {
+                address: Addr::unchecked(NFT_CONTRACT),
+                weight: 1,
+                staked: 1,
+                trait_weights: vec![],
+                allowlisted: 0,
+            },
+            CollectionInfo {
+                address: Addr::unchecked(RARE),
+                weight: 3,
+                staked: 1,
+                trait_weights: vec![],
+                allowlisted: 0,
+            },
+        ]
+    );
+    let breakdown: Vec<StakerCollection> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStakerCollections {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        breakdown,
+        vec![
+            StakerCollection {
+                collection: Addr::unchecked(NFT_CONTRACT),
+                staked: 1,
+                weight: 1,
+            },
+            StakerCollection {
+                collection: Addr::unchecked(RARE),
+                staked: 1,
+                weight: 3,
+            },
+        ]
+    );
+
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    let tickets: TicketsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetTickets {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tickets.nft_count, 2);
+    assert_eq!(tickets.tickets, 4);
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::RemoveCollection {
+            address: RARE.to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NftsInCustody {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        ExecuteMsg::UnstakeToken {
+            collection: Some(RARE.to_string()),
+            token_id: "1".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: RARE.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "alice".to_string(),
+                token_id: "1".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    let tickets: TicketsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetTickets {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tickets.tickets, 1);
+
+    execute(
+        deps.as_mut(),
+        env,
+        fixture.as_admin(),
+        ExecuteMsg::RemoveCollection {
+            address: RARE.to_string(),
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn trait_weights_resolve_from_on_chain_metadata_at_stake_time() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+    // Token "1" is Legendary, token "2" has no metadata
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == NFT_CONTRACT => {
+            let Cw721QueryMsg::NftInfo { token_id } = from_json(msg).unwrap() else {
+                panic!("unexpected query");
+            };
+            let extension = (token_id == "1").then(|| NftMetadata {
+                attributes: Some(vec![NftTrait {
+                    trait_type: "rarity".to_string(),
+                    value: "Legendary".to_string(),
+                }]),
+            });
+            let info = NftInfoResponse {
+                token_uri: None,
+                extension,
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&info).unwrap()))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+
+    let trait_weights = vec![
+        TraitWeight {
+            trait_type: "rarity".to_string(),
+            value: "Rare".to_string(),
+            weight: 2,
+        },
+        TraitWeight {
+            trait_type: "rarity".to_string(),
+            value: "Legendary".to_string(),
+            weight: 5,
+        },
+    ];
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::SetTraitWeights {
+            collection: RARE.to_string(),
+            trait_weights: trait_weights.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownCollection {
+            collection: RARE.to_string()
+        }
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::SetTraitWeights {
+            collection: NFT_CONTRACT.to_string(),
+            trait_weights,
+        },
+    )
+    .unwrap();
+
+    for token_id in ["1", "2"] {
+        execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft(token_id))
+            .unwrap();
+    }
+    let tokens: Vec<StakedToken> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStakedTokens {
+                address: "alice".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let weights: Vec<u64> = tokens.iter().map(|token| token.weight).collect();
+    assert_eq!(weights, vec![5, 1]);
+
+    // Later changes leave staked tokens at the weight they were staked with
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::SetTraitWeights {
+            collection: NFT_CONTRACT.to_string(),
+            trait_weights: vec![],
+        },
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    let tickets: TicketsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetTickets {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tickets.tickets, 6);
+}
+
+#[test]
+fn an_allowlist_limits_which_tokens_can_be_staked() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+    let stakeable = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, token_id: &str| -> bool {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::IsStakeable {
+                    collection: NFT_CONTRACT.to_string(),
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert!(stakeable(&deps, "1"));
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::AllowTokens {
+            collection: NFT_CONTRACT.to_string(),
+            token_ids: vec!["1".to_string(), "2".to_string()],
+        },
+    )
+    .unwrap();
+    assert!(stakeable(&deps, "2"));
+    assert!(!stakeable(&deps, "3"));
+    let err = execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("3"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TokenNotAllowed {
+            token_id: "3".to_string()
+        }
+    );
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("1")).unwrap();
+
+    // Removing every entry lifts the restriction
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::DisallowTokens {
+            collection: NFT_CONTRACT.to_string(),
+            token_ids: vec!["1".to_string(), "2".to_string(), "9".to_string()],
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "changed" && attr.value == "2"));
+    assert!(stakeable(&deps, "3"));
+}
@@ -0,0 +1,220 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, Env, HexBinary, Uint128};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{CommitRevealSettings, ExecuteMsg, QueryMsg, SimulateDrawResponse};
+use nft_staking_lottery::state::State;
+use nft_staking_lottery::testing::TestFixture;
+use nft_staking_lottery::ContractError;
+use sha2::{Digest, Sha256};
+
+const CONTRIBUTORS: [&str; 3] = ["carol", "dave", "erin"];
+
+fn commit_reveal_fixture(min_reveals: u32) -> TestFixture {
+    TestFixture::new()
+        .configure(move |msg| {
+            msg.nois_proxy = None;
+            msg.commit_reveal = Some(CommitRevealSettings {
+                contributors: CONTRIBUTORS.iter().map(|name| name.to_string()).collect(),
+                commit_window_seconds: 600,
+                reveal_window_seconds: 600,
+                min_reveals,
+            })
+        })
+        .with_stakers(3)
+        .with_pot(Uint128::new(900))
+        .advance_days(8)
+}
+
+fn secret(contributor: &str) -> HexBinary {
+    HexBinary::from(format!("secret of {}", contributor).into_bytes())
+}
+
+fn commitment(contributor: &str) -> HexBinary {
+    HexBinary::from(Sha256::digest(secret(contributor).as_slice()).to_vec())
+}
+
+fn advance(env: &mut Env, seconds: u64) {
+    env.block.time = env.block.time.plus_seconds(seconds);
+}
+
+#[test]
+fn draw_is_seeded_by_the_revealed_secrets() {
+    let fixture = commit_reveal_fixture(2);
+    let (mut deps, mut env, _) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::DrawWinner {},
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("mallory", &[]),
+        ExecuteMsg::CommitEntropy {
+            commitment: commitment("mallory"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotAContributor {});
+    for contributor in &CONTRIBUTORS[..2] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(contributor, &[]),
+            ExecuteMsg::CommitEntropy {
+                commitment: commitment(contributor),
+            },
+        )
+        .unwrap();
+    }
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("carol", &[]),
+        ExecuteMsg::RevealEntropy {
+            secret: secret("carol"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotInRevealWindow {});
+
+    advance(&mut env, 600);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("erin", &[]),
+        ExecuteMsg::CommitEntropy {
+            commitment: commitment("erin"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CommitWindowClosed {});
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("carol", &[]),
+        ExecuteMsg::RevealEntropy {
+            secret: secret("dave"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::RevealMismatch {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("carol", &[]),
+        ExecuteMsg::RevealEntropy {
+            secret: secret("carol"),
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::FinalizeDraw {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::RevealWindowOpen {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("dave", &[]),
+        ExecuteMsg::RevealEntropy {
+            secret: secret("dave"),
+        },
+    )
+    .unwrap();
+
+    // Every commitment is revealed, so the draw can finish before the window ends
+    let mut hasher = Sha256::new();
+    hasher.update(secret("carol").as_slice());
+    hasher.update(secret("dave").as_slice());
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(hasher.finalize().to_vec().into()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::FinalizeDraw {},
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "winner" && attr.value == simulated.would_win));
+
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.pending_commit_reveal, None);
+    assert_eq!(state.draw_count, 1);
+}
+
+#[test]
+fn too_few_reveals_cancel_the_draw() {
+    let fixture = commit_reveal_fixture(2);
+    let (mut deps, mut env, _) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::DrawWinner {},
+    )
+    .unwrap();
+    for contributor in &CONTRIBUTORS[..2] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(contributor, &[]),
+            ExecuteMsg::CommitEntropy {
+                commitment: commitment(contributor),
+            },
+        )
+        .unwrap();
+    }
+    advance(&mut env, 600);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("carol", &[]),
+        ExecuteMsg::RevealEntropy {
+            secret: secret("carol"),
+        },
+    )
+    .unwrap();
+
+    advance(&mut env, 600);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::FinalizeDraw {},
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "draw_cancelled"));
+
+    // The admin can open a fresh round
+    execute(
+        deps.as_mut(),
+        env,
+        fixture.as_admin(),
+        ExecuteMsg::DrawWinner {},
+    )
+    .unwrap();
+}
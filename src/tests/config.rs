@@ -0,0 +1,156 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, Binary, Uint128};
+use cw721::Cw721ReceiveMsg;
+use cw_utils::Duration;
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ConfigResponse, ExecuteMsg, QueryMsg};
+use nft_staking_lottery::state::PendingMinStakingPeriod;
+use nft_staking_lottery::testing::{TestFixture, ADMIN, NFT_CONTRACT, REWARD_TOKEN};
+use nft_staking_lottery::ContractError;
+
+fn update_config() -> ExecuteMsg {
+    ExecuteMsg::UpdateConfig {
+        nft_contract: None,
+        reward_token: None,
+        min_draw_interval_seconds: None,
+        max_draw_interval_seconds: None,
+        draw_interval_seconds: None,
+    }
+}
+
+#[test]
+fn only_the_admin_can_update_config() {
+    let fixture = TestFixture::new().with_stakers(1);
+    let (mut deps, env, infos) = fixture.build();
+
+    let err = execute(deps.as_mut(), env, infos[0].clone(), update_config()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn a_shorter_minimum_period_applies_to_existing_stakes_after_the_timelock() {
+    let fixture = TestFixture::new().with_stakers(1).advance_days(2);
+    let (mut deps, mut env, infos) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::Unstake {}).unwrap_err();
+
+    let propose = |period| ExecuteMsg::ProposeMinStakingPeriod { period };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        propose(Duration::Time(86_400)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        propose(Duration::Height(100)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMinStakingPeriod {});
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), propose(Duration::Time(86_400)))
+        .unwrap();
+
+    let pending: Option<PendingMinStakingPeriod> = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::GetPendingMinStakingPeriod {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pending,
+        Some(PendingMinStakingPeriod {
+            period: Duration::Time(86_400),
+            effective_at: env.block.time.plus_seconds(3 * 86_400),
+        })
+    );
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::ApplyMinStakingPeriod {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TimelockActive {
+            remaining_secs: 3 * 86_400
+        }
+    );
+
+    // Anyone can apply it once the timelock has passed
+    env.block.time = env.block.time.plus_seconds(3 * 86_400);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::ApplyMinStakingPeriod {},
+    )
+    .unwrap();
+    let config: ConfigResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+    assert_eq!(config.min_staking_seconds, 86_400);
+
+    execute(deps.as_mut(), env, infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+}
+
+#[test]
+fn the_collection_cannot_change_while_nfts_are_held() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(NFT_CONTRACT, &[]),
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "alice".to_string(),
+            token_id: "1".to_string(),
+            msg: Binary::default(),
+        }),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        nft_contract: Some("new_collection".to_string()),
+        reward_token: None,
+        min_draw_interval_seconds: None,
+        max_draw_interval_seconds: None,
+        draw_interval_seconds: None,
+    };
+    let err = execute(deps.as_mut(), env, fixture.as_admin(), msg).unwrap_err();
+    assert_eq!(err, ContractError::NftsInCustody {});
+}
+
+#[test]
+fn set_config_cannot_move_held_nfts_or_funds() {
+    let fixture = TestFixture::new().with_pot(Uint128::new(500));
+    let (mut deps, env, _) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(NFT_CONTRACT, &[]),
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "alice".to_string(),
+            token_id: "1".to_string(),
+            msg: Binary::default(),
+        }),
+    )
+    .unwrap();
+
+    let set_config = |nft_contract: &str, reward_token: &str| ExecuteMsg::SetConfig {
+        admin: ADMIN.to_string(),
+        nft_contract: nft_contract.to_string(),
+        reward_token: reward_token.to_string(),
+    };
+    let msg = set_config("new_collection", REWARD_TOKEN);
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), msg).unwrap_err();
+    assert_eq!(err, ContractError::NftsInCustody {});
+    let msg = set_config(NFT_CONTRACT, "new_token");
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), msg).unwrap_err();
+    assert_eq!(err, ContractError::PotNotEmpty {});
+
+    // Passing the current addresses along changes nothing and goes through
+    let msg = set_config(NFT_CONTRACT, REWARD_TOKEN);
+    execute(deps.as_mut(), env, fixture.as_admin(), msg).unwrap();
+}
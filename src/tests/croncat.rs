@@ -0,0 +1,145 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{
+    coin, coins, from_json, to_json_binary, Binary, CosmosMsg, Reply, SubMsgResponse,
+    SubMsgResult, Uint128, WasmMsg,
+};
+use nft_staking_lottery::contract::{execute, query, reply};
+use nft_staking_lottery::msg::{
+    CronCatAction, CronCatInterval, CronCatSettings, CronCatTaskRequest, CronCatTasksExecuteMsg,
+    ExecuteMsg, QueryMsg,
+};
+use nft_staking_lottery::state::State;
+use nft_staking_lottery::testing::{assert_pending_claim, draw, TestFixture};
+use nft_staking_lottery::{ContractError, CRONCAT_REPLY_ID};
+
+const TASKS: &str = "croncat_tasks";
+const MANAGER: &str = "croncat_manager";
+const DAILY: &str = "0 0 0 * * *";
+
+fn croncat_fixture() -> TestFixture {
+    TestFixture::new()
+        .configure(|msg| {
+            msg.croncat = Some(CronCatSettings {
+                tasks: TASKS.to_string(),
+                manager: MANAGER.to_string(),
+            })
+        })
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8)
+}
+
+fn schedule(amount: u128) -> ExecuteMsg {
+    ExecuteMsg::ScheduleDraws {
+        cron: DAILY.to_string(),
+        funding: coin(amount, "ujuno"),
+    }
+}
+
+fn task_created(task_hash: &str) -> Reply {
+    Reply {
+        id: CRONCAT_REPLY_ID,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(Binary::from(task_hash.as_bytes())),
+        }),
+    }
+}
+
+#[test]
+fn draws_are_scheduled_with_funding_from_the_native_pot() {
+    let fixture = croncat_fixture();
+    let (mut deps, env, _) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("sponsor", &coins(500, "ujuno")),
+        ExecuteMsg::FundPot {},
+    )
+    .unwrap();
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), schedule(100))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), schedule(501)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientNativePot {
+            required: coin(501, "ujuno")
+        }
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), fixture.as_admin(), schedule(100)).unwrap();
+    let task = CronCatTaskRequest {
+        interval: CronCatInterval::Cron(DAILY.to_string()),
+        stop_on_fail: false,
+        actions: vec![CronCatAction {
+            msg: WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_json_binary(&ExecuteMsg::DrawWinner {}).unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            gas_limit: None,
+        }],
+    };
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, CRONCAT_REPLY_ID);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TASKS.to_string(),
+            msg: to_json_binary(&CronCatTasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            })
+            .unwrap(),
+            funds: coins(100, "ujuno"),
+        })
+    );
+    let state: State =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.native_pot, coins(400, "ujuno"));
+
+    reply(deps.as_mut(), env.clone(), task_created("task_1")).unwrap();
+    let task_hash: Option<String> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetDrawTask {}).unwrap()).unwrap();
+    assert_eq!(task_hash, Some("task_1".to_string()));
+
+    // Rescheduling replaces the registered task
+    let res = execute(deps.as_mut(), env.clone(), fixture.as_admin(), schedule(100)).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TASKS.to_string(),
+            msg: to_json_binary(&CronCatTasksExecuteMsg::RemoveTask {
+                task_hash: "task_1".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    reply(deps.as_mut(), env.clone(), task_created("task_2")).unwrap();
+    let task_hash: Option<String> =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetDrawTask {}).unwrap()).unwrap();
+    assert_eq!(task_hash, Some("task_2".to_string()));
+}
+
+#[test]
+fn the_croncat_manager_can_draw() {
+    let fixture = croncat_fixture();
+    let (mut deps, env, _) = fixture.build();
+
+    let res = draw(deps.as_mut(), env, mock_info(MANAGER, &[])).unwrap();
+    // Scheduled draws pay no keeper reward
+    let winner = res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(1_000));
+}
+
+#[test]
+fn scheduling_needs_a_croncat_deployment() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+
+    let err = execute(deps.as_mut(), env, fixture.as_admin(), schedule(100)).unwrap_err();
+    assert_eq!(err, ContractError::CronCatNotConfigured {});
+}
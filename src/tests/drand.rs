@@ -0,0 +1,109 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, HexBinary, Timestamp, Uint128};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg, SimulateDrawResponse};
+use nft_staking_lottery::state::{DrandConfig, State};
+use nft_staking_lottery::testing::TestFixture;
+use nft_staking_lottery::ContractError;
+use sha2::{Digest, Sha256};
+
+// League of Entropy mainnet, beacon of round 72785
+const PUBKEY: &str = "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31";
+const GENESIS_TIME: u64 = 1_595_431_050;
+const ROUND: u64 = 72_785;
+const PREVIOUS_SIGNATURE: &str = "a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747";
+const SIGNATURE: &str = "82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42";
+
+fn drand_fixture() -> TestFixture {
+    TestFixture::new()
+        .configure(|msg| {
+            msg.nois_proxy = None;
+            msg.drand = Some(DrandConfig {
+                pubkey: HexBinary::from_hex(PUBKEY).unwrap(),
+                genesis_time: GENESIS_TIME,
+                period_seconds: 30,
+            })
+        })
+        .with_stakers(3)
+        .with_pot(Uint128::new(900))
+}
+
+fn beacon(round: u64, signature: &str) -> ExecuteMsg {
+    ExecuteMsg::SubmitDrandBeacon {
+        round,
+        previous_signature: HexBinary::from_hex(PREVIOUS_SIGNATURE).unwrap(),
+        signature: HexBinary::from_hex(signature).unwrap(),
+    }
+}
+
+#[test]
+fn draw_completes_with_a_verified_beacon_of_the_next_round() {
+    let fixture = drand_fixture();
+    let (mut deps, mut env, _) = fixture.build();
+    // Round 72784 is the latest published one
+    env.block.time = Timestamp::from_seconds(GENESIS_TIME + (ROUND - 2) * 30);
+
+    let res = execute(deps.as_mut(), env.clone(), fixture.as_admin(), ExecuteMsg::DrawWinner {})
+        .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "drand_round" && attr.value == ROUND.to_string()));
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        beacon(ROUND - 1, SIGNATURE),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WrongDrandRound { expected: ROUND });
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        beacon(ROUND, PREVIOUS_SIGNATURE),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidDrandSignature { round: ROUND });
+
+    // drand randomness is the sha256 of the signature
+    let randomness = Sha256::digest(HexBinary::from_hex(SIGNATURE).unwrap().as_slice()).to_vec();
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(randomness.into()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        beacon(ROUND, SIGNATURE),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "winner" && attr.value == simulated.would_win));
+
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.pending_drand_round, None);
+    assert_eq!(state.draw_count, 1);
+}
+
+#[test]
+fn beacon_without_a_pending_draw_is_rejected() {
+    let fixture = drand_fixture().advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let err = execute(deps.as_mut(), env, mock_info("keeper", &[]), beacon(ROUND, SIGNATURE))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoDrandRoundPending {});
+}
@@ -0,0 +1,1070 @@
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, CosmosMsg, Deps, Env, Reply, ReplyOn, SubMsgResponse,
+    SubMsgResult, Timestamp, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_utils::Duration;
+use nft_staking_lottery::{
+    compute_effective_weight, ContractError, BADGE_REPLY_ID, FUND_REPLY_ID, POT_PAYOUT_REPLY_ID,
+    PUSH_REPLY_ID,
+};
+use nft_staking_lottery::contract::{execute, query, reply, sudo};
+use nft_staking_lottery::msg::{
+    BadgeExecuteMsg, EligibilityProof, ExecuteMsg, JackpotResponse, MintMsg, NextDrawResponse,
+    QueryMsg, SimulateDrawResponse, SudoMsg, TicketsResponse, VotingPowerAtHeightResponse,
+    WinnerBadgeExt,
+};
+use nft_staking_lottery::state::{
+    Config, DurationWeighting, JackpotConfig, PotSplit, Round, Staker, StakerSettings, State,
+};
+use nft_staking_lottery::testing::{
+    assert_pending_claim, assert_pot_balance, deliver_randomness, draw, staker_addr, TestFixture,
+    ADMIN, DRAW_RANDOMNESS,
+};
+use sha2::{Digest, Sha256};
+
+#[test]
+fn simulate_draw_predicts_the_real_draw() {
+    let fixture = TestFixture::new()
+        .with_stakers(5)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(Binary::from(DRAW_RANDOMNESS)),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulated.eligible_staker_count, 5);
+    assert_eq!(simulated.total_weight, 5);
+    assert_pot_balance(&deps.as_ref(), Uint128::new(1_000));
+
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.last_winner, Some(simulated.would_win));
+}
+
+#[test]
+fn simulate_draw_is_deterministic_for_an_entropy_override() {
+    let (deps, env, _) = TestFixture::new().with_stakers(10).advance_days(8).build();
+    let simulate = |entropy: &[u8]| -> SimulateDrawResponse {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::SimulateDraw {
+                    entropy_override: Some(Binary::from(entropy)),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(simulate(b"seed").would_win, simulate(b"seed").would_win);
+}
+
+#[test]
+fn inactive_stakers_sit_out_the_draw() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.activity_timeout_seconds = Some(10 * 86_400))
+        .with_stakers(3)
+        .advance_days(20);
+    let (mut deps, env, infos) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), infos[1].clone(), ExecuteMsg::CheckIn {}).unwrap();
+
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(Binary::from(DRAW_RANDOMNESS)),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulated.staker_weights, vec![(staker_addr(1), 1)]);
+    assert_eq!(simulated.would_win, staker_addr(1));
+}
+
+#[test]
+fn winners_sit_out_the_cooldown_rounds() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.winner_cooldown_rounds = 2)
+        .with_stakers(3)
+        .advance_days(8);
+    let (mut deps, mut env, _) = fixture.build();
+    let mut draw = |env: &Env| -> String {
+        let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+        res.attributes
+            .iter()
+            .find(|attr| attr.key == "winner")
+            .unwrap()
+            .value
+            .clone()
+    };
+
+    let first = draw(&env);
+    env.block.time = env.block.time.plus_seconds(86_400);
+    let second = draw(&env);
+    assert_ne!(second, first);
+    // Only the staker who hasn't won is left for the third round
+    env.block.time = env.block.time.plus_seconds(86_400);
+    let third = draw(&env);
+    assert_ne!(third, first);
+    assert_ne!(third, second);
+
+    // The first winner is back for the fourth round, the second still sits out
+    let tickets = |address: &str| -> u64 {
+        let msg = QueryMsg::GetTickets {
+            address: address.to_string(),
+        };
+        let res: TicketsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap())
+            .unwrap();
+        res.tickets
+    };
+    assert_eq!(tickets(&first), 1);
+    assert_eq!(tickets(&second), 0);
+    assert_eq!(tickets(&third), 0);
+}
+
+#[test]
+fn excluded_addresses_keep_voting_power_but_never_enter_draws() {
+    let fixture = TestFixture::new().with_stakers(2);
+    let (mut deps, mut env, _) = fixture.build();
+    let stake = ExecuteMsg::Stake {
+        token_ids: vec![],
+        lock_duration: None,
+    };
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), stake).unwrap();
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    env.block.height += 1;
+
+    let exclusions: Vec<String> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetDrawExclusions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(exclusions, vec![ADMIN.to_string(), env.contract.address.to_string()]);
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_staker(1),
+        ExecuteMsg::ExcludeFromDraws {
+            addresses: vec![staker_addr(0)],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::ExcludeFromDraws {
+            addresses: vec![staker_addr(0)],
+        },
+    )
+    .unwrap();
+
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(Binary::from(DRAW_RANDOMNESS)),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulated.staker_weights, vec![(staker_addr(1), 1)]);
+    let power: VotingPowerAtHeightResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VotingPowerAtHeight {
+                address: ADMIN.to_string(),
+                height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(power.power, Uint128::one());
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::IncludeInDraws {
+            addresses: vec![staker_addr(0)],
+        },
+    )
+    .unwrap();
+    let tickets: TicketsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetTickets {
+                address: staker_addr(0),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tickets.tickets, 1);
+}
+
+#[test]
+fn positions_past_the_entry_cap_wait_for_a_place() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.max_entries_per_round = 2)
+        .with_stakers(3)
+        .with_pot(Uint128::new(300))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    let simulate = |deps: Deps| -> SimulateDrawResponse {
+        let msg = QueryMsg::SimulateDraw {
+            entropy_override: Some(Binary::from(DRAW_RANDOMNESS)),
+        };
+        from_json(query(deps, env.clone(), msg).unwrap()).unwrap()
+    };
+    let tickets = |deps: Deps, index: usize| -> u64 {
+        let msg = QueryMsg::GetTickets {
+            address: staker_addr(index),
+        };
+        let res: TicketsResponse = from_json(query(deps, env.clone(), msg).unwrap()).unwrap();
+        res.tickets
+    };
+
+    // Positions of the same age enter in address order
+    let simulated = simulate(deps.as_ref());
+    assert_eq!(simulated.staker_weights, vec![(staker_addr(0), 1), (staker_addr(1), 1)]);
+    assert_eq!(tickets(deps.as_ref(), 2), 0);
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let round: Option<Round> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetRound { round_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(round.unwrap().entry_count, 2);
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "winner" && attr.value != staker_addr(2)));
+
+    // A place frees up once staker1 leaves
+    execute(deps.as_mut(), env.clone(), fixture.as_staker(1), ExecuteMsg::Unstake {}).unwrap();
+    let simulated = simulate(deps.as_ref());
+    assert_eq!(simulated.staker_weights, vec![(staker_addr(0), 1), (staker_addr(2), 1)]);
+    assert_eq!(tickets(deps.as_ref(), 2), 1);
+}
+
+#[test]
+fn effective_weight_decays_weekly_but_never_below_one() {
+    let staker = Staker {
+        staked_at: Timestamp::from_seconds(0),
+        nft_count: 10,
+        bonus_weight: 0,
+        burned_weight: 0,
+        compounded_weight: 0,
+    };
+    let config = Config {
+        admin: Addr::unchecked("admin"),
+        nft_contract: Addr::unchecked("nft_contract"),
+        reward_token: Addr::unchecked("reward_token"),
+        activity_timeout_seconds: None,
+        weight_decay_rate_bps_per_week: 2_500,
+        min_draw_interval_seconds: 3_600,
+        max_draw_interval_seconds: 30 * 86_400,
+        badge_nft_contract: None,
+        target_pot_size: Uint128::zero(),
+        accelerated_cooldown_seconds: 3_600,
+        abandonment_timeout_seconds: 0,
+        max_win_probability_bps: 0,
+        stake_receipt_contract: None,
+        min_staking_period: Duration::Time(7 * 86_400),
+        duration_weighting: None,
+        nois_proxy: None,
+        drand: None,
+        commit_reveal: None,
+        claim_window_seconds: 0,
+        prize_split_bps: vec![],
+        prize_tiers: vec![],
+        dao: None,
+        unbonding_seconds: 0,
+        early_unstake_penalty: Uint128::zero(),
+        keeper_reward_bps: 0,
+        croncat: None,
+        burn_multiplier: 0,
+        sacrifice_bps: 0,
+        allow_wallet_stakes: true,
+        lock_tiers: vec![],
+        winner_cooldown_rounds: 0,
+        max_nfts_per_staker: 0,
+        max_entries_per_round: 0,
+        stake_fee: None,
+        protocol_fee_bps: 0,
+        treasury: None,
+        pot_split: PotSplit::default(),
+        jackpot: None,
+        emission_per_second: Uint128::zero(),
+        compound_rate: Uint128::zero(),
+        push_payouts: false,
+    };
+
+    let weight = |weeks: u64| {
+        let now = staker.staked_at.plus_seconds(weeks * 7 * 86_400);
+        compute_effective_weight(&staker, staker.staked_at, now, &config)
+    };
+    assert_eq!(weight(0), 10);
+    assert_eq!(weight(1), 8);
+    assert_eq!(weight(2), 5);
+    assert_eq!(weight(4), 1);
+    assert_eq!(weight(52), 1);
+}
+
+#[test]
+fn draw_snapshots_every_candidate_weight() {
+    let fixture = TestFixture::new().with_stakers(3).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[2].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+
+    let weight: Option<u64> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetDrawSnapshot {
+                round_id: 1,
+                address: staker_addr(2),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(weight, Some(2));
+
+    let snapshot: Vec<(String, u64)> = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetFullDrawSnapshot {
+                round_id: 1,
+                start_after: Some(staker_addr(0)),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(snapshot, vec![(staker_addr(1), 1), (staker_addr(2), 2)]);
+}
+
+#[test]
+fn stakers_still_in_the_minimum_period_are_not_drawn() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("newcomer", &[]),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(Binary::from(DRAW_RANDOMNESS)),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulated.staker_weights, vec![(staker_addr(0), 1), (staker_addr(1), 1)]);
+}
+
+#[test]
+fn draw_mints_a_badge_when_a_badge_contract_is_set() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.badge_nft_contract = Some("badges".to_string()))
+        .with_stakers(2)
+        .with_pot(Uint128::new(700))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winner")
+        .unwrap()
+        .value
+        .clone();
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, BADGE_REPLY_ID);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Error);
+    let expected = BadgeExecuteMsg::Mint(MintMsg {
+        token_id: "win_round_1".to_string(),
+        owner: winner,
+        token_uri: None,
+        extension: WinnerBadgeExt {
+            round: 1,
+            prize: Uint128::new(700),
+            eligible_stakers: 2,
+            timestamp: env.block.time,
+        },
+    });
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "badges".to_string(),
+            msg: to_json_binary(&expected).unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn eligibility_proof_hash_commits_to_address_height_and_weight() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("newcomer", &[]),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    let prove = |address: &str| -> EligibilityProof {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ProveEligibility {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let proof = prove(&staker_addr(1));
+    assert!(proof.is_eligible);
+    assert_eq!(proof.effective_weight, 1);
+    assert_eq!(proof.contract_address, env.contract.address.to_string());
+    assert_eq!(proof.block_height, env.block.height);
+    assert_eq!(proof.block_time, env.block.time);
+
+    let mut hasher = Sha256::new();
+    hasher.update(staker_addr(1).as_bytes());
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(1u64.to_be_bytes());
+    assert_eq!(proof.proof_hash, Binary::from(hasher.finalize().to_vec()));
+
+    let newcomer = prove("newcomer");
+    assert!(!newcomer.is_eligible);
+    assert_eq!(newcomer.effective_weight, 0);
+}
+
+#[test]
+fn a_pot_at_the_target_shortens_the_draw_cooldown() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.target_pot_size = Some(Uint128::new(500)))
+        .with_stakers(2)
+        .with_pot(Uint128::new(700))
+        .advance_days(8);
+    let (mut deps, mut env, _) = fixture.build();
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let drawn_at = env.block.time;
+    let next_draw = |deps: Deps, env: &Env| -> NextDrawResponse {
+        from_json(query(deps, env.clone(), QueryMsg::NextDraw {}).unwrap()).unwrap()
+    };
+
+    // The emptied pot is below the target, so the full daily interval applies
+    env.block.time = env.block.time.plus_seconds(3_600);
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap_err();
+    let res = next_draw(deps.as_ref(), &env);
+    assert_eq!(res.next_draw_time, Some(drawn_at.plus_seconds(86_400)));
+    assert_eq!(res.seconds_remaining, 86_400 - 3_600);
+    assert!(!res.pot_threshold_met);
+    assert!(!res.draw_in_progress);
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::FundPotByAllowance {
+            amount: Uint128::new(500),
+        },
+    )
+    .unwrap();
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: FUND_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "pot_progress_pct" && attr.value == "100"));
+    let res = next_draw(deps.as_ref(), &env);
+    assert_eq!(res.next_draw_time, Some(drawn_at.plus_seconds(3_600)));
+    assert_eq!(res.seconds_remaining, 0);
+    assert!(res.pot_threshold_met);
+
+    draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+}
+
+#[test]
+fn win_probability_cap_limits_a_dominant_staker() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.max_win_probability_bps = 5_000)
+        .with_stakers(3)
+        .advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+    for _ in 0..3 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            infos[0].clone(),
+            ExecuteMsg::Stake {
+                token_ids: vec![],
+                lock_duration: None,
+            },
+        )
+        .unwrap();
+    }
+
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(Binary::from(DRAW_RANDOMNESS)),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    // 4 of 6 would be 66%; capping at the other stakers' combined weight makes it 50%
+    assert_eq!(
+        simulated.staker_weights,
+        vec![(staker_addr(0), 2), (staker_addr(1), 1), (staker_addr(2), 1)]
+    );
+    assert_eq!(simulated.total_weight, 4);
+}
+
+#[test]
+fn winners_are_picked_in_proportion_to_their_tickets() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+    for _ in 0..2 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            infos[0].clone(),
+            ExecuteMsg::Stake {
+                token_ids: vec![],
+                lock_duration: None,
+            },
+        )
+        .unwrap();
+    }
+
+    // 3 tickets against 1, so about 300 of 400 draws
+    let heavy_wins = (0u32..400)
+        .filter(|seed| {
+            let simulated: SimulateDrawResponse = from_json(
+                query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::SimulateDraw {
+                        entropy_override: Some(Binary::from(seed.to_be_bytes().to_vec())),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            simulated.would_win == staker_addr(0)
+        })
+        .count();
+    assert!((250..=350).contains(&heavy_wins), "{} wins", heavy_wins);
+}
+
+#[test]
+fn tickets_count_one_entry_per_staked_nft() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+    for _ in 0..2 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            infos[0].clone(),
+            ExecuteMsg::Stake {
+                token_ids: vec![],
+                lock_duration: None,
+            },
+        )
+        .unwrap();
+    }
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("newcomer", &[]),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    let tickets = |address: &str| -> TicketsResponse {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetTickets {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let heavy = tickets(&staker_addr(0));
+    assert_eq!((heavy.nft_count, heavy.tickets), (3, 3));
+    let light = tickets(&staker_addr(1));
+    assert_eq!((light.nft_count, light.tickets), (1, 1));
+    // Staked, but not yet through the minimum period
+    let newcomer = tickets("newcomer");
+    assert_eq!((newcomer.nft_count, newcomer.tickets), (1, 0));
+}
+
+#[test]
+fn duration_weighting_adds_tickets_per_full_period_up_to_the_cap() {
+    let fixture = TestFixture::new()
+        .configure(|msg| {
+            msg.duration_weighting = Some(DurationWeighting {
+                period_seconds: 7 * 86_400,
+                tickets_per_period: 1,
+                max_bonus_tickets: 3,
+            })
+        })
+        .with_stakers(1);
+    let (mut deps, mut env, infos) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    let mut tickets_after_days = |days: u64| -> u64 {
+        env.block.time = env.block.time.plus_seconds(days * 86_400);
+        let res: TicketsResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetTickets {
+                    address: staker_addr(0),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.tickets
+    };
+    // Two NFTs, one full week: 2 * (1 + 1)
+    assert_eq!(tickets_after_days(8), 4);
+    // Three full weeks
+    assert_eq!(tickets_after_days(14), 8);
+    // Capped at 3 bonus tickets per NFT
+    assert_eq!(tickets_after_days(70), 8);
+}
+
+#[test]
+fn draw_fails_until_some_stake_has_matured() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(700))
+        .advance_days(6);
+    let (mut deps, mut env, _) = fixture.build();
+
+    let err = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap_err();
+    assert_eq!(err, ContractError::NoEligibleStakers {});
+
+    env.block.time = env.block.time.plus_seconds(86_400);
+    draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+}
+
+#[test]
+fn anyone_can_draw_for_a_keeper_reward_once_the_cooldown_passes() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    let err = draw(deps.as_mut(), env, mock_info("keeper", &[])).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let fixture = fixture.configure(|msg| msg.keeper_reward_bps = 100);
+    let (mut deps, mut env, _) = fixture.build();
+    let res = draw(deps.as_mut(), env.clone(), mock_info("keeper", &[])).unwrap();
+    assert!(res.messages.iter().any(|submsg| submsg.msg
+        == CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward_token".to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "keeper".to_string(),
+                amount: Uint128::new(10),
+            })
+            .unwrap(),
+            funds: vec![],
+        })));
+    let winner = res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(990));
+    // Keeper draws don't count as admin activity
+    let state: State =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.last_admin_action_time, mock_env().block.time);
+
+    env.block.time = env.block.time.plus_seconds(3_600);
+    let err = draw(deps.as_mut(), env, mock_info("keeper", &[])).unwrap_err();
+    assert!(matches!(err, ContractError::DrawCooldownActive { .. }));
+}
+
+#[test]
+fn a_failed_keeper_payment_goes_back_to_the_pot() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.keeper_reward_bps = 100)
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    let keeper = mock_info("keeper", &[]);
+    let res = execute(deps.as_mut(), env.clone(), keeper, ExecuteMsg::DrawWinner {}).unwrap();
+    let payout = res.messages.last().unwrap();
+    assert_eq!((payout.id, payout.reply_on.clone()), (POT_PAYOUT_REPLY_ID, ReplyOn::Always));
+    // The winner's share waits in the pot for the randomness
+    assert_pot_balance(&deps.as_ref(), Uint128::new(990));
+
+    let reply_msg = Reply {
+        id: POT_PAYOUT_REPLY_ID,
+        result: SubMsgResult::Err("frozen".to_string()),
+    };
+    let res = reply(deps.as_mut(), env, reply_msg).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.value == "pot_payout_failed"));
+    assert_pot_balance(&deps.as_ref(), Uint128::new(1_000));
+}
+
+#[test]
+fn a_protocol_fee_goes_to_the_treasury_once_the_timelock_passes() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, mut env, infos) = fixture.build();
+
+    let propose = |fee_bps, treasury: Option<&str>| ExecuteMsg::ProposeProtocolFee {
+        fee_bps,
+        treasury: treasury.map(str::to_string),
+    };
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), propose(500, None))
+        .unwrap_err();
+    assert_eq!(err, ContractError::TreasuryRequired {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        propose(500, Some("treasury")),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::ApplyProtocolFee {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TimelockActive {
+            remaining_secs: 3 * 86_400
+        }
+    );
+
+    env.block.time = env.block.time.plus_seconds(3 * 86_400);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::ApplyProtocolFee {},
+    )
+    .unwrap();
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    assert!(res.messages.iter().any(|submsg| submsg.msg
+        == CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward_token".to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury".to_string(),
+                amount: Uint128::new(50),
+            })
+            .unwrap(),
+            funds: vec![],
+        })));
+    let winner = res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(950));
+    let round: Round =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetRound { round_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(round.protocol_fee, Uint128::new(50));
+}
+
+#[test]
+fn the_pot_split_rolls_over_and_burns_its_shares() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    let split = |rollover_bps, burn_bps| ExecuteMsg::SetPotSplit {
+        rollover_bps,
+        burn_bps,
+    };
+    let err = execute(deps.as_mut(), env.clone(), infos[0].clone(), split(2_000, 1_000))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), split(6_000, 5_000))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidPotSplit {});
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), split(2_000, 1_000)).unwrap();
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    assert!(res.messages.iter().any(|submsg| submsg.msg
+        == CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward_token".to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        })));
+    let winner = res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(700));
+    assert_pot_balance(&deps.as_ref(), Uint128::new(200));
+
+    let round: Round =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetRound { round_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        (round.winner_share, round.rollover, round.burned),
+        (Uint128::new(700), Uint128::new(200), Uint128::new(100))
+    );
+}
+
+#[test]
+fn the_jackpot_grows_until_its_trigger_hits() {
+    let jackpot_after = |odds| {
+        let fixture = TestFixture::new()
+            .with_stakers(2)
+            .with_pot(Uint128::new(1_000))
+            .advance_days(8)
+            .configure(|msg| {
+                msg.jackpot = Some(JackpotConfig {
+                    share_bps: 1_000,
+                    odds,
+                })
+            });
+        let (mut deps, env, _) = fixture.build();
+        let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+        let winner =
+            res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+        let jackpot: JackpotResponse =
+            from_json(query(deps.as_ref(), env, QueryMsg::GetJackpot {}).unwrap()).unwrap();
+        (winner, deps, jackpot)
+    };
+
+    // Practically never hits, so the slice stays in the jackpot
+    let (winner, deps, jackpot) = jackpot_after(u32::MAX);
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(900));
+    assert_eq!(jackpot.jackpot, Uint128::new(100));
+    assert_eq!(jackpot.last_hit, None);
+
+    // Always hits, paying the slice straight back to first place
+    let (winner, deps, jackpot) = jackpot_after(1);
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(1_000));
+    assert_eq!(jackpot.jackpot, Uint128::zero());
+    let hit = jackpot.last_hit.unwrap();
+    assert_eq!((hit.round_id, hit.winner, hit.amount), (1, winner, Uint128::new(100)));
+}
+
+#[test]
+fn auto_compounded_prizes_become_permanent_tickets() {
+    let fixture = TestFixture::new()
+        .with_stakers(1)
+        .with_pot(Uint128::new(1_050))
+        .advance_days(8)
+        .configure(|msg| msg.compound_rate = Uint128::new(100));
+    let (mut deps, env, infos) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::SetAutoCompound { enabled: true },
+    )
+    .unwrap();
+    let settings: StakerSettings = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStakerSettings {
+                address: staker_addr(0),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(settings.auto_compound);
+
+    // 1_000 buys ten tickets and goes back into the pot; the odd 50 is left to claim
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    assert_pending_claim(&deps.as_ref(), &staker_addr(0), Uint128::new(50));
+    assert_pot_balance(&deps.as_ref(), Uint128::new(1_000));
+
+    // The compounded tickets keep the position open once its NFT is gone
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetStaker {
+                address: staker_addr(0),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let staker = staker.unwrap();
+    assert_eq!((staker.nft_count, staker.compounded_weight), (0, 10));
+    assert_eq!(staker.weight(), 10);
+}
+
+#[test]
+fn push_payouts_pay_the_winner_during_the_draw() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.push_payouts = true)
+        .with_stakers(2)
+        .with_pot(Uint128::new(700))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, PUSH_REPLY_ID);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Always);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward_token".to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: winner.clone(),
+                amount: Uint128::new(700),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::zero());
+
+    // A transfer that fails leaves the prize to claim
+    let res = reply(
+        deps.as_mut(),
+        env,
+        Reply {
+            id: PUSH_REPLY_ID,
+            result: SubMsgResult::Err("frozen".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.value == "push_payout_failed"));
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(700));
+}
+
+#[test]
+fn the_chain_can_trigger_scheduled_draws() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, mut env, _) = fixture.build();
+
+    let started = sudo(deps.as_mut(), env.clone(), SudoMsg::TriggerDraw {}).unwrap();
+    let res = deliver_randomness(deps.as_mut(), env.clone(), started).unwrap();
+    let winner = res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(1_000));
+    let state: State =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.last_admin_action_time, mock_env().block.time);
+
+    let err = sudo(deps.as_mut(), env.clone(), SudoMsg::TriggerDraw {}).unwrap_err();
+    assert!(matches!(err, ContractError::DrawCooldownActive { .. }));
+    env.block.time = env.block.time.plus_seconds(2 * 86_400);
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), ExecuteMsg::Pause { actions: vec![] })
+        .unwrap();
+    let err = sudo(deps.as_mut(), env, SudoMsg::TriggerDraw {}).unwrap_err();
+    assert!(matches!(err, ContractError::Paused { .. }));
+}
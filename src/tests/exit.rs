@@ -0,0 +1,272 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{
+    from_json, to_json_binary, Binary, CosmosMsg, Reply, Response, SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use nft_staking_lottery::contract::{execute, query, reply};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg, ReceiveMsg};
+use nft_staking_lottery::state::{Staker, State};
+use nft_staking_lottery::testing::{
+    assert_pot_balance, staker_addr, TestFixture, NFT_CONTRACT, REWARD_TOKEN,
+};
+use nft_staking_lottery::{ContractError, POT_PAYOUT_REPLY_ID};
+
+#[test]
+fn stakers_exit_with_their_share_once_the_admin_goes_silent() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.abandonment_timeout_seconds = Some(30 * 86_400))
+        .with_stakers(2)
+        .with_pot(Uint128::new(900))
+        .advance_days(8);
+    let (mut deps, mut env, infos) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    // The admin was last heard from at instantiation, 8 days ago
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::AbandonmentExit {})
+        .unwrap_err();
+
+    env.block.time = env.block.time.plus_seconds(23 * 86_400);
+    let res =
+        execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::AbandonmentExit {})
+            .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: staker_addr(0),
+                amount: Uint128::new(600),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_pot_balance(&deps.as_ref(), Uint128::new(300));
+
+    // The position is closed, so a second exit has nothing to pay out
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::AbandonmentExit {})
+        .unwrap_err();
+    execute(deps.as_mut(), env, infos[1].clone(), ExecuteMsg::AbandonmentExit {}).unwrap();
+    assert_pot_balance(&deps.as_ref(), Uint128::zero());
+}
+
+#[test]
+fn admin_actions_reset_the_abandonment_clock() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.abandonment_timeout_seconds = Some(30 * 86_400))
+        .with_stakers(1)
+        .with_pot(Uint128::new(900))
+        .advance_days(25);
+    let (mut deps, mut env, infos) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::UpdateConfig {
+            nft_contract: None,
+            reward_token: None,
+            min_draw_interval_seconds: None,
+            max_draw_interval_seconds: None,
+            draw_interval_seconds: None,
+        },
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(10 * 86_400);
+    execute(deps.as_mut(), env, infos[0].clone(), ExecuteMsg::AbandonmentExit {}).unwrap_err();
+}
+
+fn emergency_unstake(sender: &str, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_json_binary(&ReceiveMsg::EmergencyUnstake {}).unwrap(),
+    })
+}
+
+#[test]
+fn emergency_unstake_pays_the_penalty_into_the_pot() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.early_unstake_penalty = Some(Uint128::new(50)))
+        .with_stakers(1)
+        .with_pot(Uint128::new(100));
+    let (mut deps, env, _) = fixture.build();
+    let staker = staker_addr(0);
+
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_staker(0), ExecuteMsg::Unstake {})
+        .unwrap_err();
+    assert!(matches!(err, ContractError::MinStakePeriodNotMet { .. }));
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        emergency_unstake(&staker, 49),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientPenalty {
+            required: Uint128::new(50)
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        emergency_unstake(&staker, 50),
+    )
+    .unwrap();
+    assert_pot_balance(&deps.as_ref(), Uint128::new(150));
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(REWARD_TOKEN, &[]),
+        emergency_unstake(&staker, 50),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingStaked {});
+}
+
+#[test]
+fn emergency_unstake_is_disabled_without_a_penalty() {
+    let fixture = TestFixture::new().with_stakers(1);
+    let (mut deps, env, _) = fixture.build();
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(REWARD_TOKEN, &[]),
+        emergency_unstake(&staker_addr(0), 50),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EarlyUnstakeDisabled {});
+}
+
+fn send_nft(sender: &str, token_id: &str) -> ExecuteMsg {
+    ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: sender.to_string(),
+        token_id: token_id.to_string(),
+        msg: Binary::default(),
+    })
+}
+
+fn returned_tokens(res: &Response) -> Vec<(String, String)> {
+    res.messages
+        .iter()
+        .filter_map(|submsg| match &submsg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_json(msg).ok()? {
+                Cw721ExecuteMsg::TransferNft { recipient, token_id } => Some((recipient, token_id)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn emergency_return_hands_back_custody_tokens_in_pages() {
+    let fixture = TestFixture::new().configure(|msg| msg.unbonding_seconds = Some(86_400));
+    let (mut deps, mut env, _) = fixture.build();
+    for (owner, token_id) in [("alice", "1"), ("alice", "2"), ("carol", "3"), ("carol", "4")] {
+        execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft(owner, token_id))
+            .unwrap();
+    }
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), ExecuteMsg::Unstake {}).unwrap();
+
+    let emergency_return = ExecuteMsg::EmergencyReturnNfts { limit: 2 };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), emergency_return.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), env.clone(), fixture.as_admin(), emergency_return.clone())
+        .unwrap();
+    assert_eq!(
+        returned_tokens(&res),
+        vec![("alice".to_string(), "1".to_string()), ("alice".to_string(), "2".to_string())]
+    );
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "is_complete" && attr.value == "false"));
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStaker {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker, None);
+
+    // Carol's staked token, then the one that was unbonding
+    let res = execute(deps.as_mut(), env.clone(), fixture.as_admin(), emergency_return).unwrap();
+    assert_eq!(
+        returned_tokens(&res),
+        vec![("carol".to_string(), "3".to_string()), ("carol".to_string(), "4".to_string())]
+    );
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "is_complete" && attr.value == "true"));
+    let state: State = from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.total_staked, 0);
+    assert_eq!(state.staker_count, 0);
+}
+
+#[test]
+fn burned_tickets_alone_exit_with_nothing_and_a_failed_payout_stays_in_the_pot() {
+    let fixture = TestFixture::new()
+        .configure(|msg| {
+            msg.abandonment_timeout_seconds = Some(30 * 86_400);
+            msg.burn_multiplier = 3;
+        })
+        .with_stakers(1)
+        .with_pot(Uint128::new(900));
+    let (mut deps, mut env, infos) = fixture.build();
+    let send_nft = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "alice".to_string(),
+        token_id: "1".to_string(),
+        msg: Binary::default(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft).unwrap();
+    let burn = ExecuteMsg::BurnForTickets {
+        collection: None,
+        token_id: "1".to_string(),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), burn).unwrap();
+
+    // staker0 leaves the last NFT in the pool, and a transfer that fails puts it back
+    env.block.time = env.block.time.plus_seconds(31 * 86_400);
+    let exit = ExecuteMsg::AbandonmentExit {};
+    let res = execute(deps.as_mut(), env.clone(), infos[0].clone(), exit.clone()).unwrap();
+    assert_eq!(res.messages[0].id, POT_PAYOUT_REPLY_ID);
+    assert_pot_balance(&deps.as_ref(), Uint128::zero());
+    let failed = Reply {
+        id: POT_PAYOUT_REPLY_ID,
+        result: SubMsgResult::Err("frozen".to_string()),
+    };
+    reply(deps.as_mut(), env.clone(), failed).unwrap();
+    assert_pot_balance(&deps.as_ref(), Uint128::new(900));
+
+    // With nothing staked the burned position's share of the pot is nothing
+    let res = execute(deps.as_mut(), env, mock_info("alice", &[]), exit).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res.attributes.iter().any(|attr| attr.key == "amount" && attr.value == "0"));
+    assert_pot_balance(&deps.as_ref(), Uint128::new(900));
+}
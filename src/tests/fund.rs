@@ -0,0 +1,301 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, BankMsg, Binary, Coin, CosmosMsg, Deps, Env, Reply,
+    SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw721::Cw721ReceiveMsg;
+use cw_utils::PaymentError;
+use nft_staking_lottery::contract::{execute, migrate, query, reply};
+use nft_staking_lottery::msg::{
+    ExecuteMsg, MigrateMsg, QueryMsg, ReceiveMsg, StakingRewardsResponse,
+};
+use nft_staking_lottery::state::State;
+use nft_staking_lottery::ContractError;
+use nft_staking_lottery::testing::{
+    assert_pot_balance, clear_power_checkpoints, draw, staker_addr, TestFixture, NFT_CONTRACT,
+    REWARD_TOKEN,
+};
+use nft_staking_lottery::FUND_REPLY_ID;
+
+#[test]
+fn fund_by_allowance_credits_the_pot_once_the_transfer_succeeds() {
+    let fixture = TestFixture::new().with_stakers(1).with_pot(Uint128::new(100));
+    let (mut deps, env, infos) = fixture.build();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::FundPotByAllowance {
+            amount: Uint128::new(250),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, FUND_REPLY_ID);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: staker_addr(0),
+                recipient: env.contract.address.to_string(),
+                amount: Uint128::new(250),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_pot_balance(&deps.as_ref(), Uint128::new(100));
+
+    reply(
+        deps.as_mut(),
+        env,
+        Reply {
+            id: FUND_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_pot_balance(&deps.as_ref(), Uint128::new(350));
+}
+
+fn fund_hook(amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: staker_addr(0),
+        amount: Uint128::new(amount),
+        msg: to_json_binary(&ReceiveMsg::FundPot {}).unwrap(),
+    })
+}
+
+#[test]
+fn cw20_sends_of_the_reward_token_fund_the_pot() {
+    let fixture = TestFixture::new().with_pot(Uint128::new(100));
+    let (mut deps, env, _) = fixture.build();
+
+    let res = execute(deps.as_mut(), env, mock_info(REWARD_TOKEN, &[]), fund_hook(250)).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "funder" && attr.value == staker_addr(0)));
+    assert_pot_balance(&deps.as_ref(), Uint128::new(350));
+}
+
+#[test]
+fn cw20_sends_of_other_tokens_are_rejected() {
+    let fixture = TestFixture::new().with_pot(Uint128::new(100));
+    let (mut deps, env, _) = fixture.build();
+
+    let err = execute(deps.as_mut(), env, mock_info("other_token", &[]), fund_hook(250))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    assert_pot_balance(&deps.as_ref(), Uint128::new(100));
+}
+
+#[test]
+fn native_coins_fund_the_pot_and_go_to_the_draw_winner() {
+    let fixture = TestFixture::new().with_stakers(1).advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let fund = |funds: &[Coin]| mock_info("funder", funds);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fund(&[coin(100, "uatom"), coin(5, "ujuno")]),
+        ExecuteMsg::FundPot {},
+    )
+    .unwrap();
+    execute(deps.as_mut(), env.clone(), fund(&[coin(50, "uatom")]), ExecuteMsg::FundPot {})
+        .unwrap();
+    execute(deps.as_mut(), env.clone(), fund(&[]), ExecuteMsg::FundPot {}).unwrap_err();
+
+    let state: State =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.native_pot, vec![coin(150, "uatom"), coin(5, "ujuno")]);
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: staker_addr(0),
+            amount: vec![coin(150, "uatom"), coin(5, "ujuno")],
+        })
+    );
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert!(state.native_pot.is_empty());
+}
+
+#[test]
+fn each_stake_pays_the_stake_fee_into_the_native_pot() {
+    let fixture = TestFixture::new().configure(|msg| msg.stake_fee = Some(coin(10, "ujuno")));
+    let (mut deps, env, _) = fixture.build();
+    let stake = ExecuteMsg::Stake {
+        token_ids: vec![],
+        lock_duration: None,
+    };
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), stake.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+    let underpaid = mock_info("alice", &[coin(5, "ujuno")]);
+    let err = execute(deps.as_mut(), env.clone(), underpaid, stake.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WrongStakeFee {
+            expected: coin(10, "ujuno")
+        }
+    );
+    let paid = mock_info("alice", &[coin(10, "ujuno")]);
+    execute(deps.as_mut(), env.clone(), paid, stake).unwrap();
+
+    // SendNft has no way to attach the fee
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(NFT_CONTRACT, &[]),
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "alice".to_string(),
+            token_id: "7".to_string(),
+            msg: Binary::default(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::StakeFeeUnpaid {});
+
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.native_pot, vec![coin(10, "ujuno")]);
+}
+
+#[test]
+fn funded_rewards_stream_to_stakers_by_voting_power() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .configure(|msg| msg.emission_per_second = Uint128::new(10));
+    let (mut deps, mut env, infos) = fixture.build();
+    let fund_rewards = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: staker_addr(0),
+        amount: Uint128::new(1_000),
+        msg: to_json_binary(&ReceiveMsg::FundRewards {}).unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info(REWARD_TOKEN, &[]), fund_rewards).unwrap();
+
+    // 500 streamed over 50 seconds, shared by two stakers of equal weight
+    env.block.time = env.block.time.plus_seconds(50);
+    let rewards: StakingRewardsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStakingRewards {
+                address: staker_addr(0),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(rewards.pending, Uint128::new(250));
+    assert_eq!(rewards.reserve, Uint128::new(500));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::ClaimStakingRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: staker_addr(0),
+                amount: Uint128::new(250),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::ClaimStakingRewards {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoStakingRewards {});
+
+    // The stream stops once the reserve runs dry
+    env.block.time = env.block.time.plus_seconds(100);
+    let rewards: StakingRewardsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetStakingRewards {
+                address: staker_addr(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(rewards.pending, Uint128::new(500));
+    assert_eq!(rewards.reserve, Uint128::zero());
+}
+
+fn pending_rewards(deps: Deps, env: Env, index: usize) -> Uint128 {
+    let rewards: StakingRewardsResponse = from_json(
+        query(
+            deps,
+            env,
+            QueryMsg::GetStakingRewards {
+                address: staker_addr(index),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    rewards.pending
+}
+
+#[test]
+fn stakers_from_before_checkpointing_earn_by_their_weight() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .configure(|msg| msg.emission_per_second = Uint128::new(10));
+    let (mut deps, mut env, _) = fixture.build();
+    clear_power_checkpoints(deps.as_mut().storage);
+    migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            skip_version_check: true,
+        },
+    )
+    .unwrap();
+    let fund_rewards = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: staker_addr(0),
+        amount: Uint128::new(1_000),
+        msg: to_json_binary(&ReceiveMsg::FundRewards {}).unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info(REWARD_TOKEN, &[]), fund_rewards).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(50);
+    assert_eq!(pending_rewards(deps.as_ref(), env.clone(), 0), Uint128::new(250));
+
+    // Growing the position settles what the old weight earned before checkpointing it
+    let stake = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: staker_addr(0),
+        token_id: "1".to_string(),
+        msg: Binary::default(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), stake).unwrap();
+
+    // 300 more over 30 seconds, now split two to one
+    env.block.time = env.block.time.plus_seconds(30);
+    assert_eq!(pending_rewards(deps.as_ref(), env.clone(), 0), Uint128::new(450));
+    assert_eq!(pending_rewards(deps.as_ref(), env.clone(), 1), Uint128::new(350));
+}
@@ -0,0 +1,132 @@
+// Storage operation counts for the main execute paths at different pool sizes.
+// Run with `cargo test --test gas_bench -- --nocapture --ignored`.
+
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::Uint128;
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg};
+use nft_staking_lottery::testing::{draw, with_counting_storage, TestFixture};
+
+fn report(name: &str, stakers: u32, reads: u64, writes: u64) {
+    println!(
+        "{:<16} stakers={:<6} reads={:<6} writes={:<6}",
+        name, stakers, reads, writes
+    );
+}
+
+fn bench_stake(stakers: u32) {
+    let fixture = TestFixture::new().with_stakers(stakers);
+    let (deps, env, _) = fixture.build();
+    let mut deps = with_counting_storage(deps);
+
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("newcomer", &[]),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    report("stake", stakers, deps.storage.reads(), deps.storage.writes());
+}
+
+fn bench_unstake(stakers: u32) {
+    let fixture = TestFixture::new().with_stakers(stakers).advance_days(8);
+    let (deps, env, infos) = fixture.build();
+    let mut deps = with_counting_storage(deps);
+
+    execute(deps.as_mut(), env, infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+    report("unstake", stakers, deps.storage.reads(), deps.storage.writes());
+}
+
+fn bench_draw_winner(stakers: u32) {
+    let fixture = TestFixture::new()
+        .with_stakers(stakers)
+        .with_pot(Uint128::new(1_000_000))
+        .advance_days(8);
+    let (deps, env, _) = fixture.build();
+    let mut deps = with_counting_storage(deps);
+
+    draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+    report("draw_winner", stakers, deps.storage.reads(), deps.storage.writes());
+}
+
+fn bench_claim_reward(stakers: u32) {
+    let fixture = TestFixture::new()
+        .with_stakers(stakers)
+        .with_pot(Uint128::new(1_000_000))
+        .advance_days(8);
+    let (deps, env, _) = fixture.build();
+    let mut deps = with_counting_storage(deps);
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winner")
+        .unwrap()
+        .value
+        .clone();
+
+    deps.storage.reset();
+    execute(deps.as_mut(), env, mock_info(&winner, &[]), ExecuteMsg::ClaimReward {}).unwrap();
+    report("claim_reward", stakers, deps.storage.reads(), deps.storage.writes());
+}
+
+fn bench_eligible_stakers(stakers: u32) {
+    let fixture = TestFixture::new().with_stakers(stakers).advance_days(8);
+    let (deps, env, _) = fixture.build();
+    let deps = with_counting_storage(deps);
+
+    query(deps.as_ref(), env, QueryMsg::GetEligibleStakers { start_after: None, limit: None }).unwrap();
+    report("eligible_stakers", stakers, deps.storage.reads(), deps.storage.writes());
+}
+
+macro_rules! benches {
+    ($bench:ident: $($name:ident => $stakers:expr),+ $(,)?) => {
+        $(
+            #[ignore]
+            #[test]
+            fn $name() {
+                $bench($stakers);
+            }
+        )+
+    };
+}
+
+benches!(bench_stake:
+    bench_stake_10_stakers => 10,
+    bench_stake_100_stakers => 100,
+    bench_stake_1000_stakers => 1_000,
+    bench_stake_10000_stakers => 10_000,
+);
+
+benches!(bench_unstake:
+    bench_unstake_10_stakers => 10,
+    bench_unstake_100_stakers => 100,
+    bench_unstake_1000_stakers => 1_000,
+    bench_unstake_10000_stakers => 10_000,
+);
+
+benches!(bench_draw_winner:
+    bench_draw_winner_10_stakers => 10,
+    bench_draw_winner_100_stakers => 100,
+    bench_draw_winner_1000_stakers => 1_000,
+    bench_draw_winner_10000_stakers => 10_000,
+);
+
+benches!(bench_claim_reward:
+    bench_claim_reward_10_stakers => 10,
+    bench_claim_reward_100_stakers => 100,
+    bench_claim_reward_1000_stakers => 1_000,
+    bench_claim_reward_10000_stakers => 10_000,
+);
+
+benches!(bench_eligible_stakers:
+    bench_eligible_stakers_10_stakers => 10,
+    bench_eligible_stakers_100_stakers => 100,
+    bench_eligible_stakers_1000_stakers => 1_000,
+    bench_eligible_stakers_10000_stakers => 10_000,
+);
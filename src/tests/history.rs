@@ -0,0 +1,161 @@
+use cosmwasm_std::{from_json, Uint128};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg, WinnerRecord};
+use nft_staking_lottery::state::{Round, StakeEvent, StakeEventType};
+use nft_staking_lottery::testing::{draw, staker_addr, TestFixture};
+
+#[test]
+fn history_records_stakes_and_unstakes_in_order() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+
+    let history = |start_after: Option<u64>| -> Vec<(u64, StakeEvent)> {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetStakerHistory {
+                    address: staker_addr(0),
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let events = history(None);
+    let summary: Vec<(StakeEventType, u64)> = events
+        .iter()
+        .map(|(_, event)| (event.event_type.clone(), event.nft_count_after))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (StakeEventType::Staked, 1),
+            (StakeEventType::Staked, 2),
+            (StakeEventType::Unstaked, 1),
+        ]
+    );
+
+    // staker1's stake took a sequence number between staker0's events
+    let after_first = history(Some(events[0].0));
+    assert_eq!(after_first.len(), 2);
+    assert_eq!(after_first[0].0, 2);
+}
+
+#[test]
+fn draws_are_recorded_as_rounds_that_events_refer_to() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(500))
+        .advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winner")
+        .unwrap()
+        .value
+        .clone();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    let round: Option<Round> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetRound { round_id: 1 }).unwrap())
+            .unwrap();
+    let round = round.unwrap();
+    assert_eq!(round.round_id, 1);
+    assert_eq!(round.pot, Uint128::new(500));
+    assert_eq!(round.winners, vec![(winner, Uint128::new(500))]);
+    assert_eq!(round.entry_count, 2);
+    assert_eq!(round.total_tickets, 2);
+    assert_eq!(round.started_at, None);
+    assert_eq!(round.drawn_at, env.block.time);
+    let not_drawn: Option<Round> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetRound { round_id: 2 }).unwrap())
+            .unwrap();
+    assert_eq!(not_drawn, None);
+
+    let events: Vec<(u64, StakeEvent)> = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetStakerHistory {
+                address: staker_addr(0),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let rounds: Vec<u64> = events
+        .iter()
+        .filter(|(_, event)| event.event_type == StakeEventType::Staked)
+        .map(|(_, event)| event.round_id)
+        .collect();
+    // Staked before and after the first draw
+    assert_eq!(rounds, vec![1, 2]);
+}
+
+#[test]
+fn winners_are_listed_newest_first() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(500))
+        .advance_days(8);
+    let (mut deps, mut env, _) = fixture.build();
+
+    let mut winners = vec![];
+    for _ in 0..3 {
+        let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+        let winner = res.attributes.iter().find(|attr| attr.key == "winner").unwrap().value.clone();
+        winners.push((winner, env.block.time));
+        env.block.time = env.block.time.plus_days(8);
+    }
+
+    let list = |start_after: Option<u64>| -> Vec<WinnerRecord> {
+        from_json(
+            query(deps.as_ref(), env.clone(), QueryMsg::ListWinners { start_after, limit: Some(2) })
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let page = list(None);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0].round_id, 3);
+    assert_eq!(page[0].winner, winners[2].0);
+    assert_eq!(page[0].timestamp, winners[2].1);
+    // Only the first round had a pot to win
+    assert_eq!(page[1].prize, Uint128::zero());
+    let page = list(Some(2));
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].round_id, 1);
+    assert_eq!(page[0].winner, winners[0].0);
+    assert_eq!(page[0].prize, Uint128::new(500));
+    assert_eq!(page[0].timestamp, winners[0].1);
+}
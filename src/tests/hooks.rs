@@ -0,0 +1,123 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, to_json_binary, Addr, SubMsg, Uint128, WasmMsg};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg, StakeChangedExecuteMsg, StakeChangedHookMsg};
+use nft_staking_lottery::testing::{draw, staker_addr, TestFixture};
+use nft_staking_lottery::ContractError;
+
+fn hook_submsg(hook: &str, msg: StakeChangedHookMsg) -> SubMsg {
+    SubMsg::new(WasmMsg::Execute {
+        contract_addr: hook.to_string(),
+        msg: to_json_binary(&StakeChangedExecuteMsg::StakeChangeHook(msg)).unwrap(),
+        funds: vec![],
+    })
+}
+
+#[test]
+fn registered_hooks_hear_about_stakes_and_unstakes() {
+    let fixture = TestFixture::new().with_stakers(1).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    let add_hook = |addr: &str| ExecuteMsg::AddHook {
+        addr: addr.to_string(),
+    };
+    let err = execute(deps.as_mut(), env.clone(), infos[0].clone(), add_hook("distributor"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), add_hook("distributor")).unwrap();
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), add_hook("analytics")).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), add_hook("analytics"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HookAlreadyRegistered {
+            addr: "analytics".to_string()
+        }
+    );
+
+    let staker = Addr::unchecked(staker_addr(0));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    let staked = StakeChangedHookMsg::Stake {
+        addr: staker.clone(),
+        amount: Uint128::one(),
+    };
+    assert_eq!(
+        res.messages,
+        vec![hook_submsg("distributor", staked.clone()), hook_submsg("analytics", staked)]
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::RemoveHook {
+            addr: "distributor".to_string(),
+        },
+    )
+    .unwrap();
+    let hooks: Vec<Addr> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetHooks {}).unwrap()).unwrap();
+    assert_eq!(hooks, vec![Addr::unchecked("analytics")]);
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info(staker.as_str(), &[]), ExecuteMsg::Unstake {})
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![hook_submsg(
+            "analytics",
+            StakeChangedHookMsg::Unstake {
+                addr: staker,
+                amount: Uint128::one(),
+            }
+        )]
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        fixture.as_admin(),
+        ExecuteMsg::RemoveHook {
+            addr: "distributor".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HookNotRegistered {
+            addr: "distributor".to_string()
+        }
+    );
+}
+
+#[test]
+fn compounded_prizes_reach_the_hooks_as_stakes() {
+    let fixture = TestFixture::new()
+        .with_stakers(1)
+        .with_pot(Uint128::new(1_050))
+        .advance_days(8)
+        .configure(|msg| msg.compound_rate = Uint128::new(100));
+    let (mut deps, env, infos) = fixture.build();
+    let add_hook = ExecuteMsg::AddHook {
+        addr: "distributor".to_string(),
+    };
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), add_hook).unwrap();
+    let auto_compound = ExecuteMsg::SetAutoCompound { enabled: true };
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), auto_compound).unwrap();
+
+    // The 1_000 that buys ten tickets adds them to the position like a stake
+    let res = draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+    let compounded = StakeChangedHookMsg::Stake {
+        addr: Addr::unchecked(staker_addr(0)),
+        amount: Uint128::new(10),
+    };
+    assert!(res.messages.contains(&hook_submsg("distributor", compounded)));
+}
@@ -0,0 +1,59 @@
+use cosmwasm_std::from_json;
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, IntervalVotesResponse, QueryMsg};
+use nft_staking_lottery::testing::{draw, TestFixture};
+
+#[test]
+fn draw_adopts_the_weighted_median_vote() {
+    let fixture = TestFixture::new().with_stakers(3).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    // staker0 holds 4 of the 6 staked NFTs
+    for _ in 0..3 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            infos[0].clone(),
+            ExecuteMsg::Stake {
+                token_ids: vec![],
+                lock_duration: None,
+            },
+        )
+        .unwrap();
+    }
+    for (info, seconds) in infos.iter().zip([7_200, 86_400, 172_800]) {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::VoteDrawInterval {
+                preferred_seconds: seconds,
+            },
+        )
+        .unwrap();
+    }
+
+    draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+
+    let votes: IntervalVotesResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetIntervalVotes {}).unwrap()).unwrap();
+    assert_eq!(votes.histogram, vec![(7_200, 4), (86_400, 1), (172_800, 1)]);
+    assert_eq!(votes.total_weight, 6);
+    assert_eq!(votes.next_draw_interval_seconds, 7_200);
+}
+
+#[test]
+fn votes_outside_the_configured_range_are_rejected() {
+    let fixture = TestFixture::new().with_stakers(1);
+    let (mut deps, env, infos) = fixture.build();
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        infos[0].clone(),
+        ExecuteMsg::VoteDrawInterval {
+            preferred_seconds: 60,
+        },
+    );
+    assert!(res.is_err());
+}
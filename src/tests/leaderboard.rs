@@ -0,0 +1,73 @@
+use cosmwasm_std::{from_json, Uint128};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, LeaderboardEntry, QueryMsg};
+use nft_staking_lottery::state::{BoundedLeaderboard, LEADERBOARD_SIZE};
+use nft_staking_lottery::testing::{draw, staker_addr, TestFixture};
+
+#[test]
+fn leaderboard_keeps_one_entry_per_address_sorted_descending() {
+    let mut leaderboard = BoundedLeaderboard::default();
+    leaderboard.record("alice".to_string(), Uint128::new(100));
+    leaderboard.record("bob".to_string(), Uint128::new(300));
+    leaderboard.record("alice".to_string(), Uint128::new(500));
+
+    assert_eq!(
+        leaderboard.entries,
+        vec![
+            ("alice".to_string(), Uint128::new(500)),
+            ("bob".to_string(), Uint128::new(300)),
+        ]
+    );
+}
+
+#[test]
+fn leaderboard_is_bounded() {
+    let mut leaderboard = BoundedLeaderboard::default();
+    for index in 0..LEADERBOARD_SIZE as u128 + 10 {
+        leaderboard.record(format!("winner{}", index), Uint128::new(index));
+    }
+
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+    assert_eq!(leaderboard.entries[0].1, Uint128::new(LEADERBOARD_SIZE as u128 + 9));
+}
+
+#[test]
+fn leaderboard_ranks_stakers_by_weight_with_their_wins() {
+    let fixture = TestFixture::new()
+        .with_stakers(3)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    for _ in 0..2 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            fixture.as_staker(2),
+            ExecuteMsg::Stake {
+                token_ids: vec![],
+                lock_duration: None,
+            },
+        )
+        .unwrap();
+    }
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winner")
+        .unwrap()
+        .value
+        .clone();
+
+    let leaderboard: Vec<LeaderboardEntry> = from_json(
+        query(deps.as_ref(), env, QueryMsg::Leaderboard { limit: Some(2) }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(leaderboard.len(), 2);
+    assert_eq!(leaderboard[0].address, staker_addr(2));
+    assert_eq!((leaderboard[0].nft_count, leaderboard[0].weight), (3, 3));
+    assert_eq!(leaderboard[1].weight, 1);
+    for entry in &leaderboard {
+        assert_eq!(entry.wins, (entry.address == winner) as u32);
+    }
+}
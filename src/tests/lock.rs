@@ -0,0 +1,229 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, to_json_binary, Binary, Uint128};
+use cw721::Cw721ReceiveMsg;
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{
+    ExecuteMsg, QueryMsg, ReceiveNftMsg, StakedToken, VotingPowerAtHeightResponse,
+};
+use nft_staking_lottery::state::{LockTier, TokenLock};
+use nft_staking_lottery::testing::{
+    assert_invariants, mock_reward_balance, TestFixture, NFT_CONTRACT,
+};
+use nft_staking_lottery::ContractError;
+
+const DAY: u64 = 86_400;
+
+fn send_nft(token_id: &str, lock_duration: Option<u64>) -> ExecuteMsg {
+    let msg = match lock_duration {
+        Some(_) => to_json_binary(&ReceiveNftMsg { lock_duration }).unwrap(),
+        None => Binary::default(),
+    };
+    ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "alice".to_string(),
+        token_id: token_id.to_string(),
+        msg,
+    })
+}
+
+fn unstake(token_id: &str) -> ExecuteMsg {
+    ExecuteMsg::UnstakeToken {
+        collection: None,
+        token_id: token_id.to_string(),
+    }
+}
+
+fn lock_tiers() -> Vec<LockTier> {
+    vec![
+        LockTier {
+            duration_seconds: 7 * DAY,
+            multiplier_bps: 10_000,
+        },
+        LockTier {
+            duration_seconds: 30 * DAY,
+            multiplier_bps: 15_000,
+        },
+        LockTier {
+            duration_seconds: 90 * DAY,
+            multiplier_bps: 30_000,
+        },
+    ]
+}
+
+#[test]
+fn locked_tokens_count_more_and_stay_until_unlocked() {
+    let fixture = TestFixture::new().configure(|msg| msg.lock_tiers = lock_tiers());
+    let (mut deps, mut env, _) = fixture.build();
+    let nft = mock_info(NFT_CONTRACT, &[]);
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        nft.clone(),
+        send_nft("1", Some(DAY)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnknownLockTier { duration: DAY });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        nft.clone(),
+        send_nft("1", Some(90 * DAY)),
+    )
+    .unwrap();
+    execute(deps.as_mut(), env.clone(), nft, send_nft("2", None)).unwrap();
+    let staked_at = env.block.time;
+
+    env.block.time = env.block.time.plus_seconds(8 * DAY);
+    env.block.height += 1;
+    let tokens: Vec<StakedToken> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStakedTokens {
+                address: "alice".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tokens[0].weight, 3);
+    assert!(!tokens[0].eligible);
+    assert_eq!(
+        tokens[0].lock,
+        Some(TokenLock {
+            duration_seconds: 90 * DAY,
+            unlock_at: staked_at.plus_seconds(90 * DAY),
+            base_weight: 1,
+        })
+    );
+    assert_eq!(tokens[1].weight, 1);
+    assert!(tokens[1].eligible);
+    let power: VotingPowerAtHeightResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VotingPowerAtHeight {
+                address: "alice".to_string(),
+                height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(power.power, Uint128::new(4));
+
+    let alice = mock_info("alice", &[]);
+    let err = execute(deps.as_mut(), env.clone(), alice.clone(), unstake("1")).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TokenLocked {
+            token_id: "1".to_string(),
+            remaining_secs: 82 * DAY,
+        }
+    );
+    execute(deps.as_mut(), env.clone(), alice.clone(), unstake("2")).unwrap();
+
+    env.block.time = staked_at.plus_seconds(90 * DAY);
+    execute(deps.as_mut(), env, alice, unstake("1")).unwrap();
+}
+
+#[test]
+fn lock_tiers_are_checked_when_set() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+
+    let mut tiers = lock_tiers();
+    tiers.swap(0, 1);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::SetLockTiers { tiers },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidLockTiers {});
+
+    let set_tiers = ExecuteMsg::SetLockTiers {
+        tiers: lock_tiers(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        set_tiers.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), set_tiers).unwrap();
+
+    // Wallet stakes have no token to hold
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("alice", &[]),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: Some(30 * DAY),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::LockRequiresCustody {});
+}
+
+#[test]
+fn extending_a_lock_moves_up_a_tier_and_keeps_staked_at() {
+    let fixture = TestFixture::new().configure(|msg| msg.lock_tiers = lock_tiers());
+    let (mut deps, mut env, _) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(NFT_CONTRACT, &[]),
+        send_nft("1", Some(30 * DAY)),
+    )
+    .unwrap();
+    let staked_at = env.block.time;
+    let extend = |new_duration: u64| ExecuteMsg::ExtendLock {
+        collection: None,
+        token_ids: vec!["1".to_string()],
+        new_duration,
+    };
+    let alice = mock_info("alice", &[]);
+
+    env.block.time = env.block.time.plus_seconds(10 * DAY);
+    let err = execute(deps.as_mut(), env.clone(), alice.clone(), extend(7 * DAY)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::LockNotExtended {
+            token_id: "1".to_string()
+        }
+    );
+    let res = execute(deps.as_mut(), env.clone(), alice, extend(90 * DAY)).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "added_weight" && attr.value == "2"));
+
+    let tokens: Vec<StakedToken> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStakedTokens {
+                address: "alice".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tokens[0].staked_at, staked_at);
+    assert_eq!(tokens[0].weight, 3);
+    assert_eq!(
+        tokens[0].lock.as_ref().unwrap().unlock_at,
+        env.block.time.plus_seconds(90 * DAY)
+    );
+    mock_reward_balance(&mut deps.querier, Uint128::zero());
+    assert_invariants(deps.as_mut(), &env);
+}
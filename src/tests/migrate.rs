@@ -0,0 +1,276 @@
+use cosmwasm_std::{from_json, to_json_vec, Addr, Deps, Order, Storage, Timestamp, Uint128};
+use cw_storage_plus::Map;
+use cw_utils::Duration;
+use nft_staking_lottery::testing::{
+    assert_invariants, downgrade_to_v1, mock_reward_balance, staker_addr, TestFixture,
+    NFT_CONTRACT,
+};
+use nft_staking_lottery::contract::{execute, migrate, query};
+use nft_staking_lottery::msg::{
+    ConfigResponse, ContractInfoResponse, ExecuteMsg, InvariantReport, MigrateMsg, QueryMsg,
+    StakedToken,
+};
+use nft_staking_lottery::state::{Staker, State};
+
+#[test]
+fn migrate_drops_the_v1_staker_hash_set() {
+    let fixture = TestFixture::new()
+        .with_stakers(3)
+        .with_pot(Uint128::new(500));
+    let (mut deps, env, _) = fixture.build();
+    downgrade_to_v1(&mut deps.storage);
+    // Membership map written by earlier versions alongside the positions
+    let staker_set: Map<String, bool> = Map::new("staker_set");
+    staker_set.save(&mut deps.storage, staker_addr(0), &true).unwrap();
+
+    let res = migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            skip_version_check: true,
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "staker_set_cleared" && attr.value == "1"));
+    assert!(staker_set.is_empty(&deps.storage));
+
+    let state: State =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.staker_count, 3);
+    assert_eq!(state.total_staked, 3);
+    assert_eq!(state.current_pot, Uint128::new(500));
+
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStaker {
+                address: staker_addr(0),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.unwrap().nft_count, 1);
+
+    mock_reward_balance(&mut deps.querier, Uint128::new(500));
+    assert_invariants(deps.as_mut(), &env);
+}
+
+#[test]
+fn migrate_without_stored_version_needs_skip() {
+    let (mut deps, env, _) = TestFixture::new().with_stakers(1).build();
+    downgrade_to_v1(&mut deps.storage);
+
+    let err = migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            skip_version_check: false,
+        },
+    );
+    assert!(err.is_err());
+}
+
+#[test]
+fn validate_invariants_reports_unfunded_pot() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(1_000));
+    let (mut deps, env, _) = fixture.build();
+    mock_reward_balance(&mut deps.querier, Uint128::new(999));
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        fixture.as_admin(),
+        ExecuteMsg::ValidateStateInvariants {},
+    )
+    .unwrap();
+    let report: InvariantReport = from_json(res.data.unwrap()).unwrap();
+    assert!(!report.passed);
+    assert_eq!(report.details.len(), 1);
+}
+
+#[test]
+fn instantiate_records_contract_info() {
+    let (deps, env, _) = TestFixture::new().build();
+
+    let info: ContractInfoResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::ContractInfo {}).unwrap()).unwrap();
+    assert_eq!(info.name, "crates.io:burn-at-the-stake");
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn migrate_refuses_a_different_contract() {
+    let (mut deps, env, _) = TestFixture::new().build();
+    cw2::set_contract_version(&mut deps.storage, "crates.io:other-contract", "1.0.0").unwrap();
+
+    let err = migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            skip_version_check: false,
+        },
+    );
+    assert!(err.is_err());
+}
+
+#[test]
+fn migrate_refuses_a_newer_stored_version() {
+    let (mut deps, env, _) = TestFixture::new().build();
+    cw2::set_contract_version(&mut deps.storage, "crates.io:burn-at-the-stake", "99.0.0").unwrap();
+
+    let err = migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            skip_version_check: false,
+        },
+    );
+    assert!(err.is_err());
+}
+
+#[test]
+fn migrate_reports_the_version_it_upgraded_from() {
+    let (mut deps, env, _) = TestFixture::new().build();
+    cw2::set_contract_version(&mut deps.storage, "crates.io:burn-at-the-stake", "1.0.0").unwrap();
+
+    let res = migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            skip_version_check: false,
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "previous_version" && attr.value == "1.0.0"));
+    assert_eq!(
+        cw2::get_contract_version(&deps.storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[test]
+fn migrate_files_single_collection_custody_under_nft_contract() {
+    let (mut deps, env, _) = TestFixture::new().build();
+    let legacy: Map<(&Addr, &str), Timestamp> = Map::new("staked_nfts");
+    legacy
+        .save(&mut deps.storage, (&Addr::unchecked("alice"), "7"), &env.block.time)
+        .unwrap();
+
+    let res = migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            skip_version_check: true,
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "custody_tokens_migrated" && attr.value == "1"));
+
+    let tokens: Vec<StakedToken> = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetStakedTokens {
+                address: "alice".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].collection, Addr::unchecked(NFT_CONTRACT));
+    assert_eq!(tokens[0].token_id, "7");
+    assert_eq!(tokens[0].weight, 1);
+}
+
+#[test]
+fn migrate_converts_min_staking_days_into_a_period() {
+    let (mut deps, env, _) = TestFixture::new().build();
+    // Config as saved before min_staking_period existed
+    let mut config: serde_json::Value =
+        from_json(deps.storage.get(b"config").unwrap()).unwrap();
+    let fields = config.as_object_mut().unwrap();
+    fields.remove("min_staking_period");
+    fields.insert("min_staking_days".to_string(), 2.into());
+    deps.storage.set(b"config", &to_json_vec(&config).unwrap());
+
+    let res = migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            skip_version_check: true,
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "min_staking_period_converted" && attr.value == "true"));
+    let config: ConfigResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetConfig {}).unwrap()).unwrap();
+    assert_eq!(config.config.min_staking_period, Duration::Time(2 * 86_400));
+}
+
+#[test]
+fn migrate_indexes_positions_saved_before_the_staked_at_index() {
+    let (mut deps, env, _) = TestFixture::new().with_stakers(3).advance_days(8).build();
+    // Drop the index entries, as on a contract from before the index. Map namespaces
+    // are stored behind a two byte length prefix.
+    let namespace = b"staker_positions__staked_at";
+    let start = [&(namespace.len() as u16).to_be_bytes()[..], namespace].concat();
+    let mut end = start.clone();
+    *end.last_mut().unwrap() += 1;
+    let index_keys: Vec<Vec<u8>> = deps
+        .storage
+        .range(Some(&start), Some(&end), Order::Ascending)
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(index_keys.len(), 3);
+    for key in &index_keys {
+        deps.storage.remove(key);
+    }
+    let eligible = |deps: Deps| -> Vec<(String, Staker)> {
+        from_json(
+            query(
+                deps,
+                env.clone(),
+                QueryMsg::GetEligibleStakers {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert!(eligible(deps.as_ref()).is_empty());
+
+    let res = migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            skip_version_check: true,
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "stakers_indexed" && attr.value == "3"));
+    assert_eq!(eligible(deps.as_ref()).len(), 3);
+}
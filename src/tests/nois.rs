@@ -0,0 +1,137 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{
+    coins, from_json, to_json_binary, CosmosMsg, HexBinary, Timestamp, Uint128, WasmMsg,
+};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{
+    ExecuteMsg, NoisCallback, NoisProxyExecuteMsg, QueryMsg, SimulateDrawResponse,
+};
+use nft_staking_lottery::state::State;
+use nft_staking_lottery::testing::{TestFixture, ADMIN};
+use nft_staking_lottery::ContractError;
+
+const NOIS_PROXY: &str = "nois_proxy";
+
+fn callback(job_id: &str, randomness: &HexBinary) -> ExecuteMsg {
+    ExecuteMsg::NoisReceive {
+        callback: NoisCallback {
+            job_id: job_id.to_string(),
+            published: Timestamp::from_seconds(0),
+            randomness: randomness.clone(),
+        },
+    }
+}
+
+#[test]
+fn draw_waits_for_the_nois_callback() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.nois_proxy = Some(NOIS_PROXY.to_string()))
+        .with_stakers(3)
+        .with_pot(Uint128::new(900))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    let payment = coins(50, "unois");
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADMIN, &payment),
+        ExecuteMsg::DrawWinner {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: NOIS_PROXY.to_string(),
+            msg: to_json_binary(&NoisProxyExecuteMsg::GetNextRandomness {
+                job_id: "draw_1".to_string(),
+            })
+            .unwrap(),
+            funds: payment,
+        })
+    );
+    let err = execute(deps.as_mut(), env.clone(), fixture.as_admin(), ExecuteMsg::DrawWinner {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::DrawInProgress {});
+
+    let randomness = HexBinary::from(vec![7u8; 32]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("impostor", &[]),
+        callback("draw_1", &randomness),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(NOIS_PROXY, &[]),
+        callback("draw_2", &randomness),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownNoisJob {
+            job_id: "draw_2".to_string()
+        }
+    );
+
+    let simulated: SimulateDrawResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateDraw {
+                entropy_override: Some(randomness.to_vec().into()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(NOIS_PROXY, &[]),
+        callback("draw_1", &randomness),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "winner" && attr.value == simulated.would_win));
+
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.pending_draw_job, None);
+    assert_eq!(state.draw_count, 1);
+    assert_eq!(state.current_pot, Uint128::zero());
+}
+
+#[test]
+fn callback_with_no_eligible_stakers_cancels_the_draw() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.nois_proxy = Some(NOIS_PROXY.to_string()))
+        .with_stakers(1)
+        .advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), ExecuteMsg::DrawWinner {}).unwrap();
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(NOIS_PROXY, &[]),
+        callback("draw_1", &HexBinary::from(vec![1u8; 32])),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "draw_cancelled"));
+
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.pending_draw_job, None);
+    assert_eq!(state.draw_count, 0);
+}
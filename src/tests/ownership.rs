@@ -0,0 +1,83 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, Deps};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, OwnershipResponse, QueryMsg};
+use nft_staking_lottery::testing::{TestFixture, ADMIN, NFT_CONTRACT, REWARD_TOKEN};
+use nft_staking_lottery::ContractError;
+
+#[test]
+fn admin_changes_only_once_the_new_admin_accepts() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::TransferAdmin {
+            new_admin: "new_admin".to_string(),
+        },
+    )
+    .unwrap();
+
+    let ownership = |deps: Deps| -> OwnershipResponse {
+        from_json(query(deps, env.clone(), QueryMsg::GetOwnership {}).unwrap()).unwrap()
+    };
+    assert_eq!(
+        ownership(deps.as_ref()),
+        OwnershipResponse {
+            admin: ADMIN.to_string(),
+            pending_admin: Some("new_admin".to_string()),
+        }
+    );
+
+    let err =
+        execute(deps.as_mut(), env.clone(), mock_info("someone", &[]), ExecuteMsg::AcceptAdmin {})
+            .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(deps.as_mut(), env.clone(), mock_info("new_admin", &[]), ExecuteMsg::AcceptAdmin {})
+        .unwrap();
+    assert_eq!(
+        ownership(deps.as_ref()),
+        OwnershipResponse {
+            admin: "new_admin".to_string(),
+            pending_admin: None,
+        }
+    );
+
+    // The old admin has lost its rights
+    let err = execute(
+        deps.as_mut(),
+        env,
+        fixture.as_admin(),
+        ExecuteMsg::TransferAdmin {
+            new_admin: ADMIN.to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn set_config_only_proposes_a_new_admin() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::SetConfig {
+            admin: "typo_admin".to_string(),
+            nft_contract: NFT_CONTRACT.to_string(),
+            reward_token: REWARD_TOKEN.to_string(),
+        },
+    )
+    .unwrap();
+
+    let ownership: OwnershipResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetOwnership {}).unwrap()).unwrap();
+    assert_eq!(ownership.admin, ADMIN);
+    assert_eq!(ownership.pending_admin, Some("typo_admin".to_string()));
+}
@@ -0,0 +1,131 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, to_json_binary, Uint128};
+use cw20::Cw20ReceiveMsg;
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg, ReceiveMsg};
+use nft_staking_lottery::state::PausableAction;
+use nft_staking_lottery::testing::{draw, TestFixture, ADMIN, REWARD_TOKEN};
+use nft_staking_lottery::ContractError;
+
+#[test]
+fn paused_actions_are_rejected_while_others_keep_working() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    let pause = ExecuteMsg::Pause {
+        actions: vec![PausableAction::Stake, PausableAction::Fund],
+    };
+    let err = execute(deps.as_mut(), env.clone(), infos[0].clone(), pause.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), pause).unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            action: PausableAction::Stake
+        }
+    );
+    let fund = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: ADMIN.to_string(),
+        amount: Uint128::new(100),
+        msg: to_json_binary(&ReceiveMsg::FundPot {}).unwrap(),
+    });
+    let err = execute(deps.as_mut(), env.clone(), mock_info(REWARD_TOKEN, &[]), fund.clone())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            action: PausableAction::Fund
+        }
+    );
+    // Stakers can still leave
+    execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::Unpause {
+            actions: vec![PausableAction::Fund],
+        },
+    )
+    .unwrap();
+    let paused: Vec<PausableAction> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetPaused {}).unwrap()).unwrap();
+    assert_eq!(paused, vec![PausableAction::Stake]);
+    execute(deps.as_mut(), env, mock_info(REWARD_TOKEN, &[]), fund).unwrap();
+}
+
+#[test]
+fn an_empty_list_pauses_everything() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), ExecuteMsg::Pause { actions: vec![] })
+        .unwrap();
+    let err = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            action: PausableAction::Draw
+        }
+    );
+    let err = execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::Unstake {})
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            action: PausableAction::Unstake
+        }
+    );
+
+    execute(deps.as_mut(), env.clone(), fixture.as_admin(), ExecuteMsg::Unpause { actions: vec![] })
+        .unwrap();
+    draw(deps.as_mut(), env, fixture.as_admin()).unwrap();
+}
+
+#[test]
+fn abandonment_exits_and_position_settings_follow_their_actions() {
+    let fixture = TestFixture::new().with_stakers(1).advance_days(8);
+    let (mut deps, mut env, infos) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::Pause {
+            actions: vec![PausableAction::Stake, PausableAction::Unstake],
+        },
+    )
+    .unwrap();
+    // Long enough for the admin to count as gone
+    env.block.time = env.block.time.plus_seconds(365 * 86_400);
+
+    let err = execute(deps.as_mut(), env.clone(), infos[0].clone(), ExecuteMsg::AbandonmentExit {})
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            action: PausableAction::Unstake
+        }
+    );
+    for msg in [ExecuteMsg::SetAutoCompound { enabled: true }, ExecuteMsg::CheckIn {}] {
+        let err = execute(deps.as_mut(), env.clone(), infos[0].clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Paused {
+                action: PausableAction::Stake
+            }
+        );
+    }
+}
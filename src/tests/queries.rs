@@ -0,0 +1,276 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, Decimal, Deps, Env, Reply, SubMsgResponse, SubMsgResult, Uint128};
+use nft_staking_lottery::contract::{execute, query, reply};
+use nft_staking_lottery::msg::{
+    ConfigResponse, DashboardResponse, EligibleCountResponse, ExecuteMsg, OddsResponse, QueryMsg,
+    StatsResponse,
+};
+use nft_staking_lottery::state::Staker;
+use nft_staking_lottery::testing::{
+    draw, staker_addr, TestFixture, ADMIN, NFT_CONTRACT, REWARD_TOKEN,
+};
+use nft_staking_lottery::CLAIM_REPLY_ID;
+
+#[test]
+fn stakers_are_listed_in_pages() {
+    let fixture = TestFixture::new().with_stakers(5);
+    let (deps, env, _) = fixture.build();
+
+    let list = |start_after: Option<String>| -> Vec<(String, Staker)> {
+        from_json(
+            query(deps.as_ref(), env.clone(), QueryMsg::ListStakers { start_after, limit: Some(2) })
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let mut listed = vec![];
+    let mut start_after = None;
+    loop {
+        let page = list(start_after);
+        if page.is_empty() {
+            break;
+        }
+        assert!(page.len() <= 2);
+        start_after = page.last().map(|(address, _)| address.clone());
+        listed.extend(page.into_iter().map(|(address, staker)| {
+            assert_eq!(staker.nft_count, 1);
+            address
+        }));
+    }
+    let expected: Vec<String> = (0..5).map(staker_addr).collect();
+    assert_eq!(listed, expected);
+}
+
+#[test]
+fn stakers_are_listed_by_weight() {
+    let fixture = TestFixture::new().with_stakers(3);
+    let (mut deps, env, _) = fixture.build();
+    for (index, extra) in [(1, 2), (2, 1)] {
+        for _ in 0..extra {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                fixture.as_staker(index),
+                ExecuteMsg::Stake {
+                    token_ids: vec![],
+                    lock_duration: None,
+                },
+            )
+            .unwrap();
+        }
+    }
+
+    let list = |deps: Deps, descending: bool, limit: u32| -> Vec<(String, u64)> {
+        let stakers: Vec<(String, Staker)> = from_json(
+            query(
+                deps,
+                env.clone(),
+                QueryMsg::ListStakersByWeight {
+                    descending,
+                    limit: Some(limit),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        stakers
+            .into_iter()
+            .map(|(address, staker)| (address, staker.weight()))
+            .collect()
+    };
+    let heaviest = list(deps.as_ref(), true, 2);
+    assert_eq!(heaviest, vec![(staker_addr(1), 3), (staker_addr(2), 2)]);
+    assert_eq!(list(deps.as_ref(), false, 1), vec![(staker_addr(0), 1)]);
+
+    // The index follows the positions as they shrink
+    let mut later = env.clone();
+    later.block.time = later.block.time.plus_seconds(8 * 86_400);
+    for _ in 0..2 {
+        execute(deps.as_mut(), later.clone(), fixture.as_staker(1), ExecuteMsg::Unstake {})
+            .unwrap();
+    }
+    assert_eq!(list(deps.as_ref(), true, 1), vec![(staker_addr(2), 2)]);
+}
+
+#[test]
+fn config_is_returned_with_derived_values() {
+    let fixture = TestFixture::new().configure(|msg| msg.prize_split_bps = vec![7_000, 3_000]);
+    let (deps, env, _) = fixture.build();
+
+    let res: ConfigResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetConfig {}).unwrap()).unwrap();
+    assert_eq!(res.config.admin, ADMIN);
+    assert_eq!(res.config.nft_contract, NFT_CONTRACT);
+    assert_eq!(res.config.reward_token, REWARD_TOKEN);
+    // Seven days unless configured otherwise
+    assert_eq!(res.min_staking_seconds, 7 * 86_400);
+    assert_eq!(res.prize_places, vec![7_000, 3_000]);
+}
+
+#[test]
+fn eligible_stakers_are_paged_past_ineligible_ones() {
+    let fixture = TestFixture::new().with_stakers(4).advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    // staker1 and staker2 join now and are still inside the minimum period
+    for index in 1..3 {
+        execute(deps.as_mut(), env.clone(), fixture.as_staker(index), ExecuteMsg::Unstake {}).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            fixture.as_staker(index),
+            ExecuteMsg::Stake {
+                token_ids: vec![],
+                lock_duration: None,
+            },
+        )
+        .unwrap();
+    }
+
+    let list = |start_after: Option<String>| -> Vec<(String, Staker)> {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetEligibleStakers { start_after, limit: Some(1) },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let page = list(None);
+    assert_eq!(page[0].0, staker_addr(0));
+    let page = list(Some(staker_addr(0)));
+    assert_eq!(page[0].0, staker_addr(3));
+    assert!(list(Some(staker_addr(3))).is_empty());
+}
+
+#[test]
+fn eligible_count_skips_maturing_stakes() {
+    let fixture = TestFixture::new().with_stakers(4).advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_staker(0),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    execute(deps.as_mut(), env.clone(), fixture.as_staker(1), ExecuteMsg::Unstake {}).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_staker(1),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+
+    let res: EligibleCountResponse = from_json(
+        query(deps.as_ref(), env.clone(), QueryMsg::GetEligibleCount {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.eligible_stakers, 3);
+    // staker0 holds two NFTs
+    assert_eq!(res.total_tickets, 4);
+
+    let odds = |address: String| -> OddsResponse {
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetOdds { address }).unwrap())
+            .unwrap()
+    };
+    let res = odds(staker_addr(0));
+    assert_eq!((res.tickets, res.total_tickets), (2, 4));
+    assert_eq!(res.win_probability, Decimal::percent(50));
+    let res = odds(staker_addr(1));
+    assert_eq!((res.tickets, res.total_tickets), (0, 4));
+    assert_eq!(res.win_probability, Decimal::zero());
+}
+
+#[test]
+fn stats_sum_up_the_contract() {
+    let fixture = TestFixture::new()
+        .with_stakers(2)
+        .with_pot(Uint128::new(700))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+    // Leaving and coming back doesn't make a new staker
+    execute(deps.as_mut(), env.clone(), fixture.as_staker(0), ExecuteMsg::Unstake {}).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_staker(0),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winner")
+        .unwrap()
+        .value
+        .clone();
+    execute(deps.as_mut(), env.clone(), mock_info(&winner, &[]), ExecuteMsg::ClaimReward {})
+        .unwrap();
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: CLAIM_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+
+    let stats: StatsResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(
+        stats,
+        StatsResponse {
+            rounds_drawn: 1,
+            total_prizes_paid: Uint128::new(700),
+            current_pot: Uint128::zero(),
+            unique_stakers: 2,
+            nfts_staked: 2,
+            nfts_burned: 0,
+        }
+    );
+}
+
+#[test]
+fn dashboard_counts_down_to_eligibility() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(3);
+    let (deps, mut env, _) = fixture.build();
+    let dashboard = |env: &Env, address: String| -> DashboardResponse {
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Dashboard { address }).unwrap())
+            .unwrap()
+    };
+
+    let res = dashboard(&env, staker_addr(0));
+    assert_eq!(res.staker.unwrap().nft_count, 1);
+    assert_eq!(res.eligible_in_seconds, Some(4 * 86_400));
+    assert_eq!(res.tickets, 0);
+    assert!(res.staked_tokens.is_empty());
+    assert_eq!(res.pending_prize, None);
+    assert!(res.unbonding.is_empty());
+    assert_eq!(res.next_draw_time, None);
+
+    env.block.time = env.block.time.plus_seconds(5 * 86_400);
+    let res = dashboard(&env, staker_addr(0));
+    assert_eq!(res.eligible_in_seconds, Some(0));
+    assert_eq!(res.tickets, 1);
+
+    let res = dashboard(&env, "newcomer".to_string());
+    assert_eq!(res.staker, None);
+    assert_eq!(res.eligible_in_seconds, None);
+}
@@ -0,0 +1,127 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{to_json_binary, Binary, CosmosMsg, WasmMsg};
+use cw721::Cw721ReceiveMsg;
+use nft_staking_lottery::contract::execute;
+use nft_staking_lottery::msg::{ExecuteMsg, MintMsg, ReceiptExecuteMsg, StakeReceiptExt};
+use nft_staking_lottery::testing::{staker_addr, TestFixture, NFT_CONTRACT};
+
+fn receipt_msg(msg: &ReceiptExecuteMsg) -> CosmosMsg {
+    CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "receipts".to_string(),
+        msg: to_json_binary(msg).unwrap(),
+        funds: vec![],
+    })
+}
+
+#[test]
+fn receipts_are_minted_on_stake_and_burned_on_unstake() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.stake_receipt_contract = Some("receipts".to_string()))
+        .with_stakers(1)
+        .advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+    let staked_at = env.block.time.minus_seconds(8 * 86_400);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        receipt_msg(&ReceiptExecuteMsg::Mint(MintMsg {
+            token_id: format!("stake_{}_2", staker_addr(0)),
+            owner: staker_addr(0),
+            token_uri: None,
+            extension: StakeReceiptExt {
+                original_token_id: None,
+                staked_at,
+                nft_contract: NFT_CONTRACT.to_string(),
+            },
+        }))
+    );
+
+    let res = execute(deps.as_mut(), env, infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        receipt_msg(&ReceiptExecuteMsg::Burn {
+            token_id: format!("stake_{}_2", staker_addr(0)),
+        })
+    );
+}
+
+#[test]
+fn custody_receipts_stay_with_their_token() {
+    let fixture = TestFixture::new()
+        .configure(|msg| msg.stake_receipt_contract = Some("receipts".to_string()));
+    let (mut deps, mut env, _) = fixture.build();
+    let send_nft = |token_id: &str| {
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "alice".to_string(),
+            token_id: token_id.to_string(),
+            msg: Binary::default(),
+        })
+    };
+    let receipt_id = |token_id: &str| format!("stake_alice_{}_{}", NFT_CONTRACT, token_id);
+
+    let nft = mock_info(NFT_CONTRACT, &[]);
+    execute(deps.as_mut(), env.clone(), nft.clone(), send_nft("1")).unwrap();
+    env.block.time = env.block.time.plus_seconds(86_400);
+    let res = execute(deps.as_mut(), env.clone(), nft, send_nft("2")).unwrap();
+    // Dated by the token rather than the position it joined
+    assert_eq!(
+        res.messages[0].msg,
+        receipt_msg(&ReceiptExecuteMsg::Mint(MintMsg {
+            token_id: receipt_id("2"),
+            owner: "alice".to_string(),
+            token_uri: None,
+            extension: StakeReceiptExt {
+                original_token_id: Some("2".to_string()),
+                staked_at: env.block.time,
+                nft_contract: NFT_CONTRACT.to_string(),
+            },
+        }))
+    );
+
+    // Taking out the first token burns its receipt, not the one numbered by position
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("alice", &[]),
+        ExecuteMsg::UnstakeToken {
+            collection: None,
+            token_id: "1".to_string(),
+        },
+    )
+    .unwrap();
+    let burn = receipt_msg(&ReceiptExecuteMsg::Burn {
+        token_id: receipt_id("1"),
+    });
+    assert!(res.messages.iter().any(|sub| sub.msg == burn));
+}
+
+#[test]
+fn no_receipt_messages_without_a_receipt_contract() {
+    let fixture = TestFixture::new().with_stakers(1).advance_days(8);
+    let (mut deps, env, infos) = fixture.build();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    let res = execute(deps.as_mut(), env, infos[0].clone(), ExecuteMsg::Unstake {}).unwrap();
+    assert!(res.messages.is_empty());
+}
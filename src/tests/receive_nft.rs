@@ -0,0 +1,611 @@
+use cosmwasm_std::testing::{mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, ContractResult, CosmosMsg, Deps, Env, OwnedDeps,
+    Reply, Response, SubMsgResponse, SubMsgResult, SystemError, SystemResult, WasmMsg,
+    WasmQuery,
+};
+use cw721::{
+    Approval, ApprovalResponse, Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg, Expiration,
+    OwnerOfResponse,
+};
+use nft_staking_lottery::contract::{execute, query, reply};
+use nft_staking_lottery::msg::{
+    EligibilityProof, EligibleInResponse, ExecuteMsg, QueryMsg, StakeCapResponse, StakedToken,
+    TicketsResponse,
+};
+use nft_staking_lottery::state::{StakeEvent, Staker, UnbondingNft};
+use nft_staking_lottery::testing::{TestFixture, NFT_CONTRACT};
+use nft_staking_lottery::{ContractError, PULL_REPLY_ID};
+
+fn send_nft(token_id: &str) -> ExecuteMsg {
+    ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "alice".to_string(),
+        token_id: token_id.to_string(),
+        msg: Binary::default(),
+    })
+}
+
+#[test]
+fn nfts_sent_from_the_collection_are_staked_for_the_sender() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("7")).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("8")).unwrap();
+
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStaker {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.unwrap().nft_count, 2);
+
+    let history: Vec<(u64, StakeEvent)> = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetStakerHistory {
+                address: "alice".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let token_ids: Vec<_> = history.into_iter().map(|(_, event)| event.token_id).collect();
+    assert_eq!(token_ids, vec![Some("7".to_string()), Some("8".to_string())]);
+}
+
+#[test]
+fn eligible_in_counts_down_per_token() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+    let day = 86_400;
+
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("7")).unwrap();
+    env.block.time = env.block.time.plus_seconds(3 * day);
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("8")).unwrap();
+
+    let eligible_in = |env: &Env, address: &str| -> EligibleInResponse {
+        let msg = QueryMsg::EligibleIn {
+            address: address.to_string(),
+        };
+        from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+    };
+    let seconds = |res: &EligibleInResponse| -> Vec<(String, u64)> {
+        res.tokens
+            .iter()
+            .map(|token| (token.token_id.clone(), token.seconds))
+            .collect()
+    };
+
+    let res = eligible_in(&env, "alice");
+    assert_eq!(res.seconds, Some(4 * day));
+    assert_eq!(seconds(&res), vec![("7".to_string(), 4 * day), ("8".to_string(), 7 * day)]);
+
+    env.block.time = env.block.time.plus_seconds(5 * day);
+    let res = eligible_in(&env, "alice");
+    assert_eq!(res.seconds, Some(0));
+    assert_eq!(seconds(&res), vec![("7".to_string(), 0), ("8".to_string(), 2 * day)]);
+
+    let res = eligible_in(&env, "bob");
+    assert_eq!(res.seconds, None);
+    assert!(res.tokens.is_empty());
+}
+
+#[test]
+fn nfts_from_other_collections_are_rejected() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+
+    let err = execute(deps.as_mut(), env, mock_info("other_collection", &[]), send_nft("7"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownCollection {
+            collection: "other_collection".to_string(),
+        }
+    );
+}
+
+#[test]
+fn one_address_cannot_stake_past_the_cap() {
+    let fixture = TestFixture::new().configure(|msg| msg.max_nfts_per_staker = 2);
+    let (mut deps, env, _) = fixture.build();
+    let cap = |deps: Deps| -> StakeCapResponse {
+        let msg = QueryMsg::GetStakeCap {
+            address: "alice".to_string(),
+        };
+        from_json(query(deps, env.clone(), msg).unwrap()).unwrap()
+    };
+
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("7")).unwrap();
+    assert_eq!(cap(deps.as_ref()).remaining, Some(1));
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("8")).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("9"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::StakeCapExceeded { max: 2 });
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::StakeCapExceeded { max: 2 });
+
+    let res = cap(deps.as_ref());
+    assert_eq!((res.max_nfts_per_staker, res.nft_count, res.remaining), (2, 2, Some(0)));
+}
+
+#[test]
+fn receive_nft_matches_the_cw721_wire_format() {
+    let msg = Cw721ReceiveMsg {
+        sender: "alice".to_string(),
+        token_id: "7".to_string(),
+        msg: Binary::default(),
+    };
+    let parsed: ExecuteMsg = from_json(msg.clone().into_binary().unwrap()).unwrap();
+    assert_eq!(parsed, ExecuteMsg::ReceiveNft(msg));
+}
+
+#[test]
+fn unstake_returns_the_latest_token_to_its_owner() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("8")).unwrap();
+    env.block.time = env.block.time.plus_seconds(60);
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("7")).unwrap();
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+
+    let returned = |res: Response| res.messages[0].msg.clone();
+    let transfer = |token_id: &str| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: NFT_CONTRACT.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "alice".to_string(),
+                token_id: token_id.to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Unstake {})
+        .unwrap();
+    assert_eq!(returned(res), transfer("7"));
+    let res = execute(deps.as_mut(), env, mock_info("alice", &[]), ExecuteMsg::Unstake {}).unwrap();
+    assert_eq!(returned(res), transfer("8"));
+}
+
+#[test]
+fn unstake_token_withdraws_one_token_once_it_has_matured() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("7")).unwrap();
+    env.block.time = env.block.time.plus_seconds(5 * 86_400);
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("8")).unwrap();
+    env.block.time = env.block.time.plus_seconds(3 * 86_400);
+
+    let unstake = |token_id: &str| ExecuteMsg::UnstakeToken {
+        collection: None,
+        token_id: token_id.to_string(),
+    };
+    // Token 8 has only been staked for 3 days
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), unstake("8"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinStakePeriodNotMet {
+            remaining_secs: 4 * 86_400,
+        }
+    );
+    let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), unstake("7"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TokenNotStaked {
+            token_id: "7".to_string(),
+        }
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), unstake("7")).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: NFT_CONTRACT.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "alice".to_string(),
+                token_id: "7".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::GetStaker {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.unwrap().nft_count, 1);
+}
+
+#[test]
+fn staked_tokens_are_listed_with_their_own_maturity() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("7")).unwrap();
+    let first_staked = env.block.time;
+    env.block.time = env.block.time.plus_seconds(5 * 86_400);
+    for token_id in ["8", "9"] {
+        execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft(token_id)).unwrap();
+    }
+    env.block.time = env.block.time.plus_seconds(3 * 86_400);
+
+    let list = |start_after: Option<(String, String)>| -> Vec<StakedToken> {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetStakedTokens {
+                    address: "alice".to_string(),
+                    start_after,
+                    limit: Some(2),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let page = list(None);
+    assert_eq!(
+        page[0],
+        StakedToken {
+            collection: Addr::unchecked(NFT_CONTRACT),
+            token_id: "7".to_string(),
+            staked_at: first_staked,
+            weight: 1,
+            eligible: true,
+            lock: None,
+        }
+    );
+    assert_eq!(page[1].token_id, "8");
+    assert!(!page[1].eligible);
+    let page = list(Some((NFT_CONTRACT.to_string(), "8".to_string())));
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].token_id, "9");
+
+    let proof: EligibilityProof = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::ProveEligibility {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(proof.staked_nfts, vec!["7", "8", "9"]);
+    assert_eq!(proof.individual_token_timestamps[0], first_staked);
+}
+
+#[test]
+fn each_token_only_counts_once_it_has_matured() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("7")).unwrap();
+    env.block.time = env.block.time.plus_seconds(5 * 86_400);
+    execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft("8")).unwrap();
+
+    let tickets = |deps: Deps, env: &Env| -> u64 {
+        let res: TicketsResponse = from_json(
+            query(
+                deps,
+                env.clone(),
+                QueryMsg::GetTickets {
+                    address: "alice".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.tickets
+    };
+    // Token 8 joining doesn't hold back token 7, nor does it ride on its maturity
+    env.block.time = env.block.time.plus_seconds(3 * 86_400);
+    assert_eq!(tickets(deps.as_ref(), &env), 1);
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Unstake {})
+        .unwrap_err();
+    assert!(matches!(err, ContractError::MinStakePeriodNotMet { .. }));
+
+    env.block.time = env.block.time.plus_seconds(4 * 86_400);
+    assert_eq!(tickets(deps.as_ref(), &env), 2);
+}
+
+#[test]
+fn unstaked_tokens_are_returned_after_unbonding() {
+    let fixture = TestFixture::new().configure(|msg| msg.unbonding_seconds = Some(3 * 86_400));
+    let (mut deps, mut env, _) = fixture.build();
+
+    for token_id in ["7", "8"] {
+        execute(deps.as_mut(), env.clone(), mock_info(NFT_CONTRACT, &[]), send_nft(token_id)).unwrap();
+    }
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    let unstaked_at = env.block.time;
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Unstake {})
+        .unwrap();
+    assert!(res.messages.is_empty());
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStaker {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.unwrap().nft_count, 1);
+    let claims: Vec<UnbondingNft> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetUnbondingClaims {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        claims,
+        vec![UnbondingNft {
+            collection: Addr::unchecked(NFT_CONTRACT),
+            token_id: "8".to_string(),
+            release_at: unstaked_at.plus_seconds(3 * 86_400),
+        }]
+    );
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::ClaimUnstaked {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    env.block.time = env.block.time.plus_seconds(3 * 86_400);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::ClaimUnstaked {})
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: NFT_CONTRACT.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "alice".to_string(),
+                token_id: "8".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    let err = execute(deps.as_mut(), env, mock_info("alice", &[]), ExecuteMsg::ClaimUnstaked {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+}
+
+#[test]
+fn stake_batch_pulls_every_token_in_one_response() {
+    let fixture = TestFixture::new();
+    let (mut deps, mut env, _) = fixture.build();
+    // Alice owns tokens "1" to "3", bob owns "4"
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == NFT_CONTRACT => {
+            let Cw721QueryMsg::OwnerOf { token_id, .. } = from_json(msg).unwrap() else {
+                panic!("unexpected query");
+            };
+            let owner = if token_id == "4" { "bob" } else { "alice" };
+            let response = OwnerOfResponse {
+                owner: owner.to_string(),
+                approvals: vec![],
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&response).unwrap()))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+    let stake_batch = |token_ids: &[&str]| ExecuteMsg::StakeBatch {
+        collection: None,
+        token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+        lock_duration: None,
+    };
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), stake_batch(&[]))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBatchSize {});
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), stake_batch(&["1", "4"]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotTokenOwner {
+            token_id: "4".to_string()
+        }
+    );
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), stake_batch(&["1", "1"]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotTokenOwner {
+            token_id: "1".to_string()
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        stake_batch(&["1", "2", "3"]),
+    )
+    .unwrap();
+    let pulled: Vec<CosmosMsg> = res.messages.into_iter().map(|sub| sub.msg).collect();
+    let expected: Vec<CosmosMsg> = ["1", "2", "3"]
+        .iter()
+        .map(|token_id| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: NFT_CONTRACT.to_string(),
+                msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: env.contract.address.to_string(),
+                    token_id: token_id.to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        })
+        .collect();
+    assert_eq!(pulled, expected);
+    let staker: Option<Staker> = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStaker {
+                address: "alice".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.unwrap().nft_count, 3);
+
+    // All three mature together and come back in one response
+    env.block.time = env.block.time.plus_seconds(8 * 86_400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("alice", &[]),
+        ExecuteMsg::UnstakeBatch {
+            collection: None,
+            token_ids: vec!["1".to_string(), "3".to_string()],
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+}
+
+#[test]
+fn approved_tokens_are_staked_once_their_pull_succeeds() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+    // Alice owns every token and has approved all but "3"
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == NFT_CONTRACT => {
+            let result = match from_json(msg).unwrap() {
+                Cw721QueryMsg::OwnerOf { .. } => to_json_binary(&OwnerOfResponse {
+                    owner: "alice".to_string(),
+                    approvals: vec![],
+                }),
+                Cw721QueryMsg::Approval { token_id, spender, .. } if token_id != "3" => {
+                    to_json_binary(&ApprovalResponse {
+                        approval: Approval {
+                            spender,
+                            expires: Expiration::Never {},
+                        },
+                    })
+                }
+                _ => {
+                    let error = "approval not found".to_string();
+                    return SystemResult::Ok(ContractResult::Err(error));
+                }
+            };
+            SystemResult::Ok(ContractResult::Ok(result.unwrap()))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+    let stake = |token_ids: &[&str]| ExecuteMsg::Stake {
+        token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+        lock_duration: None,
+    };
+    let pulled = Reply {
+        id: PULL_REPLY_ID,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let staker = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| -> Option<Staker> {
+        from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetStaker {
+                    address: "alice".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), stake(&["1", "3"]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotApproved {
+            token_id: "3".to_string()
+        }
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), stake(&["1", "2"]))
+        .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert!(res.messages.iter().all(|sub| sub.id == PULL_REPLY_ID));
+    // Nothing counts until the transfers have gone through
+    assert_eq!(staker(&deps), None);
+
+    reply(deps.as_mut(), env.clone(), pulled.clone()).unwrap();
+    assert_eq!(staker(&deps).unwrap().nft_count, 1);
+    let res = reply(deps.as_mut(), env.clone(), pulled).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "token_id" && attr.value == "2"));
+    assert_eq!(staker(&deps).unwrap().nft_count, 2);
+}
+
+#[test]
+fn stakes_without_token_ids_need_wallet_stakes_enabled() {
+    let fixture = TestFixture::new().configure(|msg| msg.allow_wallet_stakes = false);
+    let (mut deps, env, _) = fixture.build();
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("alice", &[]),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WalletStakesDisabled {});
+}
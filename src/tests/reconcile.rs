@@ -0,0 +1,68 @@
+use cosmwasm_std::{from_json, Response};
+use cw_storage_plus::Item;
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, QueryMsg};
+use nft_staking_lottery::state::State;
+use nft_staking_lottery::testing::TestFixture;
+
+// Direct handle on the state Item so the test can corrupt the counter
+const STATE: Item<State> = Item::new("state");
+
+fn attribute(res: &Response, key: &str) -> String {
+    res.attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .unwrap()
+        .value
+        .clone()
+}
+
+#[test]
+fn recalculate_total_staked_restores_a_diverged_counter_in_batches() {
+    let fixture = TestFixture::new().with_stakers(5);
+    let (mut deps, env, _) = fixture.build();
+
+    let mut state = STATE.load(&deps.storage).unwrap();
+    state.total_staked = 42;
+    STATE.save(&mut deps.storage, &state).unwrap();
+
+    let mut start_after = None;
+    loop {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            fixture.as_admin(),
+            ExecuteMsg::RecalculateTotalStaked {
+                start_after: start_after.clone(),
+                batch_size: 2,
+            },
+        )
+        .unwrap();
+        if attribute(&res, "is_complete") == "true" {
+            assert_eq!(attribute(&res, "progress"), "5");
+            break;
+        }
+        start_after = Some(attribute(&res, "last_key"));
+    }
+
+    let state: State =
+        from_json(query(deps.as_ref(), env, QueryMsg::GetState {}).unwrap()).unwrap();
+    assert_eq!(state.total_staked, 5);
+}
+
+#[test]
+fn recalculate_total_staked_rejects_an_out_of_order_batch() {
+    let fixture = TestFixture::new().with_stakers(3);
+    let (mut deps, env, _) = fixture.build();
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        fixture.as_admin(),
+        ExecuteMsg::RecalculateTotalStaked {
+            start_after: Some("staker1".to_string()),
+            batch_size: 1,
+        },
+    );
+    assert!(res.is_err());
+}
@@ -0,0 +1,378 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, HexBinary, MessageInfo, Response,
+    StdError, StdResult, Uint128,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg, OwnerOfResponse};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Map;
+use nft_staking_lottery::contract::{execute, instantiate, query, reply};
+use nft_staking_lottery::msg::{
+    ExecuteMsg, InstantiateMsg, NoisCallback, NoisProxyExecuteMsg, QueryMsg, ReceiveMsg,
+};
+use nft_staking_lottery::state::{PotSplit, StakeEvent, StakeEventType, State};
+use nft_staking_lottery::ContractError;
+
+const ADMIN: &str = "admin";
+const ALICE: &str = "alice";
+const BOB: &str = "bob";
+
+fn lottery_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply))
+}
+
+fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+// Just enough of a cw721 for the lottery: tokens minted at instantiation as
+// (token_id, owner) pairs, TransferNft, SendNft, ApproveAll and OwnerOf
+const NFT_OWNERS: Map<&str, String> = Map::new("owners");
+const NFT_OPERATORS: Map<(&str, &str), bool> = Map::new("operators");
+
+fn mock_nft_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    tokens: Vec<(String, String)>,
+) -> StdResult<Response> {
+    for (token_id, owner) in tokens {
+        NFT_OWNERS.save(deps.storage, &token_id, &owner)?;
+    }
+    Ok(Response::new())
+}
+
+fn move_nft(deps: DepsMut, sender: &Addr, token_id: &str, new_owner: &str) -> StdResult<()> {
+    let owner = NFT_OWNERS.load(deps.storage, token_id)?;
+    if owner != sender.as_str() && !NFT_OPERATORS.has(deps.storage, (&owner, sender.as_str())) {
+        return Err(StdError::generic_err("not the owner"));
+    }
+    NFT_OWNERS.save(deps.storage, token_id, &new_owner.to_string())
+}
+
+fn mock_nft_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: Cw721ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        Cw721ExecuteMsg::TransferNft { recipient, token_id } => {
+            move_nft(deps, &info.sender, &token_id, &recipient)?;
+            Ok(Response::new())
+        }
+        Cw721ExecuteMsg::SendNft { contract, token_id, msg } => {
+            move_nft(deps, &info.sender, &token_id, &contract)?;
+            let receive = Cw721ReceiveMsg {
+                sender: info.sender.to_string(),
+                token_id,
+                msg,
+            };
+            Ok(Response::new().add_message(receive.into_cosmos_msg(contract)?))
+        }
+        Cw721ExecuteMsg::ApproveAll { operator, .. } => {
+            NFT_OPERATORS.save(deps.storage, (info.sender.as_str(), &operator), &true)?;
+            Ok(Response::new())
+        }
+        _ => Err(StdError::generic_err("not supported by the mock")),
+    }
+}
+
+fn mock_nft_query(deps: Deps, _env: Env, msg: Cw721QueryMsg) -> StdResult<Binary> {
+    match msg {
+        Cw721QueryMsg::OwnerOf { token_id, .. } => to_json_binary(&OwnerOfResponse {
+            owner: NFT_OWNERS.load(deps.storage, &token_id)?,
+            approvals: vec![],
+        }),
+        _ => Err(StdError::generic_err("not supported by the mock")),
+    }
+}
+
+fn nft_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(mock_nft_execute, mock_nft_instantiate, mock_nft_query))
+}
+
+// Nois proxy that takes randomness requests; the test delivers the randomness itself
+fn mock_nois_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: NoisProxyExecuteMsg,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_nois_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_nois_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    Err(StdError::generic_err("not supported by the mock"))
+}
+
+fn nois_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(mock_nois_execute, mock_nois_instantiate, mock_nois_query))
+}
+
+fn nft_owner(app: &App, collection: &Addr, token_id: &str) -> String {
+    let response: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            collection,
+            &Cw721QueryMsg::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    response.owner
+}
+
+fn cw20_balance(app: &App, token: &Addr, address: &Addr) -> Uint128 {
+    let response: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    response.balance
+}
+
+// Instantiate, fund, stake, draw and unstake against a real cw20 token and a mock cw721
+#[test]
+fn lottery_lifecycle() {
+    let mut app = App::default();
+    let admin = Addr::unchecked(ADMIN);
+    let alice = Addr::unchecked(ALICE);
+    let bob = Addr::unchecked(BOB);
+
+    let cw20_code = app.store_code(cw20_contract());
+    let token = app
+        .instantiate_contract(
+            cw20_code,
+            admin.clone(),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Reward".to_string(),
+                symbol: "RWD".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: ADMIN.to_string(),
+                    amount: Uint128::new(10_000),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "reward",
+            None,
+        )
+        .unwrap();
+
+    let nft_code = app.store_code(nft_contract());
+    let collection = app
+        .instantiate_contract(
+            nft_code,
+            admin.clone(),
+            &vec![
+                ("1".to_string(), ALICE.to_string()),
+                ("2".to_string(), ALICE.to_string()),
+                ("3".to_string(), ALICE.to_string()),
+            ],
+            &[],
+            "collection",
+            None,
+        )
+        .unwrap();
+
+    let nois_code = app.store_code(nois_contract());
+    let nois_proxy = app
+        .instantiate_contract(nois_code, admin.clone(), &Empty {}, &[], "nois", None)
+        .unwrap();
+
+    let lottery_code = app.store_code(lottery_contract());
+    let lottery = app
+        .instantiate_contract(
+            lottery_code,
+            admin.clone(),
+            &InstantiateMsg {
+                admin: ADMIN.to_string(),
+                nft_contract: collection.to_string(),
+                reward_token: token.to_string(),
+                activity_timeout_seconds: None,
+                weight_decay_rate_bps_per_week: 0,
+                min_draw_interval_seconds: None,
+                max_draw_interval_seconds: None,
+                badge_nft_contract: None,
+                target_pot_size: None,
+                accelerated_cooldown_seconds: None,
+                abandonment_timeout_seconds: None,
+                max_win_probability_bps: 0,
+                stake_receipt_contract: None,
+                min_staking_period: None,
+                duration_weighting: None,
+                nois_proxy: Some(nois_proxy.to_string()),
+                drand: None,
+                commit_reveal: None,
+                claim_window_seconds: None,
+                prize_split_bps: vec![],
+                unbonding_seconds: None,
+                early_unstake_penalty: None,
+                keeper_reward_bps: 0,
+                croncat: None,
+                burn_multiplier: 0,
+                sacrifice_bps: 0,
+                allow_wallet_stakes: false,
+                lock_tiers: vec![],
+                winner_cooldown_rounds: 0,
+                max_nfts_per_staker: 0,
+                max_entries_per_round: 0,
+                stake_fee: None,
+                protocol_fee_bps: 0,
+                treasury: None,
+                pot_split: PotSplit::default(),
+                jackpot: None,
+                emission_per_second: Uint128::zero(),
+                compound_rate: Uint128::zero(),
+                push_payouts: false,
+            },
+            &[],
+            "lottery",
+            None,
+        )
+        .unwrap();
+
+    // Admin funds the pot
+    app.execute_contract(
+        admin.clone(),
+        token.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: lottery.to_string(),
+            amount: Uint128::new(1_000),
+            msg: to_json_binary(&ReceiveMsg::FundPot {}).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &lottery), Uint128::new(1_000));
+
+    // Alice stakes her NFT and waits out the minimum period
+    app.execute_contract(
+        alice.clone(),
+        collection.clone(),
+        &Cw721ExecuteMsg::SendNft {
+            contract: lottery.to_string(),
+            token_id: "1".to_string(),
+            msg: Binary::default(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(nft_owner(&app, &collection, "1"), lottery.to_string());
+
+    // and lets the lottery pull the other two in one batch
+    app.execute_contract(
+        alice.clone(),
+        collection.clone(),
+        &Cw721ExecuteMsg::ApproveAll {
+            operator: lottery.to_string(),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        alice.clone(),
+        lottery.clone(),
+        &ExecuteMsg::StakeBatch {
+            collection: None,
+            token_ids: vec!["2".to_string(), "3".to_string()],
+            lock_duration: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(nft_owner(&app, &collection, "3"), lottery.to_string());
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(8 * 86_400);
+        block.height += 8 * 14_400;
+    });
+
+    app.execute_contract(admin.clone(), lottery.clone(), &ExecuteMsg::DrawWinner {}, &[])
+        .unwrap();
+    let callback = ExecuteMsg::NoisReceive {
+        callback: NoisCallback {
+            job_id: "draw_1".to_string(),
+            published: app.block_info().time,
+            randomness: HexBinary::from([7u8; 32]),
+        },
+    };
+    app.execute_contract(nois_proxy, lottery.clone(), &callback, &[]).unwrap();
+    let state: State = app
+        .wrap()
+        .query_wasm_smart(&lottery, &QueryMsg::GetState {})
+        .unwrap();
+    assert_eq!(state.last_winner, Some(ALICE.to_string()));
+
+    let history: Vec<(u64, StakeEvent)> = app
+        .wrap()
+        .query_wasm_smart(
+            &lottery,
+            &QueryMsg::GetStakerHistory {
+                address: ALICE.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(history
+        .iter()
+        .any(|(_, event)| event.event_type == StakeEventType::WonDraw));
+
+    // Only the winner can claim
+    let err = app
+        .execute_contract(bob, lottery.clone(), &ExecuteMsg::ClaimReward {}, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotTheWinner {});
+
+    // The prize was escrowed at draw time and is paid out exactly once
+    app.execute_contract(alice.clone(), lottery.clone(), &ExecuteMsg::ClaimReward {}, &[])
+        .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &alice), Uint128::new(1_000));
+    assert_eq!(cw20_balance(&app, &token, &lottery), Uint128::zero());
+    let err = app
+        .execute_contract(alice.clone(), lottery.clone(), &ExecuteMsg::ClaimReward {}, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotTheWinner {});
+
+    app.execute_contract(
+        alice,
+        lottery.clone(),
+        &ExecuteMsg::UnstakeBatch {
+            collection: None,
+            token_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+    let state: State = app
+        .wrap()
+        .query_wasm_smart(&lottery, &QueryMsg::GetState {})
+        .unwrap();
+    assert_eq!(state.total_staked, 0);
+    assert_eq!(state.staker_count, 0);
+    for token_id in ["1", "2", "3"] {
+        assert_eq!(nft_owner(&app, &collection, token_id), ALICE.to_string());
+    }
+}
@@ -0,0 +1,196 @@
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_json, to_json_binary, Uint128};
+use cw20::Cw20ReceiveMsg;
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{ExecuteMsg, PendingRewardsResponse, QueryMsg, ReceiveMsg};
+use nft_staking_lottery::state::PrizeTier;
+use nft_staking_lottery::testing::{assert_pending_claim, draw, TestFixture, ADMIN, REWARD_TOKEN};
+use nft_staking_lottery::ContractError;
+
+fn tiers() -> Vec<PrizeTier> {
+    vec![
+        PrizeTier {
+            name: "runner_up".to_string(),
+            winners: 2,
+        },
+        PrizeTier {
+            name: "participation".to_string(),
+            winners: 5,
+        },
+    ]
+}
+
+fn fund_tier(tier: &str, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: ADMIN.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_json_binary(&ReceiveMsg::FundTier {
+            tier: tier.to_string(),
+        })
+        .unwrap(),
+    })
+}
+
+fn tier_winners(res: &cosmwasm_std::Response, tier: &str) -> Vec<String> {
+    res.attributes
+        .iter()
+        .find(|attr| attr.key == format!("tier_{}_winners", tier))
+        .unwrap()
+        .value
+        .split(',')
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn tiers_are_funded_and_drawn_separately_from_the_main_pot() {
+    let fixture = TestFixture::new()
+        .with_stakers(3)
+        .with_pot(Uint128::new(1_000))
+        .advance_days(8);
+    let (mut deps, env, _) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::SetPrizeTiers { tiers: tiers() },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        fund_tier("jackpot", 100),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownTier {
+            tier: "jackpot".to_string()
+        }
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        fund_tier("runner_up", 401),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        fund_tier("participation", 500),
+    )
+    .unwrap();
+
+    let res = draw(deps.as_mut(), env.clone(), fixture.as_admin()).unwrap();
+    let winner = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "winner")
+        .unwrap()
+        .value
+        .clone();
+    assert_pending_claim(&deps.as_ref(), &winner, Uint128::new(1_000));
+
+    let runners_up = tier_winners(&res, "runner_up");
+    assert_eq!(runners_up.len(), 2);
+    assert_ne!(runners_up[0], runners_up[1]);
+    // Only three stakers for five participation places
+    assert_eq!(tier_winners(&res, "participation").len(), 3);
+
+    let pots: Vec<(String, Uint128)> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetTierPots {}).unwrap()).unwrap();
+    assert_eq!(
+        pots,
+        vec![
+            ("runner_up".to_string(), Uint128::zero()),
+            ("participation".to_string(), Uint128::new(200)),
+        ]
+    );
+
+    let rewards: PendingRewardsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetPendingRewards {
+                address: runners_up[0].clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        rewards.tier_prizes,
+        vec![
+            ("runner_up".to_string(), Uint128::new(201)),
+            ("participation".to_string(), Uint128::new(100)),
+        ]
+    );
+    let won = if runners_up[0] == winner { 1_000 } else { 0 };
+    assert_eq!(rewards.prize.is_some(), won > 0);
+    assert_eq!(rewards.total, Uint128::new(301 + won));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&runners_up[0], &[]),
+        ExecuteMsg::ClaimTierPrize {
+            tier: "runner_up".to_string(),
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "amount" && attr.value == "201"));
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&runners_up[0], &[]),
+        ExecuteMsg::ClaimTierPrize {
+            tier: "runner_up".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotTheWinner {});
+}
+
+#[test]
+fn funded_tiers_cannot_be_removed() {
+    let fixture = TestFixture::new();
+    let (mut deps, env, _) = fixture.build();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        fixture.as_admin(),
+        ExecuteMsg::SetPrizeTiers { tiers: tiers() },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(REWARD_TOKEN, &[]),
+        fund_tier("participation", 50),
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        fixture.as_admin(),
+        ExecuteMsg::SetPrizeTiers {
+            tiers: tiers()[..1].to_vec(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TierNotEmpty {
+            tier: "participation".to_string()
+        }
+    );
+}
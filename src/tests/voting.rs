@@ -0,0 +1,122 @@
+use cosmwasm_std::{from_json, Addr, Deps, Env, Uint128};
+use nft_staking_lottery::contract::{execute, query};
+use nft_staking_lottery::msg::{
+    ExecuteMsg, InfoResponse, QueryMsg, TotalPowerAtHeightResponse, VotingPowerAtHeightResponse,
+};
+use nft_staking_lottery::testing::{staker_addr, TestFixture, ADMIN};
+
+fn voting_power(deps: Deps, env: &Env, address: &str, height: u64) -> Uint128 {
+    let res: VotingPowerAtHeightResponse = from_json(
+        query(
+            deps,
+            env.clone(),
+            QueryMsg::VotingPowerAtHeight {
+                address: address.to_string(),
+                height: Some(height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.power
+}
+
+fn total_power(deps: Deps, env: &Env, height: u64) -> Uint128 {
+    let res: TotalPowerAtHeightResponse = from_json(
+        query(deps, env.clone(), QueryMsg::TotalPowerAtHeight { height: Some(height) }).unwrap(),
+    )
+    .unwrap();
+    res.power
+}
+
+#[test]
+fn voting_power_is_one_per_staked_nft() {
+    let fixture = TestFixture::new().with_stakers(3);
+    let (mut deps, mut env, infos) = fixture.build();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    env.block.height += 1;
+
+    let res: VotingPowerAtHeightResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VotingPowerAtHeight {
+                address: staker_addr(0),
+                height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.power, Uint128::new(2));
+    assert_eq!(res.height, env.block.height);
+    assert_eq!(total_power(deps.as_ref(), &env, env.block.height), Uint128::new(4));
+
+    let info: InfoResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Info {}).unwrap()).unwrap();
+    assert_eq!(info.info.contract, "crates.io:burn-at-the-stake");
+    // The fixture instantiates as the admin
+    let dao: Addr = from_json(query(deps.as_ref(), env, QueryMsg::Dao {}).unwrap()).unwrap();
+    assert_eq!(dao, ADMIN);
+}
+
+#[test]
+fn power_at_a_past_height_ignores_later_changes() {
+    let fixture = TestFixture::new().with_stakers(2).advance_days(8);
+    let (mut deps, mut env, infos) = fixture.build();
+    let staked_height = env.block.height;
+
+    env.block.height += 10;
+    let changed_height = env.block.height;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        infos[0].clone(),
+        ExecuteMsg::Stake {
+            token_ids: vec![],
+            lock_duration: None,
+        },
+    )
+    .unwrap();
+    execute(deps.as_mut(), env.clone(), infos[1].clone(), ExecuteMsg::Unstake {}).unwrap();
+    env.block.height += 1;
+
+    // Nothing was staked before the fixture's block
+    assert_eq!(voting_power(deps.as_ref(), &env, &staker_addr(0), staked_height), Uint128::zero());
+    // Changes take effect from the block after they happen
+    for height in [staked_height + 1, changed_height] {
+        assert_eq!(voting_power(deps.as_ref(), &env, &staker_addr(0), height), Uint128::new(1));
+        assert_eq!(voting_power(deps.as_ref(), &env, &staker_addr(1), height), Uint128::new(1));
+        assert_eq!(total_power(deps.as_ref(), &env, height), Uint128::new(2));
+    }
+    let now = env.block.height;
+    assert_eq!(voting_power(deps.as_ref(), &env, &staker_addr(0), now), Uint128::new(2));
+    assert_eq!(voting_power(deps.as_ref(), &env, &staker_addr(1), now), Uint128::zero());
+    assert_eq!(total_power(deps.as_ref(), &env, now), Uint128::new(2));
+}
+
+#[test]
+fn voting_queries_match_the_dao_dao_wire_format() {
+    let msg: QueryMsg =
+        from_json(br#"{"voting_power_at_height":{"address":"staker0","height":12}}"#).unwrap();
+    assert_eq!(
+        msg,
+        QueryMsg::VotingPowerAtHeight {
+            address: "staker0".to_string(),
+            height: Some(12),
+        }
+    );
+    let msg: QueryMsg = from_json(br#"{"total_power_at_height":{}}"#).unwrap();
+    assert_eq!(msg, QueryMsg::TotalPowerAtHeight { height: None });
+    let msg: QueryMsg = from_json(br#"{"dao":{}}"#).unwrap();
+    assert_eq!(msg, QueryMsg::Dao {});
+}